@@ -0,0 +1,598 @@
+use blink_core::{
+    traits::{Event, Instrument},
+    types::MissionElapsedTime,
+};
+use chrono::prelude::*;
+use std::{fmt, str::FromStr, sync::Arc};
+use uom::si::f64::*;
+
+/// Reference count rate (all groups, per second) at a given time, e.g. the
+/// rate at the same geographic position on adjacent orbits.
+pub type ReferenceRate = Arc<dyn Fn(DateTime<Utc>) -> Option<f64> + Send + Sync>;
+
+/// Estimates the background of a bin tested by the search.
+pub trait BackgroundModel<I: Instrument> {
+    /// Expected background counts per group in the bin `[start, stop]`.
+    fn expected(&self, start: MissionElapsedTime<I>, stop: MissionElapsedTime<I>) -> Vec<f64>;
+}
+
+/// Which [`BackgroundModel`] the search builds over its events.
+#[derive(Clone)]
+pub enum Background {
+    /// Rate in a symmetric `neighbor` window around the bin, excluding the
+    /// `hollow` window that contains the bin itself.
+    SlidingWindow { neighbor: Time, hollow: Time },
+    /// Polynomial of `degree` fitted to the rate in the `neighbor` window
+    /// outside `hollow`; follows smooth trends such as SAA edges.
+    Polynomial {
+        neighbor: Time,
+        hollow: Time,
+        degree: usize,
+    },
+    /// Rate in a window of `length` ending `gap` before the bin; unaffected
+    /// by a rate change right after the bin, such as Earth occultation.
+    PreOnly { length: Time, gap: Time },
+    /// Reference rate at the bin, scaled to the counts of the whole span.
+    OrbitPhase(ReferenceRate),
+}
+
+impl fmt::Debug for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Background::SlidingWindow { neighbor, hollow } => f
+                .debug_struct("SlidingWindow")
+                .field("neighbor", neighbor)
+                .field("hollow", hollow)
+                .finish(),
+            Background::Polynomial {
+                neighbor,
+                hollow,
+                degree,
+            } => f
+                .debug_struct("Polynomial")
+                .field("neighbor", neighbor)
+                .field("hollow", hollow)
+                .field("degree", degree)
+                .finish(),
+            Background::PreOnly { length, gap } => f
+                .debug_struct("PreOnly")
+                .field("length", length)
+                .field("gap", gap)
+                .finish(),
+            // 参考率是闭包，无法打印
+            Background::OrbitPhase(_) => f.write_str("OrbitPhase(..)"),
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::SlidingWindow {
+            neighbor: Time::new::<uom::si::time::second>(1.0),
+            hollow: Time::new::<uom::si::time::millisecond>(10.0),
+        }
+    }
+}
+
+impl Background {
+    /// Duration of data the estimate of one bin is drawn from, for its
+    /// Poisson error; `None` for the orbit phase, which uses the whole span.
+    pub fn window(&self) -> Option<Time> {
+        match self {
+            Background::SlidingWindow { neighbor, hollow }
+            | Background::Polynomial {
+                neighbor, hollow, ..
+            } => Some(*neighbor - *hollow),
+            Background::PreOnly { length, .. } => Some(*length),
+            Background::OrbitPhase(_) => None,
+        }
+    }
+
+    /// Error of the background `mean` expected in a bin of `bin`.
+    pub fn error(&self, mean: f64, bin: Time) -> f64 {
+        match self.window() {
+            Some(window) if seconds(window) > 0.0 => (mean * seconds(bin) / seconds(window)).sqrt(),
+            _ => 0.0,
+        }
+    }
+
+    /// Builds the model over `data`, only using events in `[start, stop)`.
+    pub fn model<E: Event>(
+        &self,
+        data: &[E],
+        group_number: usize,
+        start: MissionElapsedTime<E::Instrument>,
+        stop: MissionElapsedTime<E::Instrument>,
+    ) -> Box<dyn BackgroundModel<E::Instrument> + Send + Sync> {
+        let events = GroupTimes::new(data, group_number);
+        let span = [start, stop];
+        match self {
+            Background::SlidingWindow { neighbor, hollow } => Box::new(SlidingWindow {
+                events,
+                span,
+                neighbor: *neighbor,
+                hollow: *hollow,
+            }),
+            Background::Polynomial {
+                neighbor,
+                hollow,
+                degree,
+            } => Box::new(Polynomial {
+                window: SlidingWindow {
+                    events,
+                    span,
+                    neighbor: *neighbor,
+                    hollow: *hollow,
+                },
+                degree: *degree,
+            }),
+            Background::PreOnly { length, gap } => Box::new(PreOnly {
+                events,
+                span,
+                length: *length,
+                gap: *gap,
+            }),
+            Background::OrbitPhase(rate) => Box::new(OrbitPhase::new(events, span, rate.clone())),
+        }
+    }
+}
+
+/// A [`Background`] named in configuration, before its windows are chosen.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BackgroundKind {
+    #[default]
+    SlidingWindow,
+    Polynomial,
+    PreOnly,
+    OrbitPhase,
+}
+
+impl FromStr for BackgroundKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "sliding-window" => Ok(BackgroundKind::SlidingWindow),
+            "polynomial" => Ok(BackgroundKind::Polynomial),
+            "pre-only" => Ok(BackgroundKind::PreOnly),
+            "orbit-phase" => Ok(BackgroundKind::OrbitPhase),
+            _ => Err(format!(
+                "expected sliding-window, polynomial, pre-only or orbit-phase, got '{s}'"
+            )),
+        }
+    }
+}
+
+impl BackgroundKind {
+    /// The model over a `neighbor` window around the bin less its `hollow`:
+    /// a quadratic for `Polynomial`, the half before the bin for `PreOnly`.
+    /// `OrbitPhase` needs the reference `rate`, without which this is `None`.
+    pub fn build(
+        self,
+        neighbor: Time,
+        hollow: Time,
+        rate: Option<ReferenceRate>,
+    ) -> Option<Background> {
+        match self {
+            BackgroundKind::SlidingWindow => Some(Background::SlidingWindow { neighbor, hollow }),
+            BackgroundKind::Polynomial => Some(Background::Polynomial {
+                neighbor,
+                hollow,
+                degree: 2,
+            }),
+            BackgroundKind::PreOnly => Some(Background::PreOnly {
+                length: (neighbor - hollow) / 2.0,
+                gap: hollow / 2.0,
+            }),
+            BackgroundKind::OrbitPhase => rate.map(Background::OrbitPhase),
+        }
+    }
+}
+
+/// Event times split by group, for counting events in a window.
+struct GroupTimes<I: Instrument> {
+    times: Vec<Vec<MissionElapsedTime<I>>>,
+}
+
+impl<I: Instrument> GroupTimes<I> {
+    fn new<E: Event<Instrument = I>>(data: &[E], group_number: usize) -> Self {
+        let mut times = vec![Vec::new(); group_number];
+        for event in data {
+            times[event.group() as usize].push(event.time());
+        }
+        Self { times }
+    }
+
+    /// Events of `group` in `[from, to)`.
+    fn count(&self, group: usize, from: MissionElapsedTime<I>, to: MissionElapsedTime<I>) -> f64 {
+        let times = &self.times[group];
+        let from = times.partition_point(|&t| t < from);
+        let to = times.partition_point(|&t| t < to);
+        to.saturating_sub(from) as f64
+    }
+
+    fn groups(&self) -> usize {
+        self.times.len()
+    }
+}
+
+fn seconds(duration: Time) -> f64 {
+    duration.get::<uom::si::time::second>()
+}
+
+pub struct SlidingWindow<I: Instrument> {
+    events: GroupTimes<I>,
+    span: [MissionElapsedTime<I>; 2],
+    neighbor: Time,
+    hollow: Time,
+}
+
+impl<I: Instrument> SlidingWindow<I> {
+    /// Neighbour and hollow windows around the bin, clamped to the span.
+    fn windows(
+        &self,
+        start: MissionElapsedTime<I>,
+        stop: MissionElapsedTime<I>,
+    ) -> [[MissionElapsedTime<I>; 2]; 2] {
+        let clamp = |from: MissionElapsedTime<I>, to: MissionElapsedTime<I>| {
+            [from.max(self.span[0]), to.min(self.span[1])]
+        };
+        [
+            clamp(start - self.neighbor / 2.0, stop + self.neighbor / 2.0),
+            clamp(start - self.hollow / 2.0, stop + self.hollow / 2.0),
+        ]
+    }
+}
+
+impl<I: Instrument> BackgroundModel<I> for SlidingWindow<I> {
+    fn expected(&self, start: MissionElapsedTime<I>, stop: MissionElapsedTime<I>) -> Vec<f64> {
+        let [mean, hollow] = self.windows(start, stop);
+        let pure_duration = (mean[1] - mean[0]) - (hollow[1] - hollow[0]);
+        if seconds(pure_duration) <= 0.0 {
+            return vec![0.0; self.events.groups()];
+        }
+        let percent = ((stop - start) / pure_duration).get::<uom::si::ratio::ratio>();
+        (0..self.events.groups())
+            .map(|group| {
+                let pure = self.events.count(group, mean[0], mean[1])
+                    - self.events.count(group, hollow[0], hollow[1]);
+                pure * percent
+            })
+            .collect()
+    }
+}
+
+pub struct Polynomial<I: Instrument> {
+    window: SlidingWindow<I>,
+    degree: usize,
+}
+
+impl<I: Instrument> Polynomial<I> {
+    /// 拟合用的子区间数
+    const BINS: usize = 20;
+}
+
+impl<I: Instrument> BackgroundModel<I> for Polynomial<I> {
+    fn expected(&self, start: MissionElapsedTime<I>, stop: MissionElapsedTime<I>) -> Vec<f64> {
+        let [mean, hollow] = self.window.windows(start, stop);
+        let width = (mean[1] - mean[0]) / Self::BINS as f64;
+        let center = start + (stop - start) / 2.0;
+        // 以邻域半宽为单位，保证法方程条件数
+        let scale = seconds(self.window.neighbor / 2.0);
+        let bins = (0..Self::BINS)
+            .map(|i| {
+                let from = mean[0] + width * i as f64;
+                [from, from + width]
+            })
+            .filter(|[from, to]| *to <= hollow[0] || *from >= hollow[1])
+            .collect::<Vec<_>>();
+        if bins.len() <= self.degree || seconds(width) <= 0.0 {
+            return self.window.expected(start, stop);
+        }
+
+        let half = seconds(stop - start) / 2.0 / scale;
+        (0..self.window.events.groups())
+            .map(|group| {
+                let points = bins
+                    .iter()
+                    .map(|&[from, to]| {
+                        let x = seconds(from + width / 2.0 - center) / scale;
+                        let y = self.window.events.count(group, from, to) / seconds(width);
+                        (x, y)
+                    })
+                    .collect::<Vec<_>>();
+                let coefficients = fit_polynomial(&points, self.degree);
+                let integral = coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(k, c)| {
+                        let k = k as i32 + 1;
+                        c * (half.powi(k) - (-half).powi(k)) / k as f64
+                    })
+                    .sum::<f64>();
+                (integral * scale).max(0.0)
+            })
+            .collect()
+    }
+}
+
+/// Least-squares coefficients `c[k]` of `y = Σ c[k] x^k`.
+fn fit_polynomial(points: &[(f64, f64)], degree: usize) -> Vec<f64> {
+    let n = degree + 1;
+    // 法方程增广矩阵 [AᵀA | Aᵀy]
+    let mut matrix = vec![vec![0.0; n + 1]; n];
+    for &(x, y) in points {
+        for (row, line) in matrix.iter_mut().enumerate() {
+            for (col, value) in line[..n].iter_mut().enumerate() {
+                *value += x.powi((row + col) as i32);
+            }
+            line[n] += y * x.powi(row as i32);
+        }
+    }
+    // Gauss-Jordan 消元，列主元
+    for pivot in 0..n {
+        let best = (pivot..n)
+            .max_by(|&a, &b| matrix[a][pivot].abs().total_cmp(&matrix[b][pivot].abs()))
+            .unwrap();
+        matrix.swap(pivot, best);
+        if matrix[pivot][pivot].abs() < f64::EPSILON {
+            continue;
+        }
+        let pivot_line = matrix[pivot].clone();
+        for (row, line) in matrix.iter_mut().enumerate() {
+            if row != pivot {
+                let factor = line[pivot] / pivot_line[pivot];
+                for (value, p) in line.iter_mut().zip(&pivot_line).skip(pivot) {
+                    *value -= factor * p;
+                }
+            }
+        }
+    }
+    matrix
+        .iter()
+        .enumerate()
+        .map(|(k, line)| {
+            if line[k].abs() < f64::EPSILON {
+                0.0
+            } else {
+                line[n] / line[k]
+            }
+        })
+        .collect()
+}
+
+pub struct PreOnly<I: Instrument> {
+    events: GroupTimes<I>,
+    span: [MissionElapsedTime<I>; 2],
+    length: Time,
+    gap: Time,
+}
+
+impl<I: Instrument> BackgroundModel<I> for PreOnly<I> {
+    fn expected(&self, start: MissionElapsedTime<I>, stop: MissionElapsedTime<I>) -> Vec<f64> {
+        let to = (start - self.gap).max(self.span[0]);
+        let from = (to - self.length).max(self.span[0]);
+        let window = seconds(to - from);
+        if window <= 0.0 {
+            return vec![0.0; self.events.groups()];
+        }
+        let duration = seconds(stop - start);
+        (0..self.events.groups())
+            .map(|group| self.events.count(group, from, to) / window * duration)
+            .collect()
+    }
+}
+
+pub struct OrbitPhase<I: Instrument> {
+    rate: ReferenceRate,
+    /// Events per second of each group, averaged over the span
+    average: Vec<f64>,
+    /// Observed over predicted counts of each group
+    scale: Option<Vec<f64>>,
+    _phantom: std::marker::PhantomData<I>,
+}
+
+impl<I: Instrument> OrbitPhase<I> {
+    fn new(events: GroupTimes<I>, span: [MissionElapsedTime<I>; 2], rate: ReferenceRate) -> Self {
+        let counts = (0..events.groups())
+            .map(|group| events.count(group, span[0], span[1]))
+            .collect::<Vec<_>>();
+        let duration = seconds(span[1] - span[0]);
+        let average = counts
+            .iter()
+            .map(|count| count / duration.max(f64::EPSILON))
+            .collect();
+        // 参考率按 1 s 采样积分，得到整段的预期计数用于归一化
+        let predicted = (0..duration.floor() as i64)
+            .filter_map(|second| {
+                let time = span[0] + Time::new::<uom::si::time::second>(second as f64 + 0.5);
+                rate(time.to_utc())
+            })
+            .sum::<f64>();
+        let scale = (predicted > 0.0).then(|| counts.iter().map(|c| c / predicted).collect());
+        Self {
+            rate,
+            average,
+            scale,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<I: Instrument> BackgroundModel<I> for OrbitPhase<I> {
+    fn expected(&self, start: MissionElapsedTime<I>, stop: MissionElapsedTime<I>) -> Vec<f64> {
+        let duration = seconds(stop - start);
+        let middle = start + (stop - start) / 2.0;
+        match (&self.scale, (self.rate)(middle.to_utc())) {
+            (Some(scale), Some(rate)) => scale.iter().map(|s| s * rate * duration).collect(),
+            // 参考率缺失时退化为整段平均
+            _ => self.average.iter().map(|a| a * duration).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestEvent, TestInstrument};
+
+    #[test]
+    fn polynomial_fit_recovers_line() {
+        let points = (0..10)
+            .map(|i| {
+                let x = i as f64 / 5.0 - 1.0;
+                (x, 3.0 + 2.0 * x)
+            })
+            .collect::<Vec<_>>();
+        let coefficients = fit_polynomial(&points, 1);
+        assert!((coefficients[0] - 3.0).abs() < 1e-9);
+        assert!((coefficients[1] - 2.0).abs() < 1e-9);
+    }
+
+    fn span() -> [MissionElapsedTime<TestInstrument>; 2] {
+        [MissionElapsedTime::new(0.0), MissionElapsedTime::new(20.0)]
+    }
+
+    fn bin(from: f64, to: f64) -> [MissionElapsedTime<TestInstrument>; 2] {
+        [MissionElapsedTime::new(from), MissionElapsedTime::new(to)]
+    }
+
+    /// Events of `group` at rate `a + b t`, placed where the integrated
+    /// rate crosses each integer.
+    fn ramp(a: f64, b: f64, group: u8) -> Vec<TestEvent> {
+        (1..)
+            .map(|k| {
+                let k = k as f64;
+                if b == 0.0 {
+                    k / a
+                } else {
+                    (-a + (a * a + 2.0 * b * k).sqrt()) / b
+                }
+            })
+            .take_while(|&t| t < 20.0)
+            .map(|t| TestEvent::new(t, group))
+            .collect()
+    }
+
+    fn sorted(mut events: Vec<TestEvent>) -> Vec<TestEvent> {
+        events.sort_by_key(|event| event.time());
+        events
+    }
+
+    #[test]
+    fn polynomial_follows_a_curved_rate() {
+        // 组 0 计数率 r = 200 + 100 (t - 10)²/4 在 t = 10 处取极小，滑动窗口会高估
+        let events = sorted(
+            (1..)
+                .scan(0.0, |t: &mut f64, _| {
+                    *t += 1.0 / (200.0 + 25.0 * (*t - 10.0).powi(2));
+                    Some(*t)
+                })
+                .take_while(|&t| t < 20.0)
+                .map(|t| TestEvent::new(t, 0))
+                .chain(ramp(50.0, 0.0, 1))
+                .collect(),
+        );
+        let [start, stop] = bin(9.95, 10.05);
+        let neighbor = Time::new::<uom::si::time::second>(4.0);
+        let hollow = Time::new::<uom::si::time::second>(0.5);
+        let [from, to] = span();
+
+        let polynomial = Background::Polynomial {
+            neighbor,
+            hollow,
+            degree: 2,
+        }
+        .model(&events, 2, from, to)
+        .expected(start, stop);
+        let sliding = Background::SlidingWindow { neighbor, hollow }
+            .model(&events, 2, from, to)
+            .expected(start, stop);
+        // 真值 0.1 s × 200/s = 20
+        assert!((polynomial[0] - 20.0).abs() < 1.0, "{polynomial:?}");
+        assert!(sliding[0] - 20.0 > 2.0, "{sliding:?}");
+        assert!((polynomial[1] - 5.0).abs() < 0.3, "{polynomial:?}");
+    }
+
+    #[test]
+    fn pre_only_ignores_a_step_after_the_bin() {
+        // 组 0 在 t = 10.5 s 后计数率从 100/s 跳到 1000/s
+        let events = sorted(
+            ramp(100.0, 0.0, 0)
+                .into_iter()
+                .filter(|event| event.time().met() < 10.5)
+                .chain(
+                    ramp(1000.0, 0.0, 0)
+                        .into_iter()
+                        .filter(|event| event.time().met() >= 10.5),
+                )
+                .chain(ramp(10.0, 0.0, 1))
+                .collect(),
+        );
+        let [start, stop] = bin(10.0, 10.1);
+        let [from, to] = span();
+        let model = Background::PreOnly {
+            length: Time::new::<uom::si::time::second>(2.0),
+            gap: Time::new::<uom::si::time::millisecond>(10.0),
+        }
+        .model(&events, 2, from, to);
+        let expected = model.expected(start, stop);
+        assert!((expected[0] - 10.0).abs() < 0.1, "{expected:?}");
+        assert!((expected[1] - 1.0).abs() < 0.1, "{expected:?}");
+
+        // 窗口截在数据起点
+        let early = model.expected(MissionElapsedTime::new(0.5), MissionElapsedTime::new(0.6));
+        assert!((early[0] - 10.0).abs() < 0.5, "{early:?}");
+    }
+
+    #[test]
+    fn orbit_phase_scales_the_reference() {
+        // 参考率后 10 s 是前 10 s 的 3 倍，实际计数同形而总量不同
+        let events = sorted(
+            ramp(50.0, 0.0, 0)
+                .into_iter()
+                .filter(|event| event.time().met() < 10.0)
+                .chain(
+                    ramp(150.0, 0.0, 0)
+                        .into_iter()
+                        .filter(|event| event.time().met() >= 10.0),
+                )
+                .collect(),
+        );
+        let middle = MissionElapsedTime::<TestInstrument>::new(10.0).to_utc();
+        let rate: ReferenceRate = Arc::new(move |time| Some(if time < middle { 1.0 } else { 3.0 }));
+        let [from, to] = span();
+        let model = Background::OrbitPhase(rate).model(&events, 2, from, to);
+
+        let [start, stop] = bin(5.0, 6.0);
+        let before = model.expected(start, stop);
+        let [start, stop] = bin(15.0, 16.0);
+        let after = model.expected(start, stop);
+        assert!((before[0] - 50.0).abs() < 0.5, "{before:?}");
+        assert!((after[0] - 150.0).abs() < 1.5, "{after:?}");
+        assert_eq!(before[1], 0.0);
+
+        // 无参考率时退化为整段平均
+        let flat: ReferenceRate = Arc::new(|_| None);
+        let model = Background::OrbitPhase(flat).model(&events, 2, from, to);
+        let average = model.expected(start, stop);
+        assert!((average[0] - 100.0).abs() < 1.0, "{average:?}");
+    }
+
+    #[test]
+    fn kinds_build_their_models() {
+        let neighbor = Time::new::<uom::si::time::second>(1.0);
+        let hollow = Time::new::<uom::si::time::millisecond>(10.0);
+        assert_eq!("pre-only".parse(), Ok(BackgroundKind::PreOnly));
+        assert!("median".parse::<BackgroundKind>().is_err());
+        assert!(matches!(
+            BackgroundKind::PreOnly.build(neighbor, hollow, None),
+            Some(Background::PreOnly { .. })
+        ));
+        assert!(
+            BackgroundKind::OrbitPhase
+                .build(neighbor, hollow, None)
+                .is_none()
+        );
+    }
+}
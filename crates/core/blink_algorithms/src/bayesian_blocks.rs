@@ -0,0 +1,215 @@
+//! Event-mode Bayesian Blocks (Scargle et al. 2013, ApJ 764, 167).
+
+use blink_core::{traits::Event, types::MissionElapsedTime};
+use uom::si::f64::*;
+
+/// False-positive prior recommended by Scargle et al. for event data.
+pub const DEFAULT_P0: f64 = 0.05;
+
+/// A constant-rate segment of an event list.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Block<T> {
+    pub start: T,
+    pub stop: T,
+    pub count: u32,
+    /// Events per second
+    pub rate: f64,
+}
+
+/// Prior on the number of change points for `n` events, calibrated so that
+/// a false change point is found with probability `p0` (eq. 21).
+pub fn ncp_prior(n: usize, p0: f64) -> f64 {
+    4.0 - (73.53 * p0 * (n as f64).powf(-0.478)).ln()
+}
+
+/// Segments the sorted MET seconds `times` within `[start, stop)` into
+/// blocks of constant rate.
+///
+/// Uses the PELT pruning of Killick et al. (2012), which is exact for this
+/// fitness and makes long, quiet stretches cost linear time.
+pub fn bayesian_blocks(times: &[f64], start: f64, stop: f64, p0: f64) -> Vec<Block<f64>> {
+    if start >= stop {
+        return Vec::new();
+    }
+
+    // 同一时刻的事例并为一个 cell
+    let mut cells: Vec<(f64, u32)> = Vec::new();
+    for &time in times.iter().filter(|&&t| start <= t && t < stop) {
+        match cells.last_mut() {
+            Some((last, count)) if *last == time => *count += 1,
+            _ => cells.push((time, 1)),
+        }
+    }
+    if cells.is_empty() {
+        return vec![Block {
+            start,
+            stop,
+            count: 0,
+            rate: 0.0,
+        }];
+    }
+
+    let n = cells.len();
+    let mut edges = Vec::with_capacity(n + 1);
+    edges.push(start);
+    edges.extend(cells.windows(2).map(|pair| (pair[0].0 + pair[1].0) / 2.0));
+    edges.push(stop);
+    let mut cumulative = Vec::with_capacity(n + 1);
+    cumulative.push(0u32);
+    for (_, count) in &cells {
+        cumulative.push(cumulative.last().unwrap() + count);
+    }
+
+    let prior = ncp_prior(cumulative[n] as usize, p0);
+    let fitness = |from: usize, to: usize| {
+        let count = (cumulative[to] - cumulative[from]) as f64;
+        count * (count.ln() - (edges[to] - edges[from]).ln())
+    };
+
+    // best[r]：前 r 个 cell 的最优分段适应度；last[r]：最后一段的起点
+    let mut best = vec![0.0; n + 1];
+    let mut last = vec![0; n + 1];
+    let mut candidates = vec![0];
+    for r in 1..=n {
+        let scores = candidates
+            .iter()
+            .map(|&from| best[from] + fitness(from, r))
+            .collect::<Vec<_>>();
+        let (index, max) = scores
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        best[r] = max - prior;
+        last[r] = candidates[index];
+        // 已落后超过一个 prior 的起点以后也不可能最优
+        candidates = candidates
+            .into_iter()
+            .zip(scores)
+            .filter(|&(_, score)| score > best[r])
+            .map(|(from, _)| from)
+            .collect();
+        candidates.push(r);
+    }
+
+    let mut change_points = vec![n];
+    let mut r = n;
+    while r > 0 {
+        r = last[r];
+        change_points.push(r);
+    }
+    change_points.reverse();
+
+    change_points
+        .windows(2)
+        .map(|pair| {
+            let count = cumulative[pair[1]] - cumulative[pair[0]];
+            let (start, stop) = (edges[pair[0]], edges[pair[1]]);
+            Block {
+                start,
+                stop,
+                count,
+                rate: count as f64 / (stop - start),
+            }
+        })
+        .collect()
+}
+
+/// [`bayesian_blocks`] over the events in `[start, stop)`, which must be
+/// sorted by time.
+pub fn bayesian_blocks_events<E: Event>(
+    events: &[E],
+    start: MissionElapsedTime<E::Instrument>,
+    stop: MissionElapsedTime<E::Instrument>,
+    p0: f64,
+) -> Vec<Block<MissionElapsedTime<E::Instrument>>> {
+    // 相对 start 的秒数，避免大 MET 损失精度
+    let seconds =
+        |time: MissionElapsedTime<E::Instrument>| (time - start).get::<uom::si::time::second>();
+    let times = events
+        .iter()
+        .map(|event| seconds(event.time()))
+        .collect::<Vec<_>>();
+    bayesian_blocks(&times, 0.0, seconds(stop), p0)
+        .into_iter()
+        .map(|block| Block {
+            start: start + Time::new::<uom::si::time::second>(block.start),
+            stop: start + Time::new::<uom::si::time::second>(block.stop),
+            count: block.count,
+            rate: block.rate,
+        })
+        .collect()
+}
+
+/// Start of the first and stop of the last block brighter than both outer
+/// blocks, which are taken as background. `None` without such a block.
+pub fn excess_span<T: Copy>(blocks: &[Block<T>]) -> Option<[T; 2]> {
+    let background = blocks.first()?.rate.max(blocks.last()?.rate);
+    let first = blocks.iter().find(|block| block.rate > background)?;
+    let last = blocks.iter().rev().find(|block| block.rate > background)?;
+    Some([first.start, last.stop])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestEvent, TestInstrument};
+
+    fn uniform(start: f64, stop: f64, rate: f64) -> Vec<f64> {
+        let n = ((stop - start) * rate) as usize;
+        (0..n).map(|i| start + (i as f64 + 0.5) / rate).collect()
+    }
+
+    #[test]
+    fn constant_rate_is_one_block() {
+        let times = uniform(0.0, 10.0, 50.0);
+        let blocks = bayesian_blocks(&times, 0.0, 10.0, DEFAULT_P0);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].count, 500);
+        assert!((blocks[0].rate - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finds_a_pulse() {
+        let mut times = uniform(0.0, 10.0, 20.0);
+        times.extend(uniform(10.0, 10.5, 400.0));
+        times.extend(uniform(10.5, 20.0, 20.0));
+        let blocks = bayesian_blocks(&times, 0.0, 20.0, DEFAULT_P0);
+        assert_eq!(blocks.len(), 3);
+        let [start, stop] = excess_span(&blocks).unwrap();
+        assert!((start - 10.0).abs() < 0.05, "{start}");
+        assert!((stop - 10.5).abs() < 0.05, "{stop}");
+        assert_eq!(
+            blocks.iter().map(|b| b.count).sum::<u32>(),
+            times.len() as u32
+        );
+    }
+
+    #[test]
+    fn events_far_from_the_epoch() {
+        // MET 约 3e8 s，块边界仍需精确到 ms 以下
+        let offset = 3.0e8;
+        let times = [
+            uniform(0.0, 1.0, 2000.0),
+            uniform(1.0, 1.001, 200_000.0),
+            uniform(1.001, 2.0, 2000.0),
+        ]
+        .concat();
+        let events = times
+            .iter()
+            .map(|&t| TestEvent::new(offset + t, 0))
+            .collect::<Vec<_>>();
+        let start = MissionElapsedTime::<TestInstrument>::new(offset);
+        let stop = MissionElapsedTime::<TestInstrument>::new(offset + 2.0);
+        let blocks = bayesian_blocks_events(&events, start, stop, DEFAULT_P0);
+        assert_eq!(blocks.first().unwrap().start, start);
+        assert_eq!(blocks.last().unwrap().stop, stop);
+        let [first, last] = excess_span(&blocks).unwrap();
+        let seconds = |time: MissionElapsedTime<TestInstrument>| {
+            (time - start).get::<uom::si::time::second>()
+        };
+        assert!((seconds(first) - 1.0).abs() < 1e-4, "{}", seconds(first));
+        assert!((seconds(last) - 1.001).abs() < 1e-4, "{}", seconds(last));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::bayesian_blocks::{bayesian_blocks_events, excess_span};
+use blink_core::{
+    traits::Event,
+    types::{Characteristics, MissionElapsedTime},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use uom::si::f64::*;
+
+/// Per-instrument settings of [`characterise`].
+#[derive(Clone, Debug)]
+pub struct CharacterisationConfig {
+    /// Energy bands `[low, high)` in keV of the hardness ratio
+    pub soft: [f64; 2],
+    pub hard: [f64; 2],
+    /// Number of detectors, the length of `Characteristics::detector_counts`
+    pub detectors: usize,
+    /// Background windows on either side of the signal, also searched for
+    /// the start and end of T90
+    pub margin: Time,
+    /// Bin of the light curve the peak rate is taken from
+    pub peak_bin: Time,
+    /// Resamples for the T50/T90 errors
+    pub bootstrap: usize,
+    /// False-positive prior of the Bayesian Blocks over the window
+    pub p0: f64,
+}
+
+/// Summarises the signal `span` with best bin `best`.
+///
+/// `events` are all events of the chunk sorted by time, filtered or not;
+/// `background` is the rate of filtered events expected by the search.
+pub fn characterise<E: Event>(
+    events: &[E],
+    detector: impl Fn(&E) -> usize,
+    span: [MissionElapsedTime<E::Instrument>; 2],
+    best: [MissionElapsedTime<E::Instrument>; 2],
+    background: f64,
+    config: &CharacterisationConfig,
+) -> Characteristics {
+    let slice = |from: MissionElapsedTime<E::Instrument>, to: MissionElapsedTime<E::Instrument>| {
+        let from = events.partition_point(|event| event.time() < from);
+        let to = events.partition_point(|event| event.time() < to);
+        &events[from..to.max(from)]
+    };
+    let filtered = |events: &[E]| events.iter().filter(|event| event.keep()).count() as u32;
+    let full = slice(span[0], span[1]);
+    let best_events = slice(best[0], best[1]);
+    let duration_full = seconds(span[1] - span[0]);
+    let duration_best = seconds(best[1] - best[0]);
+    let rate = |count: u32, duration: f64| {
+        if duration > 0.0 {
+            count as f64 / duration
+        } else {
+            0.0
+        }
+    };
+
+    let count_unfiltered_full = full.len() as u32;
+    let count_unfiltered_best = best_events.len() as u32;
+    let count_filtered_full = filtered(full);
+    let count_filtered_best = filtered(best_events);
+
+    // 以 margin 外扩的窗口内的有效事例，时间取相对窗口起点的秒数
+    let window = [span[0] - config.margin, span[1] + config.margin];
+    let times = slice(window[0], window[1])
+        .iter()
+        .filter(|event| event.keep())
+        .map(|event| seconds(event.time() - window[0]))
+        .collect::<Vec<_>>();
+    let [t50, t90] = durations(&times, background);
+    let (t50_error, t90_error) = bootstrap(&times, background, config.bootstrap);
+
+    let [peak_rate, peak] = peak(&times, seconds(config.margin), duration_full, config);
+    let [t05, t95] = interval(&times, background, 0.9).unwrap_or([peak, peak]);
+
+    // Bayesian Blocks 独立于搜索给出的起止，用来核对合并后的 span
+    let window_kept = slice(window[0], window[1])
+        .iter()
+        .filter(|event| event.keep())
+        .cloned()
+        .collect::<Vec<_>>();
+    let blocks = bayesian_blocks_events(&window_kept, window[0], window[1], config.p0);
+    let (block_duration, block_overlap) = match excess_span(&blocks) {
+        Some([start, stop]) => (seconds(stop - start), start < span[1] && span[0] < stop),
+        None => (0.0, false),
+    };
+
+    let kept = full.iter().filter(|event| event.keep());
+    let energies = kept
+        .clone()
+        .map(|event| event.energy())
+        .collect::<Vec<f64>>();
+    let mean_energy = if energies.is_empty() {
+        0.0
+    } else {
+        energies.iter().sum::<f64>() / energies.len() as f64
+    };
+
+    let mut detector_counts = vec![0; config.detectors];
+    for event in kept {
+        if let Some(count) = detector_counts.get_mut(detector(event)) {
+            *count += 1;
+        }
+    }
+
+    let sidebands = slice(window[0], span[0])
+        .iter()
+        .chain(slice(span[1], window[1]))
+        .filter(|event| event.keep())
+        .map(|event| event.energy())
+        .collect::<Vec<f64>>();
+    let (hardness_ratio, hardness_ratio_error) =
+        hardness(&energies, &sidebands, background * duration_full, config);
+
+    Characteristics {
+        duration_full,
+        duration_best,
+        count_unfiltered_full,
+        count_unfiltered_best,
+        count_filtered_full,
+        count_filtered_best,
+        background,
+        flux_unfiltered_full: rate(count_unfiltered_full, duration_full),
+        flux_unfiltered_best: rate(count_unfiltered_best, duration_best),
+        flux_filtered_full: rate(count_filtered_full, duration_full),
+        flux_filtered_best: rate(count_filtered_best, duration_best),
+        t50,
+        t50_error,
+        t90,
+        t90_error,
+        peak_rate,
+        rise_time: (peak - t05).max(0.0),
+        fall_time: (t95 - peak).max(0.0),
+        block_duration,
+        block_overlap,
+        mean_energy,
+        hardness_ratio,
+        hardness_ratio_error,
+        detector_counts,
+    }
+}
+
+fn seconds(duration: Time) -> f64 {
+    duration.get::<uom::si::time::second>()
+}
+
+/// Start and end of the background-subtracted interval holding `fraction`
+/// of the counts of the sorted `times`, centred in the cumulative counts.
+fn interval(times: &[f64], background: f64, fraction: f64) -> Option<[f64; 2]> {
+    let (first, last) = (*times.first()?, *times.last()?);
+    let cumulative = |index: usize, time: f64| (index + 1) as f64 - background * (time - first);
+    let total = cumulative(times.len() - 1, last);
+    if total <= 0.0 {
+        return None;
+    }
+    let tail = (1.0 - fraction) / 2.0;
+    let crossing = |level: f64| {
+        times
+            .iter()
+            .enumerate()
+            .find(|&(index, &time)| cumulative(index, time) >= level * total)
+            .map(|(_, &time)| time)
+    };
+    Some([crossing(tail)?, crossing(1.0 - tail)?])
+}
+
+/// T50 and T90 of the sorted `times`; zero when the counts do not exceed
+/// the background.
+fn durations(times: &[f64], background: f64) -> [f64; 2] {
+    [0.5, 0.9].map(|fraction| {
+        interval(times, background, fraction).map_or(0.0, |[start, stop]| stop - start)
+    })
+}
+
+/// Standard deviations of T50 and T90 over resamples of `times`.
+fn bootstrap(times: &[f64], background: f64, resamples: usize) -> (f64, f64) {
+    if times.is_empty() || resamples < 2 {
+        return (0.0, 0.0);
+    }
+    // 固定种子，保证同一信号重复处理结果一致
+    let mut rng = StdRng::seed_from_u64(times.len() as u64);
+    let samples = (0..resamples)
+        .map(|_| {
+            let mut resample = (0..times.len())
+                .map(|_| times[rng.gen_range(0..times.len())])
+                .collect::<Vec<_>>();
+            resample.sort_by(f64::total_cmp);
+            durations(&resample, background)
+        })
+        .collect::<Vec<_>>();
+    let deviation = |index: usize| {
+        let mean = samples.iter().map(|s| s[index]).sum::<f64>() / resamples as f64;
+        (samples
+            .iter()
+            .map(|s| (s[index] - mean).powi(2))
+            .sum::<f64>()
+            / (resamples - 1) as f64)
+            .sqrt()
+    };
+    (deviation(0), deviation(1))
+}
+
+/// Highest rate and the centre of its bin, over the signal that starts at
+/// `offset` and lasts `duration` seconds.
+fn peak(times: &[f64], offset: f64, duration: f64, config: &CharacterisationConfig) -> [f64; 2] {
+    let bin = seconds(config.peak_bin);
+    if bin <= 0.0 || duration <= 0.0 {
+        return [0.0, offset];
+    }
+    let length = (duration / bin).ceil() as usize;
+    let mut counts = vec![0u32; length];
+    for &time in times {
+        let relative = time - offset;
+        if (0.0..duration).contains(&relative) {
+            counts[((relative / bin) as usize).min(length - 1)] += 1;
+        }
+    }
+    let (index, &count) = counts
+        .iter()
+        .enumerate()
+        .max_by_key(|&(_, count)| *count)
+        .unwrap();
+    [count as f64 / bin, offset + (index as f64 + 0.5) * bin]
+}
+
+/// Background-subtracted hard over soft ratio and its error. The background
+/// `expected` over the signal is split into bands like the `sidebands`.
+fn hardness(
+    energies: &[f64],
+    sidebands: &[f64],
+    expected: f64,
+    config: &CharacterisationConfig,
+) -> (f64, f64) {
+    let count = |energies: &[f64], [low, high]: [f64; 2]| {
+        energies.iter().filter(|&&e| low <= e && e < high).count() as f64
+    };
+    let background = |band: [f64; 2]| {
+        if sidebands.is_empty() {
+            0.0
+        } else {
+            expected * count(sidebands, band) / sidebands.len() as f64
+        }
+    };
+    let (soft, hard) = (count(energies, config.soft), count(energies, config.hard));
+    let net_soft = soft - background(config.soft);
+    let net_hard = hard - background(config.hard);
+    if net_soft <= 0.0 || net_hard <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let ratio = net_hard / net_soft;
+    let error = ratio * (hard / net_hard.powi(2) + soft / net_soft.powi(2)).sqrt();
+    (ratio, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t90_of_a_flat_pulse() {
+        // 1 s 内 1000 个均匀事例，无本底
+        let times = (0..1000)
+            .map(|i| 0.1 + (i as f64 + 0.5) / 1000.0)
+            .collect::<Vec<_>>();
+        let [t50, t90] = durations(&times, 0.0);
+        assert!((t50 - 0.5).abs() < 2e-3, "{t50}");
+        assert!((t90 - 0.9).abs() < 2e-3, "{t90}");
+        let (t50_error, t90_error) = bootstrap(&times, 0.0, 50);
+        assert!(t50_error > 0.0 && t50_error < 0.05);
+        assert!(t90_error > 0.0 && t90_error < 0.05);
+    }
+
+    #[test]
+    fn hardness_subtracts_background() {
+        let config = CharacterisationConfig {
+            soft: [0.0, 10.0],
+            hard: [10.0, 20.0],
+            detectors: 1,
+            margin: Time::new::<uom::si::time::millisecond>(1.0),
+            peak_bin: Time::new::<uom::si::time::millisecond>(1.0),
+            bootstrap: 0,
+            p0: crate::bayesian_blocks::DEFAULT_P0,
+        };
+        // 本底全在软能段，预期 10 个
+        let channels = [[5.0; 30].as_slice(), &[15.0; 10]].concat();
+        let (ratio, error) = hardness(&channels, &[5.0; 4], 10.0, &config);
+        assert!((ratio - 0.5).abs() < 1e-12);
+        assert!(error > 0.0);
+    }
+}
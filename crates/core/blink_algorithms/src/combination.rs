@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use statrs::distribution::{ChiSquared, ContinuousCDF, Normal};
+use statrs::function::{beta, gamma};
+
+/// How the per-group Poisson probabilities of a bin are combined into one
+/// false-alarm probability.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Combination {
+    /// Pool the counts of all groups into a single Poisson test.
+    #[default]
+    Sum,
+    /// At least `k` groups are as significant as the k-th most significant
+    /// one; rejects excesses confined to a single group.
+    ///
+    /// The probability is the tail of the k-th smallest of `n` uniform
+    /// p-values. Poisson p-values are discrete and only bounded by the
+    /// uniform, so it errs on the conservative side at low counts.
+    KOfN(usize),
+    /// Fisher's method: `-2 Σ ln p` follows χ² with `2n` degrees of freedom.
+    Fisher,
+    /// Stouffer's method: `Σ z / √n` follows the standard normal.
+    Stouffer,
+}
+
+impl Combination {
+    /// Combined probability of observing `counts` given background `means`,
+    /// one entry per group.
+    pub fn sf(&self, counts: &[u32], means: &[f64]) -> f64 {
+        match self {
+            Combination::Sum => group_sf(means.iter().sum(), counts.iter().sum()),
+            Combination::KOfN(k) => {
+                let mut probs = group_sfs(counts, means);
+                probs.sort_by(f64::total_cmp);
+                let k = (*k).clamp(1, probs.len());
+                order_statistic_cdf(probs[k - 1], k, probs.len())
+            }
+            Combination::Fisher => {
+                let probs = group_sfs(counts, means);
+                let statistic = -2.0
+                    * probs
+                        .iter()
+                        .map(|p| p.max(f64::MIN_POSITIVE).ln())
+                        .sum::<f64>();
+                ChiSquared::new(2.0 * probs.len() as f64)
+                    .unwrap()
+                    .sf(statistic)
+            }
+            Combination::Stouffer => {
+                let normal = Normal::standard();
+                let probs = group_sfs(counts, means);
+                let z = probs
+                    .iter()
+                    .map(|p| {
+                        normal.inverse_cdf(1.0 - p.clamp(f64::MIN_POSITIVE, 1.0 - f64::EPSILON))
+                    })
+                    .sum::<f64>()
+                    / (probs.len() as f64).sqrt();
+                normal.sf(z)
+            }
+        }
+    }
+
+    /// Groups that push the combined statistic above its expectation under
+    /// the background-only hypothesis.
+    pub fn contributors(&self, counts: &[u32], means: &[f64]) -> Vec<u8> {
+        let probs = group_sfs(counts, means);
+        let limit = match self {
+            Combination::Sum | Combination::Stouffer => 0.5,
+            Combination::KOfN(k) => {
+                let mut sorted = probs.clone();
+                sorted.sort_by(f64::total_cmp);
+                // 前 k 个最显著的组（含并列）
+                sorted[(*k).clamp(1, sorted.len()) - 1]
+            }
+            // -2 ln p 的期望为 2
+            Combination::Fisher => (-1.0f64).exp(),
+        };
+        (0..probs.len())
+            .filter(|&group| probs[group] <= limit)
+            .map(|group| group as u8)
+            .collect()
+    }
+}
+
+impl FromStr for Combination {
+    type Err = String;
+
+    /// Parses `sum`, `fisher`, `stouffer` or `k-of-n:<k>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "sum" => Ok(Combination::Sum),
+            "fisher" => Ok(Combination::Fisher),
+            "stouffer" => Ok(Combination::Stouffer),
+            other => other
+                .strip_prefix("k-of-n:")
+                .and_then(|k| k.parse().ok())
+                .filter(|&k| k >= 1)
+                .map(Combination::KOfN)
+                .ok_or_else(|| format!("expected sum, fisher, stouffer or k-of-n:<k>, got '{s}'")),
+        }
+    }
+}
+
+fn group_sfs(counts: &[u32], means: &[f64]) -> Vec<f64> {
+    counts
+        .iter()
+        .zip(means)
+        .map(|(&count, &mean)| group_sf(mean, count))
+        .collect()
+}
+
+fn group_sf(mean: f64, count: u32) -> f64 {
+    // 无本底的组不提供显著性
+    if mean <= 0.0 {
+        return 1.0;
+    }
+    // 即 Poisson::new(mean).sf(count)，省去每步构造分布与校验
+    gamma::gamma_lr(count as f64 + 1.0, mean)
+}
+
+/// `P(U_(k) <= p)` for the k-th smallest of `n` independent uniform
+/// variables, i.e. the probability that at least `k` of them fall below `p`.
+fn order_statistic_cdf(p: f64, k: usize, n: usize) -> f64 {
+    if p <= 0.0 {
+        return 0.0;
+    }
+    if p >= 1.0 {
+        return 1.0;
+    }
+    beta::beta_reg(k as f64, (n - k + 1) as f64, p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_group_methods_agree() {
+        let expected = group_sf(2.0, 9);
+        for combination in [
+            Combination::Sum,
+            Combination::KOfN(1),
+            Combination::Fisher,
+            Combination::Stouffer,
+        ] {
+            let sf = combination.sf(&[9], &[2.0]);
+            assert!((sf / expected - 1.0).abs() < 1e-6, "{combination:?}: {sf}");
+        }
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!("sum".parse(), Ok(Combination::Sum));
+        assert_eq!("Fisher".parse(), Ok(Combination::Fisher));
+        assert_eq!("k-of-n:2".parse(), Ok(Combination::KOfN(2)));
+        assert!("k-of-n:0".parse::<Combination>().is_err());
+        assert!("median".parse::<Combination>().is_err());
+    }
+
+    #[test]
+    fn k_of_n_needs_k_groups() {
+        let means = [2.0, 2.0, 2.0];
+        let single = [12, 2, 2];
+        let double = [12, 12, 2];
+        assert!(Combination::KOfN(2).sf(&single, &means) > 0.1);
+        assert!(Combination::KOfN(2).sf(&double, &means) < 1e-6);
+        assert_eq!(Combination::KOfN(2).contributors(&double, &means), [0, 1]);
+        // 至少 1 个：1 - (1 - p)^3；全部 3 个：p^3
+        let p = 0.1;
+        let any = order_statistic_cdf(p, 1, 3);
+        assert!((any - (1.0 - (1.0 - p).powi(3))).abs() < 1e-12);
+        assert!((order_statistic_cdf(p, 3, 3) - p.powi(3)).abs() < 1e-12);
+        // 至少 2 个：3p²(1 - p) + p³
+        let two = 3.0 * p * p * (1.0 - p) + p.powi(3);
+        assert!((order_statistic_cdf(p, 2, 3) - two).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::light_curve::LightCurve;
+use blink_core::{
+    traits::Event,
+    types::{DeadTimeCorrected, MissionElapsedTime},
+};
+use uom::si::f64::*;
+
+/// Relation between the recorded and the true rate of one detector unit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DeadTimeModel {
+    /// Events arriving while dead are lost without extending the dead time.
+    NonParalysable(Time),
+    /// Every arriving event restarts the dead time.
+    Paralysable(Time),
+}
+
+impl DeadTimeModel {
+    fn tau(&self) -> f64 {
+        match self {
+            DeadTimeModel::NonParalysable(tau) | DeadTimeModel::Paralysable(tau) => {
+                tau.get::<uom::si::time::second>()
+            }
+        }
+    }
+
+    /// True rate (per second) for a recorded rate.
+    pub fn true_rate(&self, recorded: f64) -> f64 {
+        let tau = self.tau();
+        match self {
+            DeadTimeModel::NonParalysable(_) => {
+                // 记录率不可能超过 1/τ，超过时按饱和处理
+                recorded / (1.0 - recorded * tau).max(f64::EPSILON)
+            }
+            DeadTimeModel::Paralysable(_) => {
+                // m = n·exp(-nτ) 在 nτ < 1 分支上求解；mτ ≥ 1/e 时已饱和
+                if recorded * tau >= (-1.0f64).exp() {
+                    return 1.0 / tau;
+                }
+                let mut rate = recorded;
+                for _ in 0..50 {
+                    let f = rate * (-rate * tau).exp() - recorded;
+                    let df = (1.0 - rate * tau) * (-rate * tau).exp();
+                    let next = rate - f / df;
+                    if (next - rate).abs() <= 1e-12 * rate {
+                        return next;
+                    }
+                    rate = next;
+                }
+                rate
+            }
+        }
+    }
+
+    /// `d(true rate) / d(recorded rate)`, for propagating uncertainties.
+    fn derivative(&self, recorded: f64) -> f64 {
+        let tau = self.tau();
+        match self {
+            DeadTimeModel::NonParalysable(_) => {
+                1.0 / (1.0 - recorded * tau).max(f64::EPSILON).powi(2)
+            }
+            DeadTimeModel::Paralysable(_) => {
+                let rate = self.true_rate(recorded);
+                (rate * tau).exp() / (1.0 - rate * tau).max(f64::EPSILON)
+            }
+        }
+    }
+}
+
+/// Dead-time models of the independent units of an instrument (detectors
+/// or boxes), indexed by unit.
+#[derive(Clone, Debug)]
+pub struct DeadTime {
+    pub models: Vec<DeadTimeModel>,
+}
+
+/// Quantile of the inter-event intervals near the dead time
+const SHORT_QUANTILE: f64 = 1e-3;
+/// Quantiles of the inter-event intervals fixing the exponential slope
+const TAIL_QUANTILES: [f64; 2] = [0.25, 0.75];
+/// Fewer intervals than this give no estimate.
+const MIN_INTERVALS: usize = 1000;
+
+impl DeadTime {
+    pub fn uniform(model: DeadTimeModel, units: usize) -> Self {
+        Self {
+            models: vec![model; units],
+        }
+    }
+
+    /// Estimates a non-paralysable dead time per unit from the inter-event
+    /// intervals, which are exponential shifted by the dead time.
+    ///
+    /// The quartiles give the slope of the exponential; the 0.1% quantile
+    /// less that of the unshifted exponential gives the shift. `events`
+    /// should be every recorded event, since those failing `Event::keep`
+    /// cost dead time too. Units with too few events get the median of the
+    /// other estimates; events of units `>= units` are skipped.
+    pub fn estimate<E: Event>(events: &[E], units: usize, unit: impl Fn(&E) -> usize) -> Self {
+        let mut last = vec![None; units];
+        let mut intervals = vec![Vec::new(); units];
+        for event in events {
+            let index = unit(event);
+            // 探测器编号损坏的事例不计入任何单元
+            let (Some(last), Some(intervals)) = (last.get_mut(index), intervals.get_mut(index))
+            else {
+                continue;
+            };
+            let time = event.time();
+            if let Some(previous) = last.replace(time) {
+                intervals.push((time - previous).get::<uom::si::time::second>());
+            }
+        }
+        let estimates = intervals
+            .into_iter()
+            .map(|mut intervals| {
+                if intervals.len() < MIN_INTERVALS {
+                    return None;
+                }
+                intervals.sort_by(f64::total_cmp);
+                let quantile = |q: f64| intervals[((intervals.len() - 1) as f64 * q) as usize];
+                let [low, high] = TAIL_QUANTILES;
+                let spread = quantile(high) - quantile(low);
+                if spread <= 0.0 {
+                    return None;
+                }
+                let rate = ((1.0 - low) / (1.0 - high)).ln() / spread;
+                // 指数分布自身的 0.1% 分位约为 0.001 / rate，须扣除
+                Some((quantile(SHORT_QUANTILE) + (1.0 - SHORT_QUANTILE).ln() / rate).max(0.0))
+            })
+            .collect::<Vec<_>>();
+        let mut known = estimates.iter().flatten().copied().collect::<Vec<_>>();
+        known.sort_by(f64::total_cmp);
+        let fallback = known.get(known.len() / 2).copied().unwrap_or(0.0);
+        Self {
+            models: estimates
+                .into_iter()
+                .map(|tau| {
+                    DeadTimeModel::NonParalysable(Time::new::<uom::si::time::second>(
+                        tau.unwrap_or(fallback),
+                    ))
+                })
+                .collect(),
+        }
+    }
+
+    /// Corrected total counts and their error for `counts` recorded per unit
+    /// in `duration`.
+    pub fn correct(&self, counts: &[u32], duration: Time) -> (f64, f64) {
+        let duration = duration.get::<uom::si::time::second>();
+        if duration <= 0.0 {
+            let total = counts.iter().sum::<u32>() as f64;
+            return (total, total.sqrt());
+        }
+        let (count, variance) = self
+            .models
+            .iter()
+            .zip(counts)
+            .map(|(model, &count)| {
+                let recorded = count as f64 / duration;
+                let corrected = model.true_rate(recorded) * duration;
+                let error = (count as f64).sqrt() * model.derivative(recorded);
+                (corrected, error * error)
+            })
+            .fold((0.0, 0.0), |(c, v), (corrected, variance)| {
+                (c + corrected, v + variance)
+            });
+        (count, variance.sqrt())
+    }
+
+    /// Live-time corrected counts of `events` in bins of `bin` over
+    /// `[start, stop)`.
+    pub fn light_curve<E: Event>(
+        &self,
+        events: &[E],
+        unit: impl Fn(&E) -> usize,
+        start: MissionElapsedTime<E::Instrument>,
+        stop: MissionElapsedTime<E::Instrument>,
+        bin: Time,
+    ) -> Vec<f64> {
+        let units = self.models.len();
+        let light_curve = LightCurve::by_detector(events, start, stop, bin, units, unit);
+        (0..light_curve.len())
+            .map(|index| {
+                let counts = (0..units)
+                    .map(|unit| light_curve.counts[unit][index])
+                    .collect::<Vec<_>>();
+                self.correct(&counts, light_curve.width(index)).0
+            })
+            .collect()
+    }
+}
+
+/// Dead-time corrected counts and fluence of `[start, stop)`.
+///
+/// Uses the on-board dead time when every event in the bin records one,
+/// otherwise the model returned by `dead_time`, which is only called then.
+/// `background` is the expected background count of the bin and its error,
+/// corrected at the background rate spread evenly over the units of the
+/// model, or over those hit in the bin with on-board dead time; `area`
+/// is the detector area in cm².
+pub fn correct_bin<'a, E: Event>(
+    events: &[E],
+    unit: impl Fn(&E) -> usize,
+    dead_time: impl FnOnce() -> &'a DeadTime,
+    start: MissionElapsedTime<E::Instrument>,
+    stop: MissionElapsedTime<E::Instrument>,
+    background: (f64, f64),
+    area: f64,
+) -> DeadTimeCorrected {
+    let from = events.partition_point(|event| event.time() < start);
+    let to = events.partition_point(|event| event.time() < stop);
+    let window = &events[from..to.max(from)];
+    let duration = stop - start;
+
+    let mut counts = Vec::new();
+    let mut recorded = Some(Vec::new());
+    for event in window {
+        let index = unit(event);
+        if counts.len() <= index {
+            counts.resize(index + 1, 0u32);
+        }
+        counts[index] += 1;
+        match (event.dead_time(), &mut recorded) {
+            (Some(dead), Some(recorded)) => {
+                if recorded.len() <= index {
+                    recorded.resize(index + 1, 0.0);
+                }
+                recorded[index] += dead.get::<uom::si::time::second>();
+            }
+            _ => recorded = None,
+        }
+    }
+    let seconds = duration.get::<uom::si::time::second>();
+    let total = counts.iter().sum::<u32>() as f64;
+    // 死时间超过窗口时记录值不可信，改用模型
+    let (count, variance, models) = match recorded {
+        Some(recorded) if seconds > 0.0 && recorded.iter().all(|&dead| dead < seconds) => {
+            let (count, variance) = counts
+                .iter()
+                .zip(recorded.iter().chain(std::iter::repeat(&0.0)))
+                .map(|(&count, &dead)| {
+                    let factor = seconds / (seconds - dead);
+                    (count as f64 * factor, count as f64 * factor * factor)
+                })
+                .fold((0.0, 0.0), |(c, v), (count, variance)| {
+                    (c + count, v + variance)
+                });
+            // 本底按每个事例的平均记录死时间校正
+            let tau = recorded.iter().sum::<f64>() / total.max(1.0);
+            let model = DeadTimeModel::NonParalysable(Time::new::<uom::si::time::second>(tau));
+            (count, variance, vec![model; counts.len().max(1)])
+        }
+        _ => {
+            let dead_time = dead_time();
+            let mut counts = counts;
+            counts.resize(dead_time.models.len().max(counts.len()), 0);
+            let (count, error) = dead_time.correct(&counts, duration);
+            (count, error * error, dead_time.models.clone())
+        }
+    };
+
+    let (background, background_error) = background;
+    let corrected_background = if seconds > 0.0 && background > 0.0 {
+        let share = background / models.len() as f64 / seconds;
+        models
+            .iter()
+            .map(|model| model.true_rate(share) * seconds)
+            .sum::<f64>()
+    } else {
+        background
+    };
+    let background_error = if background > 0.0 {
+        background_error * corrected_background / background
+    } else {
+        background_error
+    };
+    let error = variance.sqrt();
+    DeadTimeCorrected {
+        live_fraction: if count > 0.0 { total / count } else { 1.0 },
+        count,
+        count_error: error,
+        fluence: (count - corrected_background) / area,
+        fluence_error: (variance + background_error * background_error).sqrt() / area,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestEvent;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn models_invert_their_forward_relation() {
+        let tau = Time::new::<uom::si::time::microsecond>(10.0);
+        let rate: f64 = 20_000.0;
+        let non = DeadTimeModel::NonParalysable(tau);
+        let recorded = rate / (1.0 + rate * 1e-5);
+        assert!((non.true_rate(recorded) - rate).abs() < 1e-6);
+        let par = DeadTimeModel::Paralysable(tau);
+        let recorded = rate * (-rate * 1e-5).exp();
+        assert!((par.true_rate(recorded) - rate).abs() < 1e-6);
+    }
+
+    #[test]
+    fn correction_grows_with_rate() {
+        let dead_time = DeadTime::uniform(
+            DeadTimeModel::NonParalysable(Time::new::<uom::si::time::microsecond>(10.0)),
+            2,
+        );
+        let duration = Time::new::<uom::si::time::millisecond>(1.0);
+        let (low, _) = dead_time.correct(&[1, 1], duration);
+        let (high, error) = dead_time.correct(&[20, 20], duration);
+        assert!(low / 2.0 < high / 40.0);
+        // 20 个事例在 1 ms 内：真实 20 / (1 - 0.2) = 25
+        assert!((high - 50.0).abs() < 1e-9);
+        assert!(error > 40f64.sqrt());
+    }
+
+    /// Non-paralysable stream of `rate` true events per second over
+    /// `seconds`, with dead time `tau`.
+    fn recorded_stream(rate: f64, tau: f64, seconds: f64) -> Vec<TestEvent> {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (mut time, mut live_from) = (0.0, 0.0);
+        let mut events = Vec::new();
+        while time < seconds {
+            time += -(1.0 - rng.r#gen::<f64>()).ln() / rate;
+            if time >= live_from {
+                events.push(TestEvent::new(time, 0));
+                live_from = time + tau;
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn estimate_removes_the_exponential_quantile() {
+        let (rate, tau) = (5000.0, 10e-6);
+        let events = recorded_stream(rate, tau, 40.0);
+        let estimate = DeadTime::estimate(&events, 1, |_| 0).models[0].tau();
+        // 未扣除时偏高约 0.001 / 记录率 ≈ 0.21 µs
+        assert!((estimate - tau).abs() < 0.08e-6, "{estimate}");
+    }
+
+    #[test]
+    fn estimate_skips_unknown_units() {
+        let (rate, tau) = (5000.0, 10e-6);
+        // 每 100 个事例中混入一个编号越界的事例
+        let events = recorded_stream(rate, tau, 40.0)
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| TestEvent {
+                group: if i % 100 == 0 { 7 } else { 0 },
+                ..event
+            })
+            .collect::<Vec<_>>();
+        let dead_time = DeadTime::estimate(&events, 1, |event| event.group as usize);
+        assert_eq!(dead_time.models.len(), 1);
+        assert!((dead_time.models[0].tau() - tau).abs() < 0.5e-6);
+    }
+
+    #[test]
+    fn fluence_corrects_and_propagates_the_background() {
+        let dead_time = DeadTime::uniform(
+            DeadTimeModel::NonParalysable(Time::new::<uom::si::time::microsecond>(10.0)),
+            1,
+        );
+        let events = (0..20)
+            .map(|i| TestEvent::new(i as f64 * 50e-6, 0))
+            .collect::<Vec<_>>();
+        let (start, stop) = (MissionElapsedTime::new(0.0), MissionElapsedTime::new(1e-3));
+        let without = correct_bin(&events, |_| 0, || &dead_time, start, stop, (0.0, 0.0), 1.0);
+        let with = correct_bin(&events, |_| 0, || &dead_time, start, stop, (5.0, 2.0), 1.0);
+        // 本底 5 个/ms 校正为 5 / (1 - 0.05)
+        assert!((without.fluence - with.fluence - 5.0 / 0.95).abs() < 1e-9);
+        assert!(with.fluence_error > without.fluence_error);
+        let expected = (without.count_error.powi(2) + (2.0 / 0.95f64).powi(2)).sqrt();
+        assert!((with.fluence_error - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,125 @@
+//! Groups the signals of a search into multi-pulse episodes.
+
+use blink_core::types::{Episode, UnifiedSignal};
+use chrono::prelude::*;
+use uom::si::f64::*;
+
+/// When two consecutive pulses belong to the same episode.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Separation {
+    /// Gap always allowed between the stop of a pulse and the next start
+    pub max_gap: Time,
+    /// Gap also allowed up to this multiple of the longer of the two pulses
+    pub relative_gap: f64,
+    /// Longest episode from its first start to its last stop
+    pub max_span: Time,
+    /// Fewest pulses reported as an episode
+    pub min_pulses: usize,
+}
+
+impl Default for Separation {
+    fn default() -> Self {
+        Self {
+            max_gap: Time::new::<uom::si::time::millisecond>(2.0),
+            relative_gap: 3.0,
+            max_span: Time::new::<uom::si::time::millisecond>(50.0),
+            min_pulses: 2,
+        }
+    }
+}
+
+/// Episodes of at least `min_pulses` of `signals`, in time order.
+pub fn episodes(signals: &[UnifiedSignal], separation: &Separation) -> Vec<Episode> {
+    let Some(origin) = signals.iter().map(|signal| signal.start).min() else {
+        return Vec::new();
+    };
+    let seconds =
+        |time: DateTime<Utc>| (time - origin).num_nanoseconds().unwrap_or(i64::MAX) as f64 * 1e-9;
+    let mut order = (0..signals.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| (signals[index].start, signals[index].stop));
+    let spans = order
+        .iter()
+        .map(|&index| [seconds(signals[index].start), seconds(signals[index].stop)])
+        .collect::<Vec<_>>();
+
+    group(&spans, separation)
+        .into_iter()
+        .filter(|members| members.len() >= separation.min_pulses.max(1))
+        .map(|members| {
+            let pulses = members
+                .iter()
+                .map(|&member| &signals[order[member]])
+                .collect::<Vec<_>>();
+            let peaks = pulses
+                .iter()
+                .map(|pulse| seconds(pulse.peak_time()))
+                .collect::<Vec<_>>();
+            Episode {
+                start: pulses[0].start,
+                stop: pulses.iter().map(|pulse| pulse.stop).max().unwrap(),
+                signals: pulses.iter().map(|pulse| pulse.start).collect(),
+                counts: pulses
+                    .iter()
+                    .map(|pulse| pulse.characteristics.count_filtered_full)
+                    .collect(),
+                intervals: peaks.windows(2).map(|pair| pair[1] - pair[0]).collect(),
+                gaps: members
+                    .windows(2)
+                    .map(|pair| spans[pair[1]][0] - spans[pair[0]][1])
+                    .collect(),
+                instrument: pulses[0].instrument.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Indices of `spans` (`[start, stop]` in seconds, sorted by start) grouped
+/// into episodes.
+fn group(spans: &[[f64; 2]], separation: &Separation) -> Vec<Vec<usize>> {
+    let max_gap = separation.max_gap.get::<uom::si::time::second>();
+    let max_span = separation.max_span.get::<uom::si::time::second>();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    // 当前组的起点、终点与最后一个脉冲
+    let mut current: Option<(f64, f64, usize)> = None;
+    for (index, &[start, stop]) in spans.iter().enumerate() {
+        if let Some((first, last, previous)) = current {
+            let longer = (stop - start).max(spans[previous][1] - spans[previous][0]);
+            let allowed = max_gap.max(separation.relative_gap * longer);
+            if start - last <= allowed && stop.max(last) - first <= max_span {
+                groups.last_mut().unwrap().push(index);
+                current = Some((first, last.max(stop), index));
+                continue;
+            }
+        }
+        groups.push(vec![index]);
+        current = Some((start, stop, index));
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulses_group_by_gap_and_span() {
+        let separation = Separation::default();
+        let ms = |start: f64, stop: f64| [start * 1e-3, stop * 1e-3];
+        let spans = [
+            ms(0.0, 0.1),
+            // 间隔 1.9 ms，小于 2 ms
+            ms(2.0, 2.2),
+            // 间隔 2.8 ms，超过 2 ms 但小于 3 倍的 1 ms 脉宽
+            ms(5.0, 6.0),
+            // 间隔 14 ms，另起一组
+            ms(20.0, 20.1),
+            ms(21.0, 21.1),
+            // 总跨度超过 50 ms
+            ms(22.0, 80.0),
+        ];
+        assert_eq!(
+            group(&spans, &separation),
+            [vec![0, 1, 2], vec![3, 4], vec![5]]
+        );
+    }
+}
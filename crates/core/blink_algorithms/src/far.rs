@@ -0,0 +1,268 @@
+//! Empirical false-alarm rate from searches of time-scrambled data.
+
+use blink_core::{
+    error::Error,
+    traits::{Event, Instrument},
+    types::MissionElapsedTime,
+};
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use uom::si::f64::*;
+
+use crate::constants::DAYS_PER_YEAR;
+
+/// How an event stream is scrambled so that it keeps its slow rate
+/// variations but no real burst or short-scale correlation survives.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Scramble {
+    /// Permute the inter-event intervals within consecutive blocks; keeps
+    /// the counts of every block and the interval distribution.
+    Intervals { block: Time },
+    /// Draw every event uniformly within its block; keeps the counts of
+    /// every block.
+    Uniform { block: Time },
+}
+
+impl Scramble {
+    /// A scrambled copy of the time-sorted `events`, sorted again.
+    pub fn apply<E: Event, R: Rng>(&self, events: &[E], rng: &mut R) -> Vec<E> {
+        let (Scramble::Intervals { block } | Scramble::Uniform { block }) = *self;
+        let Some(first) = events.first() else {
+            return Vec::new();
+        };
+        let origin = first.time();
+        let index = |event: &E| {
+            ((event.time() - origin) / block)
+                .get::<uom::si::ratio::ratio>()
+                .floor() as i64
+        };
+
+        let mut scrambled = Vec::with_capacity(events.len());
+        for chunk in events.chunk_by(|a, b| index(a) == index(b)) {
+            match self {
+                Scramble::Intervals { .. } => {
+                    let mut intervals = chunk
+                        .windows(2)
+                        .map(|pair| pair[1].time() - pair[0].time())
+                        .collect::<Vec<_>>();
+                    intervals.shuffle(rng);
+                    let mut time = chunk[0].time();
+                    scrambled.push(chunk[0].clone());
+                    for (event, interval) in chunk[1..].iter().zip(intervals) {
+                        time = time + interval;
+                        scrambled.push(event.with_time(time));
+                    }
+                }
+                Scramble::Uniform { .. } => {
+                    let start = origin + block * index(&chunk[0]) as f64;
+                    let from = scrambled.len();
+                    scrambled.extend(
+                        chunk
+                            .iter()
+                            .map(|event| event.with_time(start + block * rng.r#gen::<f64>())),
+                    );
+                    scrambled[from..].sort_by_key(|event| event.time());
+                }
+            }
+        }
+        scrambled
+    }
+}
+
+/// Time of `span` actually searched: `span` without the `excluded`
+/// intervals (in any order, possibly overlapping) and without the gaps longer
+/// than `max_gap` between the sorted event `times`.
+pub fn live_time<I: Instrument>(
+    span: [MissionElapsedTime<I>; 2],
+    excluded: &[(MissionElapsedTime<I>, MissionElapsedTime<I>)],
+    times: &[MissionElapsedTime<I>],
+    max_gap: Time,
+) -> Time {
+    let [start, stop] = span;
+    let inside =
+        &times[times.partition_point(|t| *t < start)..times.partition_point(|t| *t < stop)];
+    // 范围两端也算作断档的端点
+    let edges = std::iter::once(start)
+        .chain(inside.iter().copied())
+        .chain(std::iter::once(stop))
+        .collect::<Vec<_>>();
+    let mut dead = edges
+        .windows(2)
+        .filter(|pair| pair[1] - pair[0] > max_gap)
+        .map(|pair| (pair[0], pair[1]))
+        .chain(excluded.iter().map(|&(a, b)| (a.max(start), b.min(stop))))
+        .filter(|(a, b)| a < b)
+        .collect::<Vec<_>>();
+    dead.sort();
+
+    let mut live = stop - start;
+    let mut covered = start;
+    for (a, b) in dead {
+        let a = a.max(covered);
+        if a < b {
+            live -= b - a;
+            covered = b;
+        }
+    }
+    live
+}
+
+/// Empirical against nominal false alarms per year at one threshold.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FarPoint {
+    /// Nominal false alarms per year used as the threshold
+    pub nominal: f64,
+    /// Scrambled signals at or below the nominal threshold
+    pub count: usize,
+    /// `count` per year of scrambled exposure
+    pub empirical: f64,
+    /// Poisson error of `empirical`
+    pub error: f64,
+}
+
+/// Correction from the analytic to the empirical false-alarm rate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FarCalibration {
+    pub scramble: Scramble,
+    /// Searched duration summed over all scrambled copies (seconds)
+    pub exposure: f64,
+    /// Sorted by nominal rate
+    pub points: Vec<FarPoint>,
+}
+
+impl FarCalibration {
+    /// Builds the curve at `thresholds` from the nominal false alarms per
+    /// year of every signal found in `exposure` seconds of scrambled data.
+    pub fn new(scramble: Scramble, exposure: f64, signals: &[f64], thresholds: &[f64]) -> Self {
+        let years = exposure / (3600.0 * 24.0 * DAYS_PER_YEAR);
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort_by(f64::total_cmp);
+        let points = thresholds
+            .into_iter()
+            .map(|nominal| {
+                let count = signals.iter().filter(|&&far| far <= nominal).count();
+                FarPoint {
+                    nominal,
+                    count,
+                    empirical: count as f64 / years,
+                    error: (count as f64).sqrt() / years,
+                }
+            })
+            .collect();
+        Self {
+            scramble,
+            exposure,
+            points,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| Error::InvalidData(format!("{path}: {e}")))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::InvalidData(format!("{path}: {e}")))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Empirical false alarms per year of a signal with `nominal` ones.
+    ///
+    /// Interpolates the empirical/nominal ratio in log-log space between
+    /// points with counts and holds it beyond them; without any count the
+    /// nominal rate is returned.
+    pub fn empirical(&self, nominal: f64) -> f64 {
+        let known = self
+            .points
+            .iter()
+            .filter(|point| point.count > 0 && point.nominal > 0.0)
+            .map(|point| (point.nominal.ln(), (point.empirical / point.nominal).ln()))
+            .collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (known.first(), known.last()) else {
+            return nominal;
+        };
+        if nominal <= 0.0 {
+            return nominal;
+        }
+        let x = nominal.ln();
+        let ratio = if x <= first.0 {
+            first.1
+        } else if x >= last.0 {
+            last.1
+        } else {
+            let right = known.partition_point(|point| point.0 < x);
+            let (a, b) = (known[right - 1], known[right]);
+            a.1 + (b.1 - a.1) * (x - a.0) / (b.0 - a.0)
+        };
+        nominal * ratio.exp()
+    }
+
+    /// Nominal threshold whose empirical rate is `empirical`.
+    pub fn nominal(&self, empirical: f64) -> f64 {
+        if empirical <= 0.0 {
+            return empirical;
+        }
+        // empirical() 单调递增，在对数空间二分
+        let (mut low, mut high) = ((empirical * 1e-9).ln(), (empirical * 1e9).ln());
+        for _ in 0..100 {
+            let middle = (low + high) / 2.0;
+            if self.empirical(middle.exp()) < empirical {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        ((low + high) / 2.0).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestInstrument;
+
+    #[test]
+    fn calibration_inverts() {
+        let year = 3600.0 * 24.0 * DAYS_PER_YEAR;
+        // 经验率恒为名义率的 4 倍
+        let signals = (1..=400).map(|i| i as f64 / 4.0).collect::<Vec<_>>();
+        let calibration = FarCalibration::new(
+            Scramble::Uniform {
+                block: Time::new::<uom::si::time::second>(1.0),
+            },
+            year,
+            &signals,
+            &[10.0, 30.0, 100.0],
+        );
+        assert_eq!(calibration.points[0].count, 40);
+        assert!((calibration.empirical(20.0) / 80.0 - 1.0).abs() < 1e-9);
+        assert!((calibration.nominal(80.0) / 20.0 - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn live_time_drops_exclusions_and_gaps() {
+        let second = |s: f64| MissionElapsedTime::<TestInstrument>::new(s);
+        let seconds = |time: Time| time.get::<uom::si::time::second>();
+        // 100 s 范围内每 0.1 s 一个事例，60–70 s 无数据
+        let times = (0..1000)
+            .map(|i| i as f64 / 10.0)
+            .filter(|t| !(60.0..70.0).contains(t))
+            .map(second)
+            .collect::<Vec<_>>();
+        let span = [second(0.0), second(100.0)];
+        let max_gap = Time::new::<uom::si::time::second>(1.0);
+
+        assert!((seconds(live_time(span, &[], &times, max_gap)) - 89.9).abs() < 1e-6);
+        // 与断档部分重叠、并超出范围的排除区间
+        let excluded = [
+            (second(10.0), second(20.0)),
+            (second(15.0), second(25.0)),
+            (second(65.0), second(75.0)),
+            (second(95.0), second(120.0)),
+        ];
+        let live = seconds(live_time(span, &excluded, &times, max_gap));
+        assert!((live - 64.9).abs() < 1e-6, "{live}");
+    }
+}
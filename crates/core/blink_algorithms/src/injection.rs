@@ -0,0 +1,176 @@
+use blink_core::{
+    traits::{Event, Instrument},
+    types::{MissionElapsedTime, Signal},
+};
+use rand::{Rng, distributions::Distribution};
+use serde::{Deserialize, Serialize};
+use statrs::distribution::Poisson;
+use uom::si::f64::*;
+
+/// Time profile of a synthetic burst around its peak.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum PulseShape {
+    /// Constant rate over the duration, centred on the peak
+    Flat,
+    /// Gaussian whose ±2σ span the duration
+    Gaussian,
+    /// Fast rise, exponential decay: the rise takes `rise` of the duration
+    /// and both sides fall to e⁻³ at the ends
+    Fred { rise: f64 },
+}
+
+/// Energy distribution of the counts of a synthetic burst.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Spectrum {
+    Flat,
+    /// `dN/dE ∝ E^-index`
+    PowerLaw {
+        index: f64,
+    },
+}
+
+/// A synthetic burst injected into real data.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Burst {
+    /// Counts per cm² of detector area; the injected number is Poisson
+    pub fluence: f64,
+    pub duration: Time,
+    pub shape: PulseShape,
+    pub spectrum: Spectrum,
+    /// Energy range `[low, high)` in keV of the counts
+    pub energies: [f64; 2],
+    /// Relative share of each detector; empty spreads counts evenly
+    pub detectors: Vec<f64>,
+}
+
+impl Burst {
+    /// Time, energy and detector of each count of the burst peaking at
+    /// `peak`, sorted by time.
+    pub fn events<I: Instrument, R: Rng>(
+        &self,
+        peak: MissionElapsedTime<I>,
+        detectors: usize,
+        rng: &mut R,
+    ) -> Vec<(MissionElapsedTime<I>, f64, usize)> {
+        let mean = self.fluence * I::detector_area();
+        let count = if mean > 0.0 {
+            Distribution::<f64>::sample(&Poisson::new(mean).unwrap(), rng) as usize
+        } else {
+            0
+        };
+        let weights = if self.detectors.is_empty() {
+            vec![1.0; detectors]
+        } else {
+            self.detectors.clone()
+        };
+        let total = weights.iter().sum::<f64>();
+
+        let mut events = (0..count)
+            .map(|_| {
+                let offset = self.offset(rng);
+                let energy = self.energy(rng);
+                let mut pick = rng.gen_range(0.0..total);
+                let detector = weights
+                    .iter()
+                    .position(|&weight| {
+                        pick -= weight;
+                        pick < 0.0
+                    })
+                    .unwrap_or(weights.len() - 1);
+                (peak + offset, energy, detector)
+            })
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| event.0);
+        events
+    }
+
+    /// Time of one count relative to the peak.
+    fn offset<R: Rng>(&self, rng: &mut R) -> Time {
+        let duration = self.duration.get::<uom::si::time::second>();
+        let seconds = match self.shape {
+            PulseShape::Flat => rng.gen_range(-0.5..0.5) * duration,
+            PulseShape::Gaussian => {
+                // Box-Muller
+                let u: f64 = 1.0 - rng.r#gen::<f64>();
+                let v: f64 = rng.r#gen();
+                (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos() * duration / 4.0
+            }
+            PulseShape::Fred { rise } => {
+                let rise = rise.clamp(0.0, 1.0);
+                let (up, down) = (rise * duration / 3.0, (1.0 - rise) * duration / 3.0);
+                let distance = -(1.0 - rng.r#gen::<f64>()).ln();
+                if rng.gen_range(0.0..up + down) < up {
+                    -distance * up
+                } else {
+                    distance * down
+                }
+            }
+        };
+        Time::new::<uom::si::time::second>(seconds)
+    }
+
+    fn energy<R: Rng>(&self, rng: &mut R) -> f64 {
+        let [low, high] = self.energies;
+        let u: f64 = rng.r#gen();
+        match self.spectrum {
+            Spectrum::Flat => low + u * (high - low),
+            Spectrum::PowerLaw { index } => {
+                // 反函数抽样，能量下限取 1 keV 以免幂律发散
+                let low = low.max(1.0);
+                if (index - 1.0).abs() < 1e-9 {
+                    low * (high / low).powf(u)
+                } else {
+                    let a = 1.0 - index;
+                    (low.powf(a) + u * (high.powf(a) - low.powf(a))).powf(1.0 / a)
+                }
+            }
+        }
+    }
+}
+
+/// The signal that recovers a burst peaking at `peak`: the first whose span,
+/// widened by the burst duration on either side, contains the peak.
+pub fn recovered<E: Event>(
+    signals: &[Signal<E>],
+    peak: MissionElapsedTime<E::Instrument>,
+    duration: Time,
+) -> Option<&Signal<E>> {
+    signals
+        .iter()
+        .find(|signal| signal.start - duration <= peak && peak <= signal.stop + duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn power_law_stays_in_band() {
+        let burst = Burst {
+            fluence: 1.0,
+            duration: Time::new::<uom::si::time::microsecond>(100.0),
+            shape: PulseShape::Fred { rise: 0.2 },
+            spectrum: Spectrum::PowerLaw { index: 1.5 },
+            energies: [40.0, 3000.0],
+            detectors: Vec::new(),
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let energies = (0..1000)
+            .map(|_| burst.energy(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(energies.iter().all(|e| (40.0..3000.0).contains(e)));
+        // 幂律偏向低能
+        let median = {
+            let mut sorted = energies.clone();
+            sorted.sort_by(f64::total_cmp);
+            sorted[500]
+        };
+        assert!(median < (40.0 + 3000.0) / 2.0);
+        let offsets = (0..1000)
+            .map(|_| burst.offset(&mut rng).get::<uom::si::time::microsecond>())
+            .collect::<Vec<_>>();
+        // 上升沿短于下降沿
+        assert!(offsets.iter().filter(|&&t| t < 0.0).count() < 400);
+    }
+}
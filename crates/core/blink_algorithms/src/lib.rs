@@ -1,5 +1,18 @@
+pub mod background;
+pub mod bayesian_blocks;
+pub mod characterisation;
+pub mod combination;
 pub mod constants;
+pub mod dead_time;
+pub mod episode;
+pub mod far;
+pub mod fixed_bin;
+pub mod injection;
 pub mod light_curve;
 pub mod poisson;
+pub mod signal;
 pub mod snapshot_stepping;
+#[cfg(test)]
+mod testing;
+pub mod threshold;
 pub mod types;
@@ -1,5 +1,317 @@
+use blink_core::{
+    traits::{Event, Instrument},
+    types::MissionElapsedTime,
+};
 use chrono::TimeDelta;
 use chrono::prelude::*;
+use uom::si::f64::*;
+
+/// A closed-open time interval, such as a GTI or a saturation interval.
+pub type Interval<I> = (MissionElapsedTime<I>, MissionElapsedTime<I>);
+
+/// Event counts in bins of mission elapsed time, one row per channel
+/// (detector, energy band, data source, ...).
+#[derive(Clone, Debug)]
+pub struct LightCurve<I: Instrument> {
+    /// Bin edges, one more than the bins; bins differ in width after
+    /// [`LightCurve::rebin_snr`]
+    pub edges: Vec<MissionElapsedTime<I>>,
+    pub channels: Vec<String>,
+    /// Counts by channel, then bin
+    pub counts: Vec<Vec<u32>>,
+    /// Live time of each bin; the bin width unless restricted by
+    /// [`LightCurve::with_exposure`]
+    pub exposure: Vec<Time>,
+}
+
+impl<I: Instrument> LightCurve<I> {
+    /// Empty bins of `bin` over `[start, stop)`; the last bin ends at
+    /// `stop` and may be narrower.
+    pub fn new(
+        start: MissionElapsedTime<I>,
+        stop: MissionElapsedTime<I>,
+        bin: Time,
+        channels: Vec<String>,
+    ) -> Self {
+        let length = ((stop - start) / bin)
+            .get::<uom::si::ratio::ratio>()
+            .ceil()
+            .max(0.0) as usize;
+        let mut edges = (0..length)
+            .map(|i| start + bin * i as f64)
+            .collect::<Vec<_>>();
+        edges.push(stop.max(start));
+        let exposure = edges.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        Self {
+            counts: vec![vec![0; length]; channels.len()],
+            edges,
+            channels,
+            exposure,
+        }
+    }
+
+    /// Bins `events` into the channel returned by `channel`; events without
+    /// one are dropped.
+    pub fn from_events<E: Event<Instrument = I>>(
+        events: &[E],
+        start: MissionElapsedTime<I>,
+        stop: MissionElapsedTime<I>,
+        bin: Time,
+        channels: Vec<String>,
+        channel: impl Fn(&E) -> Option<usize>,
+    ) -> Self {
+        let mut light_curve = Self::new(start, stop, bin, channels);
+        let from = events.partition_point(|event| event.time() < start);
+        for event in &events[from..] {
+            if event.time() >= stop {
+                break;
+            }
+            if let Some(channel) = channel(event) {
+                light_curve.fill(channel, event.time());
+            }
+        }
+        light_curve
+    }
+
+    /// One channel per detector, numbered from 0.
+    pub fn by_detector<E: Event<Instrument = I>>(
+        events: &[E],
+        start: MissionElapsedTime<I>,
+        stop: MissionElapsedTime<I>,
+        bin: Time,
+        detectors: usize,
+        detector: impl Fn(&E) -> usize,
+    ) -> Self {
+        let channels = (0..detectors).map(|i| i.to_string()).collect();
+        Self::from_events(events, start, stop, bin, channels, |event| {
+            Some(detector(event)).filter(|&i| i < detectors)
+        })
+    }
+
+    /// One channel per energy band `[low, high)` in keV.
+    pub fn by_band<E: Event<Instrument = I>>(
+        events: &[E],
+        start: MissionElapsedTime<I>,
+        stop: MissionElapsedTime<I>,
+        bin: Time,
+        bands: &[[f64; 2]],
+    ) -> Self {
+        let channels = bands
+            .iter()
+            .map(|[low, high]| format!("{low}-{high}"))
+            .collect();
+        Self::from_events(events, start, stop, bin, channels, |event| {
+            let energy = event.energy();
+            bands
+                .iter()
+                .position(|&[low, high]| low <= energy && energy < high)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn start(&self, bin: usize) -> MissionElapsedTime<I> {
+        self.edges[bin]
+    }
+
+    pub fn width(&self, bin: usize) -> Time {
+        self.edges[bin + 1] - self.edges[bin]
+    }
+
+    /// Bin containing `time`, if any.
+    pub fn bin(&self, time: MissionElapsedTime<I>) -> Option<usize> {
+        let upper = self.edges.partition_point(|&edge| edge <= time);
+        (upper > 0 && upper < self.edges.len()).then(|| upper - 1)
+    }
+
+    /// Counts one event; `false` if it falls outside the curve.
+    pub fn fill(&mut self, channel: usize, time: MissionElapsedTime<I>) -> bool {
+        match self.bin(time) {
+            Some(bin) => {
+                self.counts[channel][bin] += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Restricts the exposure of every bin to its overlap with `gtis` (the
+    /// whole curve if `None`) outside `masks`, e.g. saturation or SAA.
+    pub fn with_exposure(mut self, gtis: Option<&[Interval<I>]>, masks: &[Interval<I>]) -> Self {
+        let whole = [(self.edges[0], self.edges[self.len()])];
+        let live = subtract(&normalise(gtis.unwrap_or(&whole)), &normalise(masks));
+        let mut first = 0;
+        for bin in 0..self.len() {
+            let (start, stop) = (self.edges[bin], self.edges[bin + 1]);
+            while first < live.len() && live[first].1 <= start {
+                first += 1;
+            }
+            self.exposure[bin] = live[first..]
+                .iter()
+                .take_while(|interval| interval.0 < stop)
+                .map(|interval| interval.1.min(stop) - interval.0.max(start))
+                .sum();
+        }
+        self
+    }
+
+    /// Counts of `channel`, or of all channels summed if `None`.
+    pub fn column(&self, channel: Option<usize>) -> Vec<u32> {
+        match channel {
+            Some(channel) => self.counts[channel].clone(),
+            None => (0..self.len())
+                .map(|bin| self.counts.iter().map(|counts| counts[bin]).sum())
+                .collect(),
+        }
+    }
+
+    /// Count rate per second of live time; 0 in bins without exposure.
+    pub fn rate(&self, channel: Option<usize>) -> Vec<f64> {
+        self.column(channel)
+            .into_iter()
+            .zip(&self.exposure)
+            .map(|(count, &exposure)| per_second(count as f64, exposure))
+            .collect()
+    }
+
+    /// Lower and upper 1σ Poisson errors of the counts (Gehrels 1986).
+    pub fn errors(&self, channel: Option<usize>) -> Vec<[f64; 2]> {
+        self.column(channel).into_iter().map(gehrels).collect()
+    }
+
+    /// [`LightCurve::errors`] scaled to [`LightCurve::rate`].
+    pub fn rate_errors(&self, channel: Option<usize>) -> Vec<[f64; 2]> {
+        self.errors(channel)
+            .into_iter()
+            .zip(&self.exposure)
+            .map(|([low, high], &exposure)| [per_second(low, exposure), per_second(high, exposure)])
+            .collect()
+    }
+
+    /// Merges every `factor` consecutive bins; the last bin takes the rest.
+    pub fn rebin(&self, factor: usize) -> Self {
+        let factor = factor.max(1);
+        let bounds = (0..self.len()).step_by(factor).chain([self.len()]);
+        self.merge(&bounds.collect::<Vec<_>>())
+    }
+
+    /// Merges consecutive bins until the summed counts reach `target`
+    /// signal to noise, `(n - b) / √n` with `b` the expected `background`
+    /// counts per bin (zero if `None`). A last group short of the target
+    /// joins the previous bin.
+    pub fn rebin_snr(&self, target: f64, background: Option<&[f64]>) -> Self {
+        self.merge(&snr_bounds(&self.column(None), background, target))
+    }
+
+    /// Curve with bins `bounds[k]..bounds[k + 1]` merged.
+    fn merge(&self, bounds: &[usize]) -> Self {
+        let ranges = bounds.windows(2).map(|pair| pair[0]..pair[1]);
+        Self {
+            edges: bounds.iter().map(|&bin| self.edges[bin]).collect(),
+            channels: self.channels.clone(),
+            counts: self
+                .counts
+                .iter()
+                .map(|counts| {
+                    ranges
+                        .clone()
+                        .map(|range| counts[range].iter().sum())
+                        .collect()
+                })
+                .collect(),
+            exposure: ranges
+                .map(|range| self.exposure[range].iter().copied().sum())
+                .collect(),
+        }
+    }
+}
+
+/// Group boundaries of [`LightCurve::rebin_snr`].
+fn snr_bounds(total: &[u32], background: Option<&[f64]>, target: f64) -> Vec<usize> {
+    let mut bounds = vec![0];
+    let (mut counts, mut expected) = (0.0, 0.0);
+    for bin in 0..total.len() {
+        counts += total[bin] as f64;
+        expected += background.map_or(0.0, |background| background[bin]);
+        if counts > 0.0 && (counts - expected) / counts.sqrt() >= target {
+            bounds.push(bin + 1);
+            (counts, expected) = (0.0, 0.0);
+        }
+    }
+    if bounds.last() != Some(&total.len()) {
+        // 不足目标的尾部并入前一个区间
+        if bounds.len() > 1 {
+            bounds.pop();
+        }
+        bounds.push(total.len());
+    }
+    bounds
+}
+
+fn per_second(value: f64, exposure: Time) -> f64 {
+    let exposure = exposure.get::<uom::si::time::second>();
+    if exposure > 0.0 {
+        value / exposure
+    } else {
+        0.0
+    }
+}
+
+fn gehrels(count: u32) -> [f64; 2] {
+    let n = count as f64;
+    let high = 1.0 + (n + 0.75).sqrt();
+    let low = if count == 0 {
+        0.0
+    } else {
+        n - n * (1.0 - 1.0 / (9.0 * n) - 1.0 / (3.0 * n.sqrt())).powi(3)
+    };
+    [low, high]
+}
+
+/// Sorted union of `intervals`.
+fn normalise<I: Instrument>(intervals: &[Interval<I>]) -> Vec<Interval<I>> {
+    let mut sorted = intervals
+        .iter()
+        .copied()
+        .filter(|interval| interval.0 < interval.1)
+        .collect::<Vec<_>>();
+    sorted.sort();
+    let mut union: Vec<Interval<I>> = Vec::with_capacity(sorted.len());
+    for interval in sorted {
+        match union.last_mut() {
+            Some(last) if interval.0 <= last.1 => last.1 = last.1.max(interval.1),
+            _ => union.push(interval),
+        }
+    }
+    union
+}
+
+/// `good` without `bad`, both sorted unions.
+fn subtract<I: Instrument>(good: &[Interval<I>], bad: &[Interval<I>]) -> Vec<Interval<I>> {
+    let mut result = Vec::new();
+    let mut first = 0;
+    for &(mut start, stop) in good {
+        while first < bad.len() && bad[first].1 <= start {
+            first += 1;
+        }
+        for &(bad_start, bad_stop) in bad[first..].iter().take_while(|bad| bad.0 < stop) {
+            if bad_start > start {
+                result.push((start, bad_start));
+            }
+            start = start.max(bad_stop);
+        }
+        if start < stop {
+            result.push((start, stop));
+        }
+    }
+    result
+}
 
 pub fn light_curve_chrono(
     time: &[DateTime<Utc>],
@@ -21,3 +333,28 @@ pub fn light_curve_chrono(
     });
     light_curve
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gehrels_errors() {
+        assert_eq!(gehrels(0), [0.0, 1.0 + 0.75f64.sqrt()]);
+        // Gehrels (1986) 表 1、表 2 中 n = 10 的 1σ 上下限
+        let [low, high] = gehrels(10);
+        assert!((10.0 - low - 6.891).abs() < 0.01, "{low}");
+        assert!((10.0 + high - 14.27).abs() < 0.01, "{high}");
+    }
+
+    #[test]
+    fn snr_groups_reach_the_target() {
+        let total = [1, 1, 1, 1, 9, 0, 0, 1];
+        // √n ≥ 2 即 n ≥ 4；尾部 1 个并入前一组
+        assert_eq!(snr_bounds(&total, None, 2.0), [0, 4, 8]);
+        // 本底每区间 0.5 个
+        let total = [1, 1, 1, 1, 9, 4, 4, 1];
+        let background = [0.5; 8];
+        assert_eq!(snr_bounds(&total, Some(&background), 2.0), [0, 5, 8]);
+    }
+}
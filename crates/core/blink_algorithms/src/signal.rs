@@ -0,0 +1,160 @@
+//! Signals from the candidates of a chunk, the part of the search every
+//! instrument shares.
+
+use crate::{
+    characterisation::{CharacterisationConfig, characterise},
+    dead_time::{DeadTime, correct_bin},
+    snapshot_stepping::SearchConfig,
+    types::candidate::Candidate,
+};
+use blink_core::{
+    traits::{Event, Instrument},
+    types::{
+        Attitude, Classification, Extrapolation, Geometry, MissionElapsedTime, OrbitState,
+        Pointing, Position, Region, Signal, TemporalState, Trajectory,
+    },
+};
+use chrono::prelude::*;
+use std::cell::LazyCell;
+use uom::si::f64::*;
+
+/// Attitude, position and orbit of a chunk, interpolated at the peak of
+/// each candidate.
+pub struct Ephemeris<I: Instrument> {
+    attitudes: Trajectory<MissionElapsedTime<I>, Attitude>,
+    positions: Trajectory<MissionElapsedTime<I>, Position>,
+    orbits: Trajectory<MissionElapsedTime<I>, OrbitState>,
+}
+
+impl<I: Instrument> Ephemeris<I> {
+    /// Gaps longer than `max_gap` of each file are not interpolated, and
+    /// only a little extrapolation is allowed at the file edges.
+    pub fn new(
+        attitudes: Trajectory<MissionElapsedTime<I>, Attitude>,
+        positions: Trajectory<MissionElapsedTime<I>, Position>,
+        orbits: Trajectory<MissionElapsedTime<I>, OrbitState>,
+    ) -> Self {
+        let seconds = Time::new::<uom::si::time::second>;
+        Self {
+            attitudes: attitudes
+                .with_max_gap(seconds(10.0))
+                .with_extrapolation(Extrapolation::Hold(seconds(1.0))),
+            positions: positions
+                .with_max_gap(seconds(60.0))
+                .with_extrapolation(Extrapolation::Hold(seconds(1.0))),
+            orbits: orbits
+                .with_max_gap(seconds(60.0))
+                .with_extrapolation(Extrapolation::Extend(seconds(10.0))),
+        }
+    }
+}
+
+/// Events and settings the candidates of a chunk are described with.
+pub struct SignalContext<'a, E: Event, U> {
+    /// Kept events sorted by time, as searched
+    pub events: &'a [E],
+    /// All events sorted by time, for the dead time, the characteristics
+    /// and the classification
+    pub all_events: &'a [E],
+    /// Detector of an event, below `characterisation.detectors`; also the
+    /// unit of the dead-time estimate
+    pub unit: U,
+    pub characterisation: &'a CharacterisationConfig,
+    pub ephemeris: &'a Ephemeris<E::Instrument>,
+    pub config: &'a SearchConfig,
+}
+
+/// Describes each candidate, dropping those the ephemeris does not cover.
+///
+/// `region` gives the region of a position and `classify` the nature of
+/// the events of a signal.
+pub fn signals<E: Event, U: Fn(&E) -> usize + Copy>(
+    candidates: Vec<Candidate<E::Instrument>>,
+    context: &SignalContext<E, U>,
+    region: impl Fn(&TemporalState<DateTime<Utc>, Position>) -> Region,
+    classify: impl Fn(&[E]) -> Classification,
+) -> Vec<Signal<E>> {
+    let SignalContext {
+        events,
+        all_events,
+        unit,
+        characterisation,
+        ephemeris,
+        config,
+    } = *context;
+    // 只有存在候选时才估计死时间
+    let dead_time =
+        LazyCell::new(|| DeadTime::estimate(all_events, characterisation.detectors, unit));
+
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let peak = candidate.start + candidate.bin_size_best / 2.0;
+            let attitude = ephemeris.attitudes.interpolate(peak).ok()?;
+            let position = ephemeris.positions.interpolate(peak).ok()?;
+            let orbit = ephemeris.orbits.interpolate(peak).ok()?;
+            let pointing = Pointing::from_orbit(
+                &attitude.state,
+                &orbit.state,
+                <E::Instrument as Instrument>::boresight(),
+            );
+            let geometry = Geometry::new(
+                &attitude.state,
+                &orbit.state,
+                &<E::Instrument as Instrument>::detector_axes(),
+            );
+            let region = region(&TemporalState {
+                timestamp: peak.to_utc(),
+                state: position.state.clone(),
+            });
+            let best = candidate.start + candidate.delay;
+            let dead_time = correct_bin(
+                events,
+                unit,
+                || &*dead_time,
+                best,
+                best + candidate.bin_size_best,
+                (
+                    candidate.mean,
+                    config
+                        .background
+                        .error(candidate.mean, candidate.bin_size_best),
+                ),
+                <E::Instrument as Instrument>::detector_area(),
+            );
+            let characteristics = characterise(
+                all_events,
+                unit,
+                [candidate.start, candidate.stop],
+                [best, best + candidate.bin_size_best],
+                candidate.mean / candidate.bin_size_best.get::<uom::si::time::second>(),
+                characterisation,
+            );
+            // 粒子与电子学特征看信号全程的全部事例，不经 keep() 筛选
+            let span = all_events.partition_point(|event| event.time() < candidate.start)
+                ..all_events.partition_point(|event| event.time() < candidate.stop);
+            let classification = classify(&all_events[span]);
+            Some(Signal {
+                start: candidate.start,
+                stop: candidate.stop,
+                bin_size_min: candidate.bin_size_min,
+                bin_size_max: candidate.bin_size_max,
+                bin_size_best: candidate.bin_size_best,
+                delay: candidate.delay,
+                count: candidate.count,
+                mean: candidate.mean,
+                sf: candidate.sf,
+                false_positive_per_year: config.calibrated_far(candidate.false_positive_per_year()),
+                groups: candidate.groups,
+                attitude: attitude.state,
+                position: position.state,
+                pointing,
+                region,
+                geometry,
+                dead_time,
+                characteristics,
+                classification,
+            })
+        })
+        .collect()
+}
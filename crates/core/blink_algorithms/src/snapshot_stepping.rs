@@ -1,231 +1,428 @@
-use crate::{constants::DAYS_PER_YEAR, types::candidate::Candidate};
+use crate::{
+    background::{Background, BackgroundKind, BackgroundModel},
+    combination::Combination,
+    constants::DAYS_PER_YEAR,
+    far::FarCalibration,
+    fixed_bin::{FixedBin, search_fixed_bin},
+    threshold::ThresholdTable,
+    types::candidate::Candidate,
+};
 use blink_core::{traits::Event, types::MissionElapsedTime};
-use statrs::distribution::{DiscreteCDF, Poisson};
+use statrs::function::gamma;
+use std::{env, fmt::Display, ops::Range, str::FromStr, sync::Arc, thread};
 use uom::si::f64::*;
 
+/// Which trigger tests the bins of the search.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum Algorithm {
+    /// Bins between every pair of events; background from `background`.
+    #[default]
+    SnapshotStepping,
+    /// Fixed bins of several widths and phases with their own background.
+    FixedBin(FixedBin),
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    /// Parses `snapshot-stepping`, or `fixed-bin` with the default bins.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "snapshot-stepping" => Ok(Algorithm::SnapshotStepping),
+            "fixed-bin" => Ok(Algorithm::FixedBin(FixedBin::default())),
+            _ => Err(format!(
+                "expected snapshot-stepping or fixed-bin, got '{s}'"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct SearchConfig {
+    /// Which trigger tests the bins
+    pub algorithm: Algorithm,
     pub min_duration: Time,
     pub max_duration: Time,
-    pub neighbor: Time,
-    pub hollow: Time,
     pub false_positive_per_year: f64,
     pub min_number: u32,
+    /// How the groups of `Event::group` are combined
+    pub combination: Combination,
+    /// How the background of each bin is estimated
+    pub background: Background,
+    /// When set, `false_positive_per_year` is an empirical rate and the
+    /// analytic threshold is corrected to meet it
+    pub far_calibration: Option<Arc<FarCalibration>>,
+    /// Threads of the snapshot-stepping scan; all available cores when
+    /// `None`
+    pub threads: Option<usize>,
+}
+
+impl SearchConfig {
+    /// Analytic false alarms per year the search thresholds on.
+    pub fn nominal_threshold(&self) -> f64 {
+        match &self.far_calibration {
+            Some(calibration) => calibration.nominal(self.false_positive_per_year),
+            None => self.false_positive_per_year,
+        }
+    }
+
+    /// False alarms per year of a signal with `nominal` analytic ones,
+    /// corrected if calibrated.
+    pub fn calibrated_far(&self, nominal: f64) -> f64 {
+        match &self.far_calibration {
+            Some(calibration) => calibration.empirical(nominal),
+            None => nominal,
+        }
+    }
 }
 
 impl Default for SearchConfig {
     fn default() -> Self {
         Self {
+            algorithm: Algorithm::SnapshotStepping,
             min_duration: Time::new::<uom::si::time::microsecond>(10.0),
             max_duration: Time::new::<uom::si::time::millisecond>(1.0),
-            neighbor: Time::new::<uom::si::time::second>(1.0),
-            hollow: Time::new::<uom::si::time::millisecond>(10.0),
             false_positive_per_year: 20.0,
             min_number: 8,
+            combination: Combination::Sum,
+            background: Background::default(),
+            far_calibration: None,
+            threads: None,
         }
     }
 }
 
-// fn coincidence_prob(probs: &[f64], n: usize) -> f64 {
-//     let mut cache = vec![0.0; n + 1];
-//     cache[0] = 1.0;
-
-//     for m_i in 1..=probs.len() {
-//         for n_i in (0..=n).rev() {
-//             cache[n_i] = match (m_i, n_i) {
-//                 (_, 0) => 1.0,
-//                 // The following line can be removed, because cache[n_i] is 0.0 initially,
-//                 // although it is meaningless mathematically
-//                 // (m_i, n_i) if m_i == n_i => probs[m_i - 1] * cache[n_i - 1],
-//                 (m_i, n_i) => probs[m_i - 1] * cache[n_i - 1] + (1.0 - probs[m_i - 1]) * cache[n_i],
-//             }
-//         }
-//     }
-
-//     cache[n]
-// }
+/// Search settings an instrument reads from the environment variables
+/// `<PREFIX>_ALGORITHM`, `<PREFIX>_COMBINATION`, `<PREFIX>_BACKGROUND` and
+/// `<PREFIX>_FAR_CALIBRATION`. Values that do not parse are reported and
+/// ignored.
+#[derive(Clone, Debug, Default)]
+pub struct SearchEnv {
+    /// Trigger; snapshot stepping unless `fixed-bin` is set
+    pub algorithm: Algorithm,
+    /// How group probabilities are combined; pooled counts unless set.
+    /// `k-of-n:2` rejects excesses confined to one group, which are mostly
+    /// electronic noise.
+    pub combination: Combination,
+    /// Background model; `orbit-phase` takes its reference from the rate
+    /// map of the instrument along the orbit of each chunk.
+    pub background: BackgroundKind,
+    /// Calibration written by `blink far`; when set, the false-alarm
+    /// threshold and the reported rates are empirical.
+    pub far_calibration: Option<Arc<FarCalibration>>,
+}
 
-pub fn poisson_isf(p: f64, lambda: f64) -> u32 {
-    let mut k = 0;
-    let mut cumulative_prob = (-lambda).exp();
-    let mut part = 0.0;
-
-    while cumulative_prob < 1.0 - p {
-        k += 1;
-        part += (lambda / k as f64).ln();
-        cumulative_prob += (-lambda + part).exp();
+impl SearchEnv {
+    /// Reads the variables starting with `prefix`. Without a `rate_map` the
+    /// `orbit-phase` background falls back to the default.
+    pub fn from_env(prefix: &str, rate_map: bool) -> Self {
+        let algorithm = parse_var(&format!("{prefix}_ALGORITHM")).unwrap_or_default();
+        let combination = parse_var(&format!("{prefix}_COMBINATION")).unwrap_or_default();
+        let mut background = parse_var(&format!("{prefix}_BACKGROUND")).unwrap_or_default();
+        if background == BackgroundKind::OrbitPhase && !rate_map {
+            eprintln!("ignoring {prefix}_BACKGROUND: orbit-phase needs {prefix}_RATE_MAP");
+            background = BackgroundKind::default();
+        }
+        let far_calibration = env::var(format!("{prefix}_FAR_CALIBRATION"))
+            .ok()
+            .and_then(|path| {
+                FarCalibration::load(&path)
+                    .inspect_err(|e| eprintln!("ignoring {prefix}_FAR_CALIBRATION: {e}"))
+                    .ok()
+            })
+            .map(Arc::new);
+        Self {
+            algorithm,
+            combination,
+            background,
+            far_calibration,
+        }
     }
 
-    k
+    /// The configuration of the routine search with these settings. The
+    /// reference rate of an `orbit-phase` background depends on the chunk
+    /// and is filled in by the instrument; until then the sliding window
+    /// stands in.
+    pub fn search_config(&self) -> SearchConfig {
+        let neighbor = Time::new::<uom::si::time::second>(1.0);
+        let hollow = Time::new::<uom::si::time::millisecond>(10.0);
+        SearchConfig {
+            algorithm: self.algorithm.clone(),
+            min_duration: Time::new::<uom::si::time::microsecond>(0.0),
+            max_duration: Time::new::<uom::si::time::millisecond>(1.0),
+            false_positive_per_year: 20.0,
+            min_number: 8,
+            combination: self.combination,
+            background: self
+                .background
+                .build(neighbor, hollow, None)
+                .unwrap_or(Background::SlidingWindow { neighbor, hollow }),
+            far_calibration: self.far_calibration.clone(),
+            threads: None,
+        }
+    }
 }
 
-pub fn search_new<E: Event>(
+/// The environment variable `name` parsed, if set and valid.
+fn parse_var<T: FromStr>(name: &str) -> Option<T>
+where
+    T::Err: Display,
+{
+    env::var(name).ok().and_then(|value| {
+        value
+            .parse()
+            .inspect_err(|e| eprintln!("ignoring {name}: {e}"))
+            .ok()
+    })
+}
+
+/// Smallest `k` with `P(N > k) <= p` for `N ~ Poisson(lambda)`.
+pub fn poisson_isf(p: f64, lambda: f64) -> u32 {
+    // 直接用上尾概率，p 远小于 f64::EPSILON 时 1 - p 不再可分辨
+    let sf = |k: u32| gamma::gamma_lr(k as f64 + 1.0, lambda);
+    if lambda <= 0.0 || sf(0) <= p {
+        return 0;
+    }
+    let (mut low, mut high) = (0, lambda.ceil().clamp(1.0, u32::MAX as f64) as u32);
+    while sf(high) > p {
+        if high == u32::MAX {
+            return u32::MAX;
+        }
+        low = high;
+        high = high.saturating_mul(2);
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if sf(middle) <= p {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    high
+}
+
+/// Searches `[start, stop)` with the algorithm selected by `config`.
+pub fn search<E: Event + Sync>(
     data: &[E],
     group_number: usize,
     start: MissionElapsedTime<E::Instrument>,
     stop: MissionElapsedTime<E::Instrument>,
     config: SearchConfig,
 ) -> Vec<Candidate<E::Instrument>> {
-    let mut result: Vec<Candidate<E::Instrument>> = Vec::new();
-    // let mut cache = vec![
-    //     vec![None; CACHE_COUNT_MAX as usize];
-    //     (CACHE_MEAN_MAX * CACHE_MEAN_HASH_FACTOR).ceil() as usize
-    // ];
-    // let mut cache = vec![0; 100_000];
-
-    let mut cursor = data
-        .binary_search_by(|event| event.time().cmp(&start))
-        .unwrap_or_else(|index| index);
-    if cursor == data.len() {
-        return result;
+    match &config.algorithm {
+        Algorithm::SnapshotStepping => search_new(data, group_number, start, stop, config),
+        Algorithm::FixedBin(fixed_bin) => {
+            search_fixed_bin(data, group_number, start, stop, &config, fixed_bin)
+        }
     }
+}
 
-    let mut mean_start_snapshot = cursor;
-    let mut mean_stop_snapshot = cursor;
-    let mut mean_numbers_snapshot: Vec<u32> = vec![0; group_number];
-    mean_numbers_snapshot[data[cursor].group() as usize] = 1;
-    while mean_stop_snapshot < data.len()
-        && data[mean_stop_snapshot].time() - data[cursor].time() < config.neighbor / 2.0
-    {
-        mean_stop_snapshot += 1;
-        mean_numbers_snapshot[data[mean_stop_snapshot].group() as usize] += 1;
+/// Searches `[start, stop)` on `config.threads` threads, or all available
+/// cores; see [`search_sharded`].
+pub fn search_new<E: Event + Sync>(
+    data: &[E],
+    group_number: usize,
+    start: MissionElapsedTime<E::Instrument>,
+    stop: MissionElapsedTime<E::Instrument>,
+    config: SearchConfig,
+) -> Vec<Candidate<E::Instrument>> {
+    let shards = config
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    search_sharded(data, group_number, start, stop, config, shards)
+}
+
+/// Searches `[start, stop)` split into `shards` consecutive time ranges of
+/// equal event counts, scanned in parallel.
+///
+/// Every shard reads past its end as far as `max_duration` needs and shares
+/// one background model built over all of `data`, so each start event is
+/// tested exactly as in a serial scan. The candidates of all shards are then
+/// merged in time order, which makes the result identical for any number of
+/// shards.
+pub fn search_sharded<E: Event + Sync>(
+    data: &[E],
+    group_number: usize,
+    start: MissionElapsedTime<E::Instrument>,
+    stop: MissionElapsedTime<E::Instrument>,
+    config: SearchConfig,
+    shards: usize,
+) -> Vec<Candidate<E::Instrument>> {
+    let first = data.partition_point(|event| event.time() < start);
+    let last = data.partition_point(|event| event.time() < stop);
+    if first >= last {
+        return Vec::new();
     }
+    let background = config.background.model(data, group_number, start, stop);
+    let scan = Scan {
+        data,
+        group_number,
+        stop,
+        config: &config,
+        background: background.as_ref(),
+        threshold: config.nominal_threshold(),
+        table: (config.combination == Combination::Sum).then(|| {
+            ThresholdTable::shared(
+                config.nominal_threshold(),
+                config.min_duration,
+                config.max_duration,
+            )
+        }),
+    };
 
-    let mut hollow_start_snapshot = cursor;
-    let mut hollow_stop_snapshot = cursor;
-    let mut hollow_numbers_snapshot: Vec<u32> = vec![0; group_number];
-    hollow_numbers_snapshot[data[cursor].group() as usize] = 1;
-    while hollow_stop_snapshot < data.len()
-        && data[hollow_stop_snapshot].time() - data[cursor].time() < config.hollow / 2.0
-    {
-        hollow_stop_snapshot += 1;
-        hollow_numbers_snapshot[data[hollow_stop_snapshot].group() as usize] += 1;
+    let shards = shards.clamp(1, last - first);
+    let bounds = (0..=shards)
+        .map(|k| first + (last - first) * k / shards)
+        .collect::<Vec<_>>();
+    let found = if shards == 1 {
+        vec![scan.run(first..last)]
+    } else {
+        thread::scope(|scope| {
+            let scan = &scan;
+            bounds
+                .windows(2)
+                .map(|pair| scope.spawn(move || scan.run(pair[0]..pair[1])))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().expect("search shard panicked"))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // 按时间顺序逐个合并，与分片数无关
+    let mut result: Vec<Candidate<E::Instrument>> = Vec::new();
+    for current in found.into_iter().flatten() {
+        match result.last_mut() {
+            Some(last) if last.mergeable(&current, 0.0) => *last = last.merge(&current),
+            _ => result.push(current),
+        }
     }
+    result
+}
 
-    loop {
-        let mut step = 0;
-        let mut numbers: Vec<u32> = vec![0; group_number];
-        numbers[data[cursor].group() as usize] = 1;
-        let mut mean_stop = mean_stop_snapshot;
-        let mut mean_numbers = mean_numbers_snapshot.clone();
-        let mut hollow_stop = hollow_stop_snapshot;
-        let mut hollow_numbers = hollow_numbers_snapshot.clone();
-
-        loop {
-            let total_number = numbers.iter().sum(); // [TODO] Use real total number calculation
-            let duration = data[cursor + step].time() - data[cursor].time();
-            if total_number >= config.min_number && duration >= config.min_duration {
-                let mean_start_time = (data[cursor].time() - config.neighbor / 2.0).max(start);
-                let mean_stop_time = (data[cursor + step].time() + config.neighbor / 2.0).min(stop);
-                let hollow_start_time = (data[cursor].time() - config.hollow / 2.0).max(start);
-                let hollow_stop_time = (data[cursor + step].time() + config.hollow / 2.0).min(stop);
-                let pure_mean_duration =
-                    (mean_stop_time - mean_start_time) - (hollow_stop_time - hollow_start_time);
-                let pure_mean_percent =
-                    (duration / pure_mean_duration).get::<uom::si::ratio::ratio>();
-                let fps = (0..group_number)
-                    .map(|group| {
-                        let pure_mean_number = mean_numbers[group] - hollow_numbers[group];
-                        let equivalent_background_number =
-                            pure_mean_number as f64 * pure_mean_percent;
-                        match (equivalent_background_number, numbers[group]) {
-                            (0.0, 0) => 1.0,
-                            (0.0, _) => 1.0,
-                            _ => Poisson::new(equivalent_background_number)
-                                .unwrap()
-                                .sf(numbers[group] as u64),
-                        }
-                    })
-                    .collect::<Vec<f64>>();
-                let fp = fps[0];
-                let threshold = config.false_positive_per_year
-                    / (uom::si::f64::Time::new::<uom::si::time::second>(3600.0)
-                        * 24.0
-                        * DAYS_PER_YEAR
-                        / duration)
-                        .get::<uom::si::ratio::ratio>();
-                if fp < threshold {
-                    let total_equivalent_background_number = (0..group_number)
-                        .map(|group| mean_numbers[group] - hollow_numbers[group])
-                        .sum::<u32>()
-                        as f64
-                        * pure_mean_percent;
-                    // println!(
-                    //     "Found trigger: total_number: {}, equivalent_background_number: {}, fp: {}, threshold: {}, duration: {}",
-                    //     total_number,
-                    //     total_equivalent_background_number,
-                    //     fp,
-                    //     threshold,
-                    //     duration.to_seconds() * 1e6
-                    // );
-                    let current = Candidate::new(
-                        data[cursor].time(),
-                        data[cursor + step].time(),
-                        total_number,
-                        total_equivalent_background_number,
-                    );
-                    if let Some(last) = result.last_mut() {
-                        if last.mergeable(&current, 0.0) {
-                            *last = last.merge(&current);
-                        } else {
-                            result.push(current);
-                        }
+/// Shared state of the shards of one search.
+struct Scan<'a, E: Event> {
+    data: &'a [E],
+    group_number: usize,
+    stop: MissionElapsedTime<E::Instrument>,
+    config: &'a SearchConfig,
+    background: &'a (dyn BackgroundModel<E::Instrument> + Send + Sync),
+    threshold: f64,
+    /// Minimum counts for `Combination::Sum`, which tests the pooled count
+    table: Option<Arc<ThresholdTable>>,
+}
+
+impl<E: Event> Scan<'_, E> {
+    /// Unmerged candidates of the bins starting at the events `cursors`.
+    fn run(&self, cursors: Range<usize>) -> Vec<Candidate<E::Instrument>> {
+        let (data, config) = (self.data, self.config);
+        let year = Time::new::<uom::si::time::second>(3600.0) * 24.0 * DAYS_PER_YEAR;
+        let mut result = Vec::new();
+        for cursor in cursors {
+            let mut step = 0;
+            let mut numbers: Vec<u32> = vec![0; self.group_number];
+            numbers[data[cursor].group() as usize] = 1;
+
+            loop {
+                let total_number = numbers.iter().sum();
+                let duration = data[cursor + step].time() - data[cursor].time();
+                if total_number >= config.min_number && duration >= config.min_duration {
+                    let means = self
+                        .background
+                        .expected(data[cursor].time(), data[cursor + step].time());
+                    // 查表可排除的区间不必计算生存函数
+                    let rejected = self.table.as_ref().is_some_and(|table| {
+                        table.rejects(total_number, means.iter().sum(), duration)
+                    });
+                    let fp = if rejected {
+                        1.0
                     } else {
-                        result.push(current);
+                        config.combination.sf(&numbers, &means)
+                    };
+                    let threshold =
+                        self.threshold / (year / duration).get::<uom::si::ratio::ratio>();
+                    if !rejected && fp < threshold {
+                        result.push(Candidate::new(
+                            data[cursor].time(),
+                            data[cursor + step].time(),
+                            total_number,
+                            means.iter().sum::<f64>(),
+                            fp,
+                            config.combination.contributors(&numbers, &means),
+                        ));
                     }
                 }
-            }
 
-            step += 1;
-            if cursor + step >= data.len()
-                || data[cursor + step].time() - data[cursor].time() >= config.max_duration
-                || data[cursor + step].time() >= stop
-            {
-                break;
-            }
-            numbers[data[cursor + step].group() as usize] += 1;
-            while mean_stop + 1 < data.len()
-                && data[mean_stop + 1].time() - data[cursor + step].time() < config.neighbor / 2.0
-            {
-                mean_stop += 1;
-                mean_numbers[data[mean_stop].group() as usize] += 1;
-            }
-            while hollow_stop + 1 < data.len()
-                && data[hollow_stop + 1].time() - data[cursor + step].time() < config.hollow / 2.0
-            {
-                hollow_stop += 1;
-                hollow_numbers[data[hollow_stop].group() as usize] += 1;
+                step += 1;
+                if cursor + step >= data.len()
+                    || data[cursor + step].time() - data[cursor].time() >= config.max_duration
+                    || data[cursor + step].time() >= self.stop
+                {
+                    break;
+                }
+                numbers[data[cursor + step].group() as usize] += 1;
             }
         }
+        result
+    }
+}
 
-        cursor += 1;
-        if cursor >= data.len() || data[cursor].time() >= stop {
-            break;
-        }
-        while mean_start_snapshot + 1 < data.len()
-            && data[cursor].time() - data[mean_start_snapshot + 1].time() > config.neighbor / 2.0
-        {
-            mean_numbers_snapshot[data[mean_start_snapshot].group() as usize] -= 1;
-            mean_start_snapshot += 1;
-        }
-        while mean_stop_snapshot + 1 < data.len()
-            && data[mean_stop_snapshot + 1].time() - data[cursor].time() < config.neighbor / 2.0
-        {
-            mean_stop_snapshot += 1;
-            mean_numbers_snapshot[data[mean_stop_snapshot].group() as usize] += 1;
-        }
-        while hollow_start_snapshot + 1 < data.len()
-            && data[cursor].time() - data[hollow_start_snapshot + 1].time() > config.hollow / 2.0
-        {
-            hollow_numbers_snapshot[data[hollow_start_snapshot].group() as usize] -= 1;
-            hollow_start_snapshot += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestEvent, TestInstrument};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn shards_agree_with_a_serial_scan() {
+        // 10 s 均匀本底 2000/s，两组
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut background = (0..20_000)
+            .map(|_| TestEvent::new(rng.r#gen::<f64>() * 10.0, rng.gen_range(0..2)))
+            .collect::<Vec<_>>();
+        background.sort_by_key(|event| event.time());
+
+        // 30 个事例的暴发跨在 2、3、4、6、12 片的分界上
+        const BURST: usize = 30;
+        let fractions = [1.0 / 4.0, 1.0 / 3.0, 1.0 / 2.0, 2.0 / 3.0, 3.0 / 4.0];
+        let total = background.len() + BURST * fractions.len();
+        let mut events = background.clone();
+        for (j, fraction) in fractions.iter().enumerate() {
+            let index = (total as f64 * fraction) as usize - BURST / 2 - BURST * j;
+            let start = background[index].time().met() + 0.5e-6;
+            events.extend((0..BURST).map(|i| TestEvent::new(start + i as f64 * 1e-6, i as u8 % 2)));
         }
-        while hollow_stop_snapshot + 1 < data.len()
-            && data[hollow_stop_snapshot + 1].time() - data[cursor].time() < config.hollow / 2.0
-        {
-            hollow_stop_snapshot += 1;
-            hollow_numbers_snapshot[data[hollow_stop_snapshot].group() as usize] += 1;
+        events.sort_by_key(|event| event.time());
+
+        let config = SearchConfig {
+            min_duration: Time::new::<uom::si::time::microsecond>(0.0),
+            ..SearchConfig::default()
+        };
+        let (start, stop) = (
+            MissionElapsedTime::<TestInstrument>::new(0.0),
+            MissionElapsedTime::new(10.0),
+        );
+        let serial = search_sharded(&events, 2, start, stop, config.clone(), 1);
+        assert_eq!(serial.len(), fractions.len());
+        for shards in [2, 3, 4, 6, 12] {
+            // 至少一个分界落在暴发内
+            let bounds = (1..shards).map(|k| events.len() * k / shards);
+            assert!(
+                bounds
+                    .into_iter()
+                    .any(|bound| serial.iter().any(|candidate| {
+                        candidate.start < events[bound].time()
+                            && events[bound].time() <= candidate.stop
+                    }))
+            );
+            let sharded = search_sharded(&events, 2, start, stop, config.clone(), shards);
+            assert_eq!(serial, sharded, "{shards} shards");
         }
+        let threads = SearchConfig {
+            threads: Some(3),
+            ..config
+        };
+        assert_eq!(serial, search_new(&events, 2, start, stop, threads));
     }
-
-    result
 }
@@ -0,0 +1,125 @@
+//! A minimal instrument and event for unit tests of the algorithms.
+
+use blink_core::{
+    error::Error,
+    traits::{Chunk, Event, Instrument},
+    types::{MissionElapsedTime, Signal},
+};
+use chrono::{TimeDelta, prelude::*};
+use serde::Serialize;
+use std::sync::LazyLock;
+use uom::si::f64::Time;
+
+static REF_TIME: LazyLock<DateTime<Utc>> =
+    LazyLock::new(|| Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize)]
+pub struct TestInstrument;
+
+impl Instrument for TestInstrument {
+    type Chunk = TestChunk;
+
+    fn ref_time() -> &'static DateTime<Utc> {
+        &REF_TIME
+    }
+    fn launch_day() -> NaiveDate {
+        REF_TIME.date_naive()
+    }
+    fn name() -> &'static str {
+        "test"
+    }
+    fn boresight() -> [f64; 3] {
+        [0.0, 0.0, 1.0]
+    }
+    fn detector_axes() -> Vec<[f64; 3]> {
+        vec![[0.0, 0.0, 1.0]; 2]
+    }
+    fn group_names() -> &'static [&'static str] {
+        &["a", "b"]
+    }
+    fn detector_area() -> f64 {
+        100.0
+    }
+    fn met_counts_leap_seconds() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TestEvent {
+    pub time: MissionElapsedTime<TestInstrument>,
+    pub group: u8,
+}
+
+impl TestEvent {
+    pub fn new(seconds: f64, group: u8) -> Self {
+        Self {
+            time: MissionElapsedTime::new(seconds),
+            group,
+        }
+    }
+}
+
+impl Event for TestEvent {
+    type Instrument = TestInstrument;
+    type ChannelType = u16;
+
+    fn time(&self) -> MissionElapsedTime<TestInstrument> {
+        self.time
+    }
+    fn channel(&self) -> u16 {
+        0
+    }
+    fn energy(&self) -> f64 {
+        100.0
+    }
+    fn group(&self) -> u8 {
+        self.group
+    }
+    fn keep(&self) -> bool {
+        true
+    }
+    fn with_time(&self, time: MissionElapsedTime<TestInstrument>) -> Self {
+        Self { time, ..*self }
+    }
+}
+
+/// Never loaded; only names the chunk type of [`TestInstrument`].
+pub struct TestChunk;
+
+impl Chunk for TestChunk {
+    type Event = TestEvent;
+    type SearchConfig = crate::snapshot_stepping::SearchConfig;
+
+    fn from_range(_: &DateTime<Utc>, _: &DateTime<Utc>, _: TimeDelta) -> Result<Self, Error> {
+        unimplemented!()
+    }
+    fn search_events(&self, _: &Self::SearchConfig, _: Vec<TestEvent>) -> Vec<Signal<TestEvent>> {
+        unimplemented!()
+    }
+    fn events(&self) -> Vec<TestEvent> {
+        unimplemented!()
+    }
+    fn live_time(&self) -> Time {
+        unimplemented!()
+    }
+    fn search_config() -> Self::SearchConfig {
+        Self::SearchConfig::default()
+    }
+    fn detectors() -> usize {
+        2
+    }
+    fn synthetic_event(
+        time: MissionElapsedTime<TestInstrument>,
+        _: f64,
+        detector: usize,
+    ) -> TestEvent {
+        TestEvent {
+            time,
+            group: detector as u8,
+        }
+    }
+    fn last_modified(_: &DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,189 @@
+//! Minimum trigger counts precomputed on a grid of background means and bin
+//! durations, so that most bins of the search are rejected without
+//! evaluating the Poisson survival function.
+//!
+//! A bin of duration `d` with `count` events over a background `mean`
+//! triggers when `P(N > count | mean) < F d / year` for the nominal false
+//! alarms per year `F`. The probability grows with the mean and the right
+//! side with the duration, so within a grid cell `[m0, m1] × [d0, d1]` the
+//! count needed is smallest at `(m0, d1)`. Every cell stores that count, less
+//! one for rounding, and a bin is rejected only below it: the table never
+//! rejects a bin the exact test would accept. Bins at or above the stored
+//! count, and bins outside the grid, are tested exactly. Durations below
+//! the grid, down to zero, fall in its first row, whose count is already
+//! smaller than a shorter bin would need.
+
+use std::sync::{Arc, LazyLock, Mutex};
+use uom::si::f64::*;
+
+use crate::{constants::DAYS_PER_YEAR, snapshot_stepping::poisson_isf};
+
+/// Grid points per decade of mean and duration
+const STEPS_PER_DECADE: f64 = 50.0;
+/// Smallest background mean on the grid
+const MIN_MEAN: f64 = 1e-3;
+/// Largest background mean on the grid
+const MAX_MEAN: f64 = 1e4;
+/// Shortest duration on the grid in seconds, used when the search starts
+/// from zero
+const MIN_DURATION: f64 = 1e-7;
+
+/// Tables built so far, shared by all searches with the same settings.
+static TABLES: LazyLock<Mutex<Vec<Arc<ThresholdTable>>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug)]
+pub struct ThresholdTable {
+    /// Nominal false alarms per year the table is built for
+    false_positive_per_year: f64,
+    /// Grid of background means, ascending
+    means: Vec<f64>,
+    /// Grid of durations in seconds, ascending
+    durations: Vec<f64>,
+    /// Lower bound of the trigger count of each cell, by duration then mean
+    counts: Vec<u32>,
+}
+
+impl ThresholdTable {
+    /// Table for thresholds at `false_positive_per_year` over bins from
+    /// `min_duration` to `max_duration`.
+    pub fn new(false_positive_per_year: f64, min_duration: Time, max_duration: Time) -> Self {
+        let means = log_grid(MIN_MEAN, MAX_MEAN);
+        let [low, high] = duration_range(min_duration, max_duration);
+        let durations = log_grid(low, high);
+        let year = 3600.0 * 24.0 * DAYS_PER_YEAR;
+        let mut counts = Vec::with_capacity(means.len().saturating_sub(1) * durations.len());
+        for duration in durations.iter().skip(1) {
+            // 略放宽概率上限，吸收阈值计算中的舍入
+            let p = false_positive_per_year * duration / year * (1.0 + 1e-9);
+            counts.extend(
+                means[..means.len().saturating_sub(1)]
+                    .iter()
+                    .map(|&mean| poisson_isf(p, mean).saturating_sub(1)),
+            );
+        }
+        Self {
+            false_positive_per_year,
+            means,
+            durations,
+            counts,
+        }
+    }
+
+    /// The table for these settings, built on first use and shared after.
+    pub fn shared(
+        false_positive_per_year: f64,
+        min_duration: Time,
+        max_duration: Time,
+    ) -> Arc<Self> {
+        let mut tables = TABLES.lock().expect("threshold tables poisoned");
+        let durations = duration_range(min_duration, max_duration);
+        if let Some(table) = tables.iter().find(|table| {
+            table.false_positive_per_year == false_positive_per_year
+                && table.durations.first() == Some(&durations[0])
+                && table.durations.last() == Some(&durations[1])
+        }) {
+            return table.clone();
+        }
+        let table = Arc::new(Self::new(
+            false_positive_per_year,
+            min_duration,
+            max_duration,
+        ));
+        tables.push(table.clone());
+        table
+    }
+
+    /// Whether a bin of `count` events over `mean` in `duration` surely
+    /// stays below the threshold. `false` means the exact test decides.
+    pub fn rejects(&self, count: u32, mean: f64, duration: Time) -> bool {
+        let duration = duration.get::<uom::si::time::second>();
+        // 短于网格的区间归入第一行，其计数下限对它同样成立
+        let duration = duration.max(self.durations[0]);
+        match (cell(&self.means, mean), cell(&self.durations, duration)) {
+            (Some(m), Some(d)) => count < self.counts[d * (self.means.len() - 1) + m],
+            _ => false,
+        }
+    }
+}
+
+/// Grid edges in seconds for bins from `min` to `max`.
+fn duration_range(min: Time, max: Time) -> [f64; 2] {
+    let max = max.get::<uom::si::time::second>();
+    [
+        min.get::<uom::si::time::second>()
+            .max(MIN_DURATION.min(max)),
+        max,
+    ]
+}
+
+fn log_grid(low: f64, high: f64) -> Vec<f64> {
+    if !(low > 0.0 && high > low) {
+        return vec![low];
+    }
+    let steps = ((high / low).log10() * STEPS_PER_DECADE).ceil().max(1.0) as usize;
+    let mut grid = (0..steps)
+        .map(|i| low * (high / low).powf(i as f64 / steps as f64))
+        .collect::<Vec<_>>();
+    grid.push(high);
+    grid
+}
+
+/// Index `i` with `grid[i] <= x <= grid[i + 1]`, if any.
+fn cell(grid: &[f64], x: f64) -> Option<usize> {
+    let (&first, &last) = (grid.first()?, grid.last()?);
+    if grid.len() < 2 || !(x >= first && x <= last) {
+        return None;
+    }
+    let upper = grid.partition_point(|&point| point < x);
+    Some(upper.saturating_sub(1).min(grid.len() - 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combination::Combination;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn never_rejects_a_trigger() {
+        let (min, max) = (
+            Time::new::<uom::si::time::microsecond>(10.0),
+            Time::new::<uom::si::time::millisecond>(1.0),
+        );
+        let table = ThresholdTable::shared(20.0, min, max);
+        assert!(Arc::ptr_eq(&table, &ThresholdTable::shared(20.0, min, max)));
+
+        let year = 3600.0 * 24.0 * DAYS_PER_YEAR;
+        let mut rng = StdRng::seed_from_u64(0);
+        let (mut quiet, mut rejected) = (0, 0);
+        for _ in 0..20000 {
+            let mean = 10f64.powf(rng.gen_range(-3.0..4.0));
+            let duration = 10f64.powf(rng.gen_range(-5.0..-3.0));
+            let count = rng.gen_range(0..(mean * 2.0 + 20.0) as u32);
+            let exact = Combination::Sum.sf(&[count], &[mean]) < 20.0 * duration / year;
+            if table.rejects(count, mean, Time::new::<uom::si::time::second>(duration)) {
+                assert!(!exact, "count {count}, mean {mean}, duration {duration}");
+                rejected += 1;
+            }
+            quiet += !exact as usize;
+        }
+        // 从零开始的网格：短于网格的区间同样不会被误排除
+        let zero = ThresholdTable::shared(20.0, Time::new::<uom::si::time::second>(0.0), max);
+        for _ in 0..20000 {
+            let mean = 10f64.powf(rng.gen_range(-3.0..4.0));
+            let duration = 10f64.powf(rng.gen_range(-9.0..-3.0));
+            let count = rng.gen_range(0..(mean * 2.0 + 20.0) as u32);
+            let exact = Combination::Sum.sf(&[count], &[mean]) < 20.0 * duration / year;
+            let rejected = zero.rejects(count, mean, Time::new::<uom::si::time::second>(duration));
+            assert!(
+                !(rejected && exact),
+                "count {count}, mean {mean}, duration {duration}"
+            );
+        }
+        // 绝大多数不触发的区间直接由表排除
+        assert!(
+            rejected as f64 > 0.9 * quiet as f64,
+            "{rejected} of {quiet}"
+        );
+    }
+}
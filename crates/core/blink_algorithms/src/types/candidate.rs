@@ -2,7 +2,7 @@ use crate::poisson;
 use blink_core::{traits::Instrument, types::MissionElapsedTime};
 use uom::si::f64::*;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Candidate<I: Instrument> {
     pub start: MissionElapsedTime<I>,
     pub stop: MissionElapsedTime<I>,
@@ -12,6 +12,10 @@ pub struct Candidate<I: Instrument> {
     pub delay: Time,
     pub count: u32,
     pub mean: f64,
+    /// Combined false-alarm probability of the best bin
+    pub sf: f64,
+    /// Groups contributing to the best bin
+    pub groups: Vec<u8>,
 }
 
 impl<I: Instrument> Candidate<I> {
@@ -20,6 +24,8 @@ impl<I: Instrument> Candidate<I> {
         stop: MissionElapsedTime<I>,
         count: u32,
         mean: f64,
+        sf: f64,
+        groups: Vec<u8>,
     ) -> Candidate<I> {
         let bin_size = stop - start;
         Candidate {
@@ -31,15 +37,13 @@ impl<I: Instrument> Candidate<I> {
             delay: Time::new::<uom::si::time::second>(0.0),
             count,
             mean,
+            sf,
+            groups,
         }
     }
 
-    pub fn sf(&self) -> f64 {
-        poisson::sf(self.mean, self.count)
-    }
-
     pub fn false_positive_per_year(&self) -> f64 {
-        poisson::false_positive_per_year(self.sf(), self.bin_size_best)
+        poisson::false_positive_per_year(self.sf, self.bin_size_best)
     }
 
     pub fn mergeable(&self, other: &Self, vision: f64) -> bool {
@@ -58,6 +62,8 @@ impl<I: Instrument> Candidate<I> {
             res = Candidate {
                 count: other.count,
                 mean: other.mean,
+                sf: other.sf,
+                groups: other.groups.clone(),
                 bin_size_best: other.bin_size_best,
                 delay: other.start - res.start,
                 ..res
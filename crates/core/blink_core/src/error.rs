@@ -1,3 +1,5 @@
+use chrono::prelude::*;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,8 +12,274 @@ pub enum Error {
     InvalidData(String),
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("interpolation refused: {0}")]
+    Interpolation(String),
     #[error("unknown detector: {0}")]
     UnknownDetector(String),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("database error: {0}")]
+    Database(String),
+    #[error("{source} ({context})")]
+    Context {
+        context: Context,
+        source: Box<Error>,
+    },
     #[error("unknown error occurred")]
     Unknown,
 }
+
+/// How a caller processing many epochs should react to an error.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Category {
+    /// The input does not exist (yet); skip it
+    MissingData,
+    /// The input exists but cannot be used; skip it and report
+    CorruptData,
+    /// The environment failed (NFS hiccup, locked database, files still
+    /// being staged without read permission); retry
+    TransientIo,
+    /// A programming or configuration error; abort
+    Bug,
+}
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::MissingData => "missing-data",
+            Category::CorruptData => "corrupt-data",
+            Category::TransientIo => "transient-io",
+            Category::Bug => "bug",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Where an error happened. Fields are filled in as the error propagates
+/// outwards; inner (more specific) values are never overwritten.
+#[derive(Clone, Default, Debug)]
+pub struct Context {
+    pub instrument: Option<&'static str>,
+    pub epoch: Option<DateTime<Utc>>,
+    pub path: Option<String>,
+    pub hdu: Option<String>,
+    pub column: Option<String>,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(instrument) = self.instrument {
+            parts.push(instrument.to_string());
+        }
+        if let Some(epoch) = self.epoch {
+            parts.push(format!("epoch {}", epoch.format("%Y-%m-%dT%H")));
+        }
+        if let Some(path) = &self.path {
+            parts.push(format!("file {path}"));
+        }
+        if let Some(hdu) = &self.hdu {
+            parts.push(format!("HDU {hdu}"));
+        }
+        if let Some(column) = &self.column {
+            parts.push(format!("column {column}"));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+impl Error {
+    pub fn category(&self) -> Category {
+        match self {
+            Error::FitsioError(error) => fitsio_category(error),
+            Error::FileNotFound(_) => Category::MissingData,
+            Error::InvalidData(_) | Error::UnknownDetector(_) => Category::CorruptData,
+            // 轨道/姿态覆盖不到请求时刻，本质是数据缺失
+            Error::Interpolation(_) => Category::MissingData,
+            Error::Io(error) => io_category(error),
+            Error::Database(_) => Category::TransientIo,
+            Error::Context { source, .. } => source.category(),
+            Error::InvalidArgument(_) | Error::Unknown => Category::Bug,
+        }
+    }
+
+    /// Short stable name of the root variant, for grouping in reports.
+    pub fn kind(&self) -> &'static str {
+        match self.root() {
+            Error::FitsioError(_) => "fitsio",
+            Error::FileNotFound(_) => "file-not-found",
+            Error::InvalidData(_) => "invalid-data",
+            Error::Io(_) => "io",
+            Error::Interpolation(_) => "interpolation",
+            Error::UnknownDetector(_) => "unknown-detector",
+            Error::InvalidArgument(_) => "invalid-argument",
+            Error::Database(_) => "database",
+            Error::Context { .. } | Error::Unknown => "unknown",
+        }
+    }
+
+    pub fn context(&self) -> Option<&Context> {
+        match self {
+            Error::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without its context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    fn with(self, update: impl FnOnce(&mut Context)) -> Error {
+        match self {
+            Error::Context {
+                mut context,
+                source,
+            } => {
+                update(&mut context);
+                Error::Context { context, source }
+            }
+            error => {
+                let mut context = Context::default();
+                update(&mut context);
+                Error::Context {
+                    context,
+                    source: Box::new(error),
+                }
+            }
+        }
+    }
+}
+
+fn io_category(error: &std::io::Error) -> Category {
+    use std::io::ErrorKind;
+    match error.kind() {
+        ErrorKind::NotFound => Category::MissingData,
+        ErrorKind::InvalidData | ErrorKind::UnexpectedEof => Category::CorruptData,
+        ErrorKind::InvalidInput => Category::Bug,
+        _ => Category::TransientIo,
+    }
+}
+
+fn fitsio_category(error: &fitsio::errors::Error) -> Category {
+    use fitsio::errors::Error as FitsioError;
+    match error {
+        // CFITSIO 状态码：104 FILE_NOT_OPENED 为缺文件；103 打开文件过多、105 无法创建、
+        // 106/108 读写失败、110 无法关闭、113 内存不足、116 seek 失败为 I/O 故障；
+        // 101 同名文件、111 数组过大、112 只读、114/115 无效指针来自调用方；
+        // 其余（107 END_OF_FILE 截断，头、HDU、列、数据格式）视为损坏
+        FitsioError::Fits(error) => match error.status {
+            104 => Category::MissingData,
+            103 | 105 | 106 | 108 | 110 | 113 | 116 => Category::TransientIo,
+            101 | 111 | 112 | 114 | 115 => Category::Bug,
+            _ => Category::CorruptData,
+        },
+        FitsioError::Io(error) => io_category(error),
+        FitsioError::Index(_)
+        | FitsioError::Utf8(_)
+        | FitsioError::IntoString(_)
+        | FitsioError::Message(_) => Category::CorruptData,
+        // 路径含 NUL、输出文件已存在、空指针只能来自调用方或配置
+        FitsioError::Null(_) | FitsioError::ExistingFile(_) | FitsioError::NullPointer => {
+            Category::Bug
+        }
+        // 互斥锁失败来自运行环境，重试
+        FitsioError::UnlockError => Category::TransientIo,
+    }
+}
+
+/// Attaches [`Context`] to errors on their way out.
+pub trait ResultExt<T> {
+    fn instrument(self, instrument: &'static str) -> Result<T, Error>;
+    fn epoch(self, epoch: DateTime<Utc>) -> Result<T, Error>;
+    fn path(self, path: impl AsRef<std::path::Path>) -> Result<T, Error>;
+    fn hdu(self, hdu: &str) -> Result<T, Error>;
+    fn column(self, column: &str) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for Result<T, E> {
+    fn instrument(self, instrument: &'static str) -> Result<T, Error> {
+        self.map_err(|e| {
+            e.into()
+                .with(|c| _ = c.instrument.get_or_insert(instrument))
+        })
+    }
+
+    fn epoch(self, epoch: DateTime<Utc>) -> Result<T, Error> {
+        self.map_err(|e| e.into().with(|c| _ = c.epoch.get_or_insert(epoch)))
+    }
+
+    fn path(self, path: impl AsRef<std::path::Path>) -> Result<T, Error> {
+        self.map_err(|e| {
+            e.into().with(|c| {
+                c.path
+                    .get_or_insert_with(|| path.as_ref().display().to_string());
+            })
+        })
+    }
+
+    fn hdu(self, hdu: &str) -> Result<T, Error> {
+        self.map_err(|e| {
+            e.into()
+                .with(|c| _ = c.hdu.get_or_insert_with(|| hdu.to_string()))
+        })
+    }
+
+    fn column(self, column: &str) -> Result<T, Error> {
+        self.map_err(|e| {
+            e.into()
+                .with(|c| _ = c.column.get_or_insert_with(|| column.to_string()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_accumulates_without_overwriting() {
+        let epoch = Utc.with_ymd_and_hms(2025, 1, 1, 3, 0, 0).unwrap();
+        let result: Result<(), Error> = Err(Error::InvalidData("bad TIME".to_string()));
+        let error = result
+            .column("TIME")
+            .hdu("EVENTS01")
+            .path("/data/a.fits")
+            .path("/data/outer.fits")
+            .epoch(epoch)
+            .instrument("SVOM/GRM")
+            .unwrap_err();
+
+        let context = error.context().unwrap();
+        assert_eq!(context.path.as_deref(), Some("/data/a.fits"));
+        assert_eq!(error.category(), Category::CorruptData);
+        assert!(matches!(error.root(), Error::InvalidData(_)));
+        assert_eq!(
+            error.to_string(),
+            "invalid data: bad TIME (SVOM/GRM, epoch 2025-01-01T03, file /data/a.fits, HDU EVENTS01, column TIME)"
+        );
+    }
+
+    #[test]
+    fn io_errors_are_classified() {
+        let missing = Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        let timeout = Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(missing.category(), Category::MissingData);
+        assert_eq!(timeout.category(), Category::TransientIo);
+        let denied = Error::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert_eq!(denied.category(), Category::TransientIo);
+        assert_eq!(Error::InvalidArgument("x".into()).category(), Category::Bug);
+        let message = Error::from(fitsio::errors::Error::Message("no such HDU".into()));
+        assert_eq!(message.category(), Category::CorruptData);
+        let existing = Error::from(fitsio::errors::Error::ExistingFile("a.fits".into()));
+        assert_eq!(existing.category(), Category::Bug);
+    }
+}
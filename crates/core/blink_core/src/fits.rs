@@ -0,0 +1,186 @@
+//! Thin wrappers over `fitsio` that attach the HDU and column to errors.
+
+use fitsio::{FitsFile, errors::check_status, hdu::FitsHdu, sys, tables::ReadsCol};
+use std::{ffi::CString, ops::Range, ptr};
+
+use crate::error::{Error, ResultExt};
+
+pub fn hdu(fptr: &mut FitsFile, name: &str) -> Result<FitsHdu, Error> {
+    fptr.hdu(name).hdu(name)
+}
+
+pub fn read_col<T: ReadsCol>(
+    fptr: &mut FitsFile,
+    hdu: &FitsHdu,
+    column: &str,
+) -> Result<Vec<T>, Error> {
+    let result = hdu.read_col(fptr, column);
+    with_location(result, fptr, hdu, column)
+}
+
+/// Reads `rows` of a column; an empty range reads nothing.
+pub fn read_col_range<T: ReadsCol>(
+    fptr: &mut FitsFile,
+    hdu: &FitsHdu,
+    column: &str,
+    rows: &Range<usize>,
+) -> Result<Vec<T>, Error> {
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+    let result = hdu.read_col_range(fptr, column, rows);
+    with_location(result, fptr, hdu, column)
+}
+
+fn with_location<T>(
+    result: fitsio::errors::Result<T>,
+    fptr: &mut FitsFile,
+    hdu: &FitsHdu,
+    column: &str,
+) -> Result<T, Error> {
+    // 名字只在出错时才读，正常路径不多一次 CFITSIO 调用
+    result.map_err(|error| {
+        let name = hdu
+            .name(fptr)
+            .unwrap_or_else(|_| format!("#{}", hdu.number));
+        Err::<(), _>(error).column(column).hdu(&name).unwrap_err()
+    })
+}
+
+/// A binary table read row by row through CFITSIO itself. `fitsio` cannot
+/// describe variable-length (`P`/`Q`) columns and panics on tables holding
+/// them, which OGIP response matrices usually do.
+pub struct RawTable<'a> {
+    fptr: &'a mut FitsFile,
+    name: String,
+    pub rows: usize,
+}
+
+impl<'a> RawTable<'a> {
+    /// Moves to the first binary table extension named one of `names`.
+    pub fn open(fptr: &'a mut FitsFile, names: &[&str]) -> Result<Self, Error> {
+        let mut last = Error::InvalidArgument("no extension name given".to_string());
+        for name in names {
+            let c_name = CString::new(*name).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+            let (mut status, mut rows) = (0, 0);
+            // SAFETY: fptr 是打开的文件，c_name 在调用期间有效
+            unsafe {
+                let raw = fptr.as_raw();
+                sys::ffmnhd(
+                    raw,
+                    sys::BINARY_TBL as i32,
+                    c_name.as_ptr().cast_mut(),
+                    0,
+                    &mut status,
+                );
+                sys::ffgnrw(raw, &mut rows, &mut status);
+            }
+            match check_status(status) {
+                Ok(()) => {
+                    return Ok(Self {
+                        fptr,
+                        name: name.to_string(),
+                        rows: rows as usize,
+                    });
+                }
+                Err(e) => last = Err::<(), _>(e).hdu(name).unwrap_err(),
+            }
+        }
+        Err(last)
+    }
+
+    /// One-based number of `column`.
+    pub fn column(&mut self, column: &str) -> Result<i32, Error> {
+        let c_name = CString::new(column).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        let (mut status, mut number) = (0, 0);
+        // SAFETY: 同上
+        unsafe {
+            sys::ffgcno(
+                self.fptr.as_raw(),
+                sys::CASEINSEN as i32,
+                c_name.as_ptr().cast_mut(),
+                &mut number,
+                &mut status,
+            );
+        }
+        self.check(status, column).map(|_| number)
+    }
+
+    /// Values of zero-based `row` of `column`: the whole vector of a
+    /// fixed-width column, or the elements stored for a variable-length one.
+    pub fn read_row(&mut self, column: &str, row: usize) -> Result<Vec<f64>, Error> {
+        let number = self.column(column)?;
+        let (mut status, mut typecode, mut repeat, mut width) = (0, 0, 0, 0);
+        let (mut length, mut heap) = (0, 0);
+        // SAFETY: 同上；缓冲区长度与读取的元素数一致
+        unsafe {
+            let raw = self.fptr.as_raw();
+            sys::ffgtcl(
+                raw,
+                number,
+                &mut typecode,
+                &mut repeat,
+                &mut width,
+                &mut status,
+            );
+            // 负的类型码表示变长列，长度取自该行的描述符
+            if typecode < 0 {
+                sys::ffgdes(
+                    raw,
+                    number,
+                    row as i64 + 1,
+                    &mut length,
+                    &mut heap,
+                    &mut status,
+                );
+            } else {
+                length = repeat;
+            }
+        }
+        self.check(status, column)?;
+
+        let mut values = vec![0.0; length.max(0) as usize];
+        if values.is_empty() {
+            return Ok(values);
+        }
+        let (mut null, mut any_null) = (0.0f64, 0);
+        // SAFETY: 同上
+        unsafe {
+            sys::ffgcv(
+                self.fptr.as_raw(),
+                sys::TDOUBLE as i32,
+                number,
+                row as i64 + 1,
+                1,
+                values.len() as i64,
+                ptr::from_mut(&mut null).cast(),
+                values.as_mut_ptr().cast(),
+                &mut any_null,
+                &mut status,
+            );
+        }
+        self.check(status, column).map(|_| values)
+    }
+
+    /// Integer keyword `prefix` followed by the number of `column`, e.g.
+    /// `TLMIN` of `F_CHAN`; `None` when absent.
+    pub fn column_key(&mut self, prefix: &str, column: &str) -> Option<i64> {
+        let key = CString::new(format!("{prefix}{}", self.column(column).ok()?)).ok()?;
+        let (mut status, mut value) = (0, 0);
+        // SAFETY: 同上
+        unsafe {
+            sys::ffgkyj(
+                self.fptr.as_raw(),
+                key.as_ptr(),
+                &mut value,
+                ptr::null_mut(),
+                &mut status,
+            );
+        }
+        (status == 0).then_some(value)
+    }
+
+    fn check(&self, status: i32, column: &str) -> Result<(), Error> {
+        check_status(status).column(column).hdu(&self.name)
+    }
+}
@@ -0,0 +1,220 @@
+//! Coordinate frames: WGS84 geodetic, ECEF and J2000/ECI.
+//!
+//! Angles in the public API are degrees, lengths metres and velocities
+//! metres per second. ECI means J2000; the conversion to ECEF applies IAU
+//! 1976 precession and GMST rotation, nutation and polar motion are ignored
+//! (below 0.01° at the epochs we process).
+
+use chrono::prelude::*;
+
+/// WGS84 semi-major axis (m).
+pub const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Mean Earth radius for spherical approximations (m).
+pub const EARTH_MEAN_RADIUS: f64 = 6_371_000.0;
+/// Earth rotation rate (rad/s).
+pub const EARTH_ROTATION_RATE: f64 = 7.292_115_146_706_979e-5;
+
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+fn centuries_since_j2000(time: DateTime<Utc>) -> f64 {
+    let j2000 = Utc.with_ymd_and_hms(2000, 1, 1, 12, 0, 0).unwrap();
+    let days = (time - j2000).num_nanoseconds().unwrap() as f64 / 86_400e9;
+    days / 36_525.0
+}
+
+/// Greenwich mean sidereal time in radians (IAU 1982, UT1 ≈ UTC).
+pub fn gmst(time: DateTime<Utc>) -> f64 {
+    let centuries = centuries_since_j2000(time);
+    let seconds = 67_310.548_41
+        + (876_600.0 * 3600.0 + 8_640_184.812_866) * centuries
+        + 0.093_104 * centuries.powi(2)
+        - 6.2e-6 * centuries.powi(3);
+    (seconds.rem_euclid(86_400.0) / 240.0).to_radians()
+}
+
+/// IAU 1976 precession matrix, J2000 → mean equator and equinox of date.
+fn precession(time: DateTime<Utc>) -> [[f64; 3]; 3] {
+    let t = centuries_since_j2000(time);
+    let arcsec = |x: f64| (x / 3600.0).to_radians();
+    let zeta = arcsec(2306.2181 * t + 0.30188 * t * t + 0.017998 * t * t * t);
+    let z = arcsec(2306.2181 * t + 1.09468 * t * t + 0.018203 * t * t * t);
+    let theta = arcsec(2004.3109 * t - 0.42665 * t * t - 0.041833 * t * t * t);
+    let (sz, cz) = zeta.sin_cos();
+    let (sth, cth) = theta.sin_cos();
+    let (szz, czz) = z.sin_cos();
+    [
+        [
+            cz * cth * czz - sz * szz,
+            -sz * cth * czz - cz * szz,
+            -sth * czz,
+        ],
+        [
+            cz * cth * szz + sz * czz,
+            -sz * cth * szz + cz * czz,
+            -sth * szz,
+        ],
+        [cz * sth, -sz * sth, cth],
+    ]
+}
+
+fn mul(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|i| m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2])
+}
+
+fn mul_transposed(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|i| m[0][i] * v[0] + m[1][i] * v[1] + m[2][i] * v[2])
+}
+
+/// Rotation about z by `angle` (frame rotation, as R3 in the literature).
+fn rotate_z(v: [f64; 3], angle: f64) -> [f64; 3] {
+    let (s, c) = angle.sin_cos();
+    [c * v[0] + s * v[1], -s * v[0] + c * v[1], v[2]]
+}
+
+fn earth_rotation_cross(r: [f64; 3]) -> [f64; 3] {
+    [-EARTH_ROTATION_RATE * r[1], EARTH_ROTATION_RATE * r[0], 0.0]
+}
+
+pub fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+pub fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn geodetic_to_ecef(longitude: f64, latitude: f64, altitude: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+    let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+    [
+        (n + altitude) * cos_lat * cos_lon,
+        (n + altitude) * cos_lat * sin_lon,
+        (n * (1.0 - WGS84_E2) + altitude) * sin_lat,
+    ]
+}
+
+/// Inverse of [`geodetic_to_ecef`], returning `(longitude, latitude, altitude)`.
+pub fn ecef_to_geodetic(r: [f64; 3]) -> (f64, f64, f64) {
+    let [x, y, z] = r;
+    let p = (x * x + y * y).sqrt();
+    let longitude = y.atan2(x).to_degrees();
+    if p < 1e-6 {
+        let b = WGS84_A * (1.0 - WGS84_F);
+        return (longitude, 90f64.copysign(z), z.abs() - b);
+    }
+
+    let mut latitude = z.atan2(p * (1.0 - WGS84_E2));
+    let mut altitude = 0.0;
+    // 不动点迭代，低轨高度上 5 次即收敛到亚毫米
+    for _ in 0..5 {
+        let sin_lat = latitude.sin();
+        let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+        altitude = p / latitude.cos() - n;
+        latitude = z.atan2(p * (1.0 - WGS84_E2 * n / (n + altitude)));
+    }
+    (longitude, latitude.to_degrees(), altitude)
+}
+
+pub fn eci_to_ecef(r: [f64; 3], time: DateTime<Utc>) -> [f64; 3] {
+    rotate_z(mul(&precession(time), r), gmst(time))
+}
+
+pub fn ecef_to_eci(r: [f64; 3], time: DateTime<Utc>) -> [f64; 3] {
+    mul_transposed(&precession(time), rotate_z(r, -gmst(time)))
+}
+
+/// Converts a position/velocity pair from ECI to ECEF, including the
+/// transport term of the rotating frame.
+pub fn eci_to_ecef_state(
+    position: [f64; 3],
+    velocity: [f64; 3],
+    time: DateTime<Utc>,
+) -> ([f64; 3], [f64; 3]) {
+    let r = eci_to_ecef(position, time);
+    let v = eci_to_ecef(velocity, time);
+    let w = earth_rotation_cross(r);
+    (r, [v[0] - w[0], v[1] - w[1], v[2] - w[2]])
+}
+
+pub fn ecef_to_eci_state(
+    position: [f64; 3],
+    velocity: [f64; 3],
+    time: DateTime<Utc>,
+) -> ([f64; 3], [f64; 3]) {
+    let w = earth_rotation_cross(position);
+    let v = [velocity[0] + w[0], velocity[1] + w[1], velocity[2] + w[2]];
+    (ecef_to_eci(position, time), ecef_to_eci(v, time))
+}
+
+pub fn hav(theta: f64) -> f64 {
+    (theta / 2.0).sin().powi(2)
+}
+
+/// Great-circle angle (radians) between two geographic points, haversine form.
+pub fn central_angle(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let phi1 = lat1.to_radians();
+    let phi2 = lat2.to_radians();
+    let a =
+        hav((lat2 - lat1).to_radians()) + phi1.cos() * phi2.cos() * hav((lon2 - lon1).to_radians());
+    2.0 * a.sqrt().atan2((1.0 - a).sqrt())
+}
+
+/// Straight-line distance between points at radii `r1`, `r2` separated by a
+/// central angle.
+pub fn chord(r1: f64, r2: f64, angle: f64) -> f64 {
+    (r1 * r1 + r2 * r2 - 2.0 * r1 * r2 * angle.cos())
+        .max(0.0)
+        .sqrt()
+}
+
+/// Spherical interpolation of the directions of `a` and `b`, returning a
+/// unit vector.
+pub fn slerp_direction(a: [f64; 3], b: [f64; 3], ratio: f64) -> [f64; 3] {
+    let (na, nb) = (norm(a), norm(b));
+    let a = [a[0] / na, a[1] / na, a[2] / na];
+    let b = [b[0] / nb, b[1] / nb, b[2] / nb];
+    let cos_theta = dot(a, b).clamp(-1.0, 1.0);
+    let (w0, w1) = if cos_theta > 0.9995 {
+        (1.0 - ratio, ratio)
+    } else {
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        (
+            ((1.0 - ratio) * theta).sin() / sin_theta,
+            (ratio * theta).sin() / sin_theta,
+        )
+    };
+    let v: [f64; 3] = std::array::from_fn(|i| w0 * a[i] + w1 * b[i]);
+    let n = norm(v);
+    [v[0] / n, v[1] / n, v[2] / n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geodetic_round_trip() {
+        for &(lon, lat, alt) in &[(116.4, 39.9, 550e3), (-179.9, -45.0, 0.0), (0.0, 89.9, 1e3)] {
+            let (lon2, lat2, alt2) = ecef_to_geodetic(geodetic_to_ecef(lon, lat, alt));
+            assert!((lon - lon2).abs() < 1e-9);
+            assert!((lat - lat2).abs() < 1e-9);
+            assert!((alt - alt2).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn eci_state_round_trip() {
+        let time = Utc.with_ymd_and_hms(2025, 1, 1, 6, 0, 0).unwrap();
+        let r = [6_900e3, 100e3, -200e3];
+        let v = [-100.0, 7_500.0, 1_000.0];
+        let (re, ve) = eci_to_ecef_state(r, v, time);
+        let (r2, v2) = ecef_to_eci_state(re, ve, time);
+        assert!((0..3).all(|i| (r[i] - r2[i]).abs() < 1e-6 && (v[i] - v2[i]).abs() < 1e-9));
+        // 地固系中卫星速度比惯性系约小 ω·r 量级
+        assert!((norm(ve) - norm(v)).abs() < EARTH_ROTATION_RATE * norm(r) * 1.01);
+    }
+}
@@ -1,3 +1,8 @@
 pub mod error;
+pub mod fits;
+pub mod frames;
+#[cfg(test)]
+mod testing;
+pub mod time;
 pub mod traits;
 pub mod types;
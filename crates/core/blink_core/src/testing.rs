@@ -0,0 +1,107 @@
+//! A minimal instrument for unit tests of the core types.
+
+use crate::{
+    error::Error,
+    traits::{Chunk, Event, Instrument},
+    types::{MissionElapsedTime, Signal},
+};
+use chrono::{TimeDelta, prelude::*};
+use serde::Serialize;
+use std::sync::LazyLock;
+use uom::si::f64::Time;
+
+static REF_TIME: LazyLock<DateTime<Utc>> =
+    LazyLock::new(|| Utc.with_ymd_and_hms(2012, 1, 1, 0, 0, 0).unwrap());
+
+/// MET counts leap seconds from 2012-01-01, as HXMT does.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize)]
+pub struct TestInstrument;
+
+impl Instrument for TestInstrument {
+    type Chunk = TestChunk;
+
+    fn ref_time() -> &'static DateTime<Utc> {
+        &REF_TIME
+    }
+    fn launch_day() -> NaiveDate {
+        REF_TIME.date_naive()
+    }
+    fn name() -> &'static str {
+        "test"
+    }
+    fn boresight() -> [f64; 3] {
+        [0.0, 0.0, 1.0]
+    }
+    fn detector_axes() -> Vec<[f64; 3]> {
+        vec![[0.0, 0.0, 1.0]]
+    }
+    fn group_names() -> &'static [&'static str] {
+        &["a"]
+    }
+    fn detector_area() -> f64 {
+        100.0
+    }
+    fn met_counts_leap_seconds() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TestEvent {
+    pub time: MissionElapsedTime<TestInstrument>,
+}
+
+impl Event for TestEvent {
+    type Instrument = TestInstrument;
+    type ChannelType = u16;
+
+    fn time(&self) -> MissionElapsedTime<TestInstrument> {
+        self.time
+    }
+    fn channel(&self) -> u16 {
+        0
+    }
+    fn energy(&self) -> f64 {
+        100.0
+    }
+    fn group(&self) -> u8 {
+        0
+    }
+    fn keep(&self) -> bool {
+        true
+    }
+    fn with_time(&self, time: MissionElapsedTime<TestInstrument>) -> Self {
+        Self { time }
+    }
+}
+
+/// Never loaded; only names the chunk type of [`TestInstrument`].
+pub struct TestChunk;
+
+impl Chunk for TestChunk {
+    type Event = TestEvent;
+    type SearchConfig = ();
+
+    fn from_range(_: &DateTime<Utc>, _: &DateTime<Utc>, _: TimeDelta) -> Result<Self, Error> {
+        unimplemented!()
+    }
+    fn search_events(&self, _: &(), _: Vec<TestEvent>) -> Vec<Signal<TestEvent>> {
+        unimplemented!()
+    }
+    fn events(&self) -> Vec<TestEvent> {
+        unimplemented!()
+    }
+    fn live_time(&self) -> Time {
+        unimplemented!()
+    }
+    fn search_config() {}
+    fn detectors() -> usize {
+        1
+    }
+    fn synthetic_event(time: MissionElapsedTime<TestInstrument>, _: f64, _: usize) -> TestEvent {
+        TestEvent { time }
+    }
+    fn last_modified(_: &DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
+        unimplemented!()
+    }
+}
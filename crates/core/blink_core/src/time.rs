@@ -0,0 +1,308 @@
+//! Leap seconds and the UTC, TAI, TT and GPS time scales.
+//!
+//! The leap-second table is read from the file named by `BLINK_LEAP_SECONDS`
+//! (NAIF LSK, IERS `Leap_Second.dat` or `leap-seconds.list`), falling back
+//! to the built-in table. Non-UTC scales are represented as
+//! [`NaiveDateTime`] labels on that scale.
+
+use chrono::{Duration, prelude::*};
+use std::{env, sync::LazyLock};
+
+use crate::{error::Error, traits::Instrument};
+
+/// TT − TAI (s).
+pub const TT_MINUS_TAI: f64 = 32.184;
+/// TAI − GPS (s).
+pub const TAI_MINUS_GPS: i64 = 19;
+
+/// UTC dates on which a new TAI − UTC took effect, up to 2017-01-01.
+const BUILTIN: [(i32, u32, i64); 28] = [
+    (1972, 1, 10),
+    (1972, 7, 11),
+    (1973, 1, 12),
+    (1974, 1, 13),
+    (1975, 1, 14),
+    (1976, 1, 15),
+    (1977, 1, 16),
+    (1978, 1, 17),
+    (1979, 1, 18),
+    (1980, 1, 19),
+    (1981, 7, 20),
+    (1982, 7, 21),
+    (1983, 7, 22),
+    (1985, 7, 23),
+    (1988, 1, 24),
+    (1990, 1, 25),
+    (1991, 1, 26),
+    (1992, 7, 27),
+    (1993, 7, 28),
+    (1994, 7, 29),
+    (1996, 1, 30),
+    (1997, 7, 31),
+    (1999, 1, 32),
+    (2006, 1, 33),
+    (2009, 1, 34),
+    (2012, 7, 35),
+    (2015, 7, 36),
+    (2017, 1, 37),
+];
+
+static LEAP_SECONDS: LazyLock<LeapSeconds> =
+    LazyLock::new(|| match env::var("BLINK_LEAP_SECONDS") {
+        Ok(path) => LeapSeconds::load(&path).unwrap_or_else(|e| {
+            eprintln!("warning: {path}: {e}, using built-in leap seconds");
+            LeapSeconds::builtin()
+        }),
+        Err(_) => LeapSeconds::builtin(),
+    });
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeScale {
+    Utc,
+    Tai,
+    Tt,
+    Gps,
+}
+
+/// TAI − UTC as a step function of UTC.
+#[derive(Clone, Debug)]
+pub struct LeapSeconds {
+    /// `(UTC midnight the offset takes effect, TAI − UTC in seconds)`, sorted
+    entries: Vec<(NaiveDateTime, i64)>,
+}
+
+impl LeapSeconds {
+    pub fn global() -> &'static LeapSeconds {
+        &LEAP_SECONDS
+    }
+
+    pub fn builtin() -> Self {
+        LeapSeconds {
+            entries: BUILTIN
+                .iter()
+                .map(|&(year, month, offset)| (midnight(year, month, 1), offset))
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses an NAIF LSK kernel or either IERS leap-second file.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut entries = if text.contains("DELTA_AT") {
+            parse_lsk(text)?
+        } else {
+            parse_iers(text)?
+        };
+        if entries.is_empty() {
+            return Err(Error::InvalidData("no leap seconds found".to_string()));
+        }
+        entries.sort();
+        Ok(LeapSeconds { entries })
+    }
+
+    /// Last date covered by the table.
+    pub fn last_change(&self) -> NaiveDateTime {
+        self.entries.last().unwrap().0
+    }
+
+    /// TAI − UTC (s) at `utc`. Before 1972 the first offset is used.
+    pub fn tai_minus_utc(&self, utc: DateTime<Utc>) -> i64 {
+        let utc = utc.naive_utc();
+        let idx = self.entries.partition_point(|(start, _)| *start <= utc);
+        self.entries[idx.saturating_sub(1)].1
+    }
+
+    pub fn utc_to_tai(&self, utc: DateTime<Utc>) -> NaiveDateTime {
+        let naive = utc.naive_utc();
+        if naive.nanosecond() >= 1_000_000_000 {
+            // chrono 把闰秒 23:59:60 表示成 23:59:59 加超过 1e9 的纳秒
+            let base = naive
+                .with_nanosecond(naive.nanosecond() - 1_000_000_000)
+                .unwrap();
+            return base + Duration::seconds(self.tai_minus_utc(utc) + 1);
+        }
+        naive + Duration::seconds(self.tai_minus_utc(utc))
+    }
+
+    pub fn tai_to_utc(&self, tai: NaiveDateTime) -> DateTime<Utc> {
+        let idx = self
+            .entries
+            .partition_point(|(start, offset)| *start + Duration::seconds(*offset) <= tai);
+        if idx < self.entries.len() && idx > 0 {
+            // 正闰秒那一秒落在下一条目生效前的最后一秒内
+            let (start, offset) = self.entries[idx];
+            let leap_start = start + Duration::seconds(offset - 1);
+            if tai >= leap_start && offset > self.entries[idx - 1].1 {
+                let into = tai - leap_start;
+                let last = start - Duration::seconds(1);
+                let nanos = 1_000_000_000 + into.num_nanoseconds().unwrap_or(0) as u32;
+                return Utc.from_utc_datetime(&last.with_nanosecond(nanos).unwrap());
+            }
+        }
+        let offset = self.entries[idx.saturating_sub(1)].1;
+        Utc.from_utc_datetime(&(tai - Duration::seconds(offset)))
+    }
+
+    /// Label of `utc` on another time scale.
+    pub fn to_scale(&self, utc: DateTime<Utc>, scale: TimeScale) -> NaiveDateTime {
+        let tai = self.utc_to_tai(utc);
+        match scale {
+            TimeScale::Utc => utc.naive_utc(),
+            TimeScale::Tai => tai,
+            TimeScale::Tt => tai + tt_minus_tai(),
+            TimeScale::Gps => tai - Duration::seconds(TAI_MINUS_GPS),
+        }
+    }
+
+    pub fn from_scale(&self, time: NaiveDateTime, scale: TimeScale) -> DateTime<Utc> {
+        match scale {
+            TimeScale::Utc => Utc.from_utc_datetime(&time),
+            TimeScale::Tai => self.tai_to_utc(time),
+            TimeScale::Tt => self.tai_to_utc(time - tt_minus_tai()),
+            TimeScale::Gps => self.tai_to_utc(time + Duration::seconds(TAI_MINUS_GPS)),
+        }
+    }
+}
+
+/// Converts `utc` to another scale with the global leap-second table.
+pub fn utc_to(utc: DateTime<Utc>, scale: TimeScale) -> NaiveDateTime {
+    LeapSeconds::global().to_scale(utc, scale)
+}
+
+/// Converts a label on `scale` to UTC with the global leap-second table.
+pub fn utc_from(time: NaiveDateTime, scale: TimeScale) -> DateTime<Utc> {
+    LeapSeconds::global().from_scale(time, scale)
+}
+
+/// Checks a FITS `TIMESYS` value against the instrument's declaration of
+/// whether its MET counts leap seconds.
+pub fn check_timesys<I: Instrument>(timesys: &str) -> Result<(), Error> {
+    let continuous = match timesys.trim().to_ascii_uppercase().as_str() {
+        "UTC" => false,
+        "TT" | "TAI" | "GPS" | "TDB" => true,
+        other => {
+            return Err(Error::InvalidData(format!(
+                "{}: unknown TIMESYS '{other}'",
+                I::name()
+            )));
+        }
+    };
+    if continuous != I::met_counts_leap_seconds() {
+        return Err(Error::InvalidData(format!(
+            "{}: TIMESYS '{timesys}' contradicts met_counts_leap_seconds() = {}",
+            I::name(),
+            I::met_counts_leap_seconds()
+        )));
+    }
+    Ok(())
+}
+
+fn tt_minus_tai() -> Duration {
+    Duration::nanoseconds((TT_MINUS_TAI * 1e9) as i64)
+}
+
+fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+/// `DELTET/DELTA_AT = ( 10, @1972-JAN-1  11, @1972-JUL-1 ... )`
+fn parse_lsk(text: &str) -> Result<Vec<(NaiveDateTime, i64)>, Error> {
+    let invalid = |what: &str| Error::InvalidData(format!("LSK DELTA_AT: {what}"));
+    let start = text.find("DELTA_AT").unwrap();
+    let body = &text[start..];
+    let open = body.find('(').ok_or_else(|| invalid("missing '('"))?;
+    let close = body.find(')').ok_or_else(|| invalid("missing ')'"))?;
+    let tokens = body[open + 1..close]
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+
+    tokens
+        .chunks(2)
+        .map(|pair| {
+            let [offset, date] = pair else {
+                return Err(invalid("odd number of values"));
+            };
+            let offset = offset.parse::<f64>().map_err(|_| invalid(offset))? as i64;
+            let date = date.trim_start_matches('@');
+            let date = NaiveDate::parse_from_str(date, "%Y-%b-%d").map_err(|_| invalid(date))?;
+            Ok((date.and_hms_opt(0, 0, 0).unwrap(), offset))
+        })
+        .collect()
+}
+
+/// `leap-seconds.list` (NTP seconds, offset) or `Leap_Second.dat`
+/// (MJD, day, month, year, offset); `#` starts a comment.
+fn parse_iers(text: &str) -> Result<Vec<(NaiveDateTime, i64)>, Error> {
+    let ntp_epoch = midnight(1900, 1, 1);
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let invalid = || Error::InvalidData(format!("leap-second line: {line}"));
+            let fields = line
+                .split_whitespace()
+                .map(|field| field.parse::<f64>().map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?;
+            match fields.as_slice() {
+                [ntp, offset] => Ok((ntp_epoch + Duration::seconds(*ntp as i64), *offset as i64)),
+                [_mjd, day, month, year, offset] => {
+                    let date = NaiveDate::from_ymd_opt(*year as i32, *month as u32, *day as u32)
+                        .ok_or_else(invalid)?;
+                    Ok((date.and_hms_opt(0, 0, 0).unwrap(), *offset as i64))
+                }
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_agree_with_builtin() {
+        let lsk = "\\begindata\nDELTET/DELTA_AT = ( 10, @1972-JAN-1\n  11, @1972-JUL-1\n 37, @2017-JAN-1 )\n\\begintext\n";
+        let list = "#@ 3944678400\n2272060800\t10\t# 1 Jan 1972\n2287785600\t11\n3692217600\t37\t# 1 Jan 2017\n";
+        let dat =
+            "# MJD Date TAI-UTC\n41317.0 1 1 1972 10\n41499.0 1 7 1972 11\n57754.0 1 1 2017 37\n";
+        let builtin = LeapSeconds::builtin();
+        for text in [lsk, list, dat] {
+            let table = LeapSeconds::parse(text).unwrap();
+            assert_eq!(table.last_change(), builtin.last_change());
+            let t = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+            assert_eq!(table.tai_minus_utc(t), 37);
+        }
+    }
+
+    #[test]
+    fn scales_round_trip_across_leap_second() {
+        let table = LeapSeconds::builtin();
+        let before = Utc.with_ymd_and_hms(2016, 12, 31, 23, 59, 59).unwrap();
+        let leap = before.with_nanosecond(1_500_000_000).unwrap();
+        let after = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+
+        let tai = |t| table.utc_to_tai(t);
+        assert_eq!((tai(after) - tai(before)).num_seconds(), 2);
+        assert_eq!(table.tai_to_utc(tai(leap)), leap);
+        for t in [before, after] {
+            for scale in [
+                TimeScale::Utc,
+                TimeScale::Tai,
+                TimeScale::Tt,
+                TimeScale::Gps,
+            ] {
+                assert_eq!(table.from_scale(table.to_scale(t, scale), scale), t);
+            }
+        }
+        let gps = table.to_scale(after, TimeScale::Gps);
+        assert_eq!((gps - after.naive_utc()).num_seconds(), 18);
+    }
+}
@@ -1,10 +1,12 @@
 pub mod chunk;
+pub mod energy_calibration;
 pub mod event;
 pub mod instrument;
 pub mod interpolatable;
 pub mod temporal;
 
 pub use chunk::Chunk;
+pub use energy_calibration::EnergyCalibration;
 pub use event::Event;
 pub use instrument::Instrument;
 pub use interpolatable::Interpolatable;
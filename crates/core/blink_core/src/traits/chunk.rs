@@ -1,14 +1,129 @@
 use crate::error::Error;
 use crate::traits::Event;
-use crate::types::Signal;
-use chrono::prelude::*;
+use crate::types::{MissionElapsedTime, Signal};
+use chrono::{TimeDelta, prelude::*};
+use std::fmt::Debug;
+use uom::si::f64::Time;
 
 pub trait Chunk {
     type Event: Event;
+    /// Settings of [`Chunk::search_with`].
+    type SearchConfig: Clone + Debug;
 
-    fn from_epoch(epoch: &DateTime<Utc>) -> Result<Self, Error>
+    /// Loads `[start, stop)` plus up to `margin` of data on either side.
+    ///
+    /// The margin only feeds the background estimate: `search` reports
+    /// signals starting inside `[start, stop)`, so adjacent chunks loaded
+    /// with overlapping margins never report the same signal twice. Missing
+    /// files in the margin shrink it instead of failing the chunk.
+    fn from_range(
+        start: &DateTime<Utc>,
+        stop: &DateTime<Utc>,
+        margin: TimeDelta,
+    ) -> Result<Self, Error>
     where
         Self: Sized;
-    fn search(&self) -> Vec<Signal<Self::Event>>;
+    fn from_epoch(epoch: &DateTime<Utc>) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::from_range(epoch, &(*epoch + TimeDelta::hours(1)), TimeDelta::zero())
+    }
+    fn search(&self) -> Vec<Signal<Self::Event>> {
+        self.search_with(&Self::search_config(), Vec::new())
+    }
+    /// Searches with `config` after merging the time-sorted `injected`
+    /// events into the event stream.
+    fn search_with(
+        &self,
+        config: &Self::SearchConfig,
+        injected: Vec<Self::Event>,
+    ) -> Vec<Signal<Self::Event>> {
+        let mut events = self.events();
+        if !injected.is_empty() {
+            events.extend(injected);
+            // 两段各自有序，稳定排序只做一次归并
+            events.sort_by_key(|event| event.time());
+        }
+        self.search_events(config, events)
+    }
+    /// Searches `events` in place of the loaded ones, keeping the chunk's
+    /// attitude, orbit and excluded intervals.
+    fn search_events(
+        &self,
+        config: &Self::SearchConfig,
+        events: Vec<Self::Event>,
+    ) -> Vec<Signal<Self::Event>>;
+    /// Every loaded event including the margins, unfiltered and sorted by
+    /// time.
+    fn events(&self) -> Vec<Self::Event>;
+    /// Time of `[start, stop)` actually searched, without the excluded
+    /// intervals and data gaps; the exposure of [`Chunk::search`].
+    fn live_time(&self) -> Time;
+    /// The configuration [`Chunk::search`] uses.
+    fn search_config() -> Self::SearchConfig;
+    /// Number of detectors synthetic events can be placed in.
+    fn detectors() -> usize;
+    /// An event of `energy` keV passing `Event::keep`, for injecting
+    /// synthetic bursts.
+    fn synthetic_event(
+        time: MissionElapsedTime<<Self::Event as Event>::Instrument>,
+        energy: f64,
+        detector: usize,
+    ) -> Self::Event;
     fn last_modified(epoch: &DateTime<Utc>) -> Result<DateTime<Utc>, Error>;
 }
+
+/// Start of every hour overlapping `[start, stop)`, i.e. the hourly files
+/// needed to cover the range.
+pub fn hours(start: &DateTime<Utc>, stop: &DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let mut hour = start
+        .with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(*start);
+    let mut hours = Vec::new();
+    while hour < *stop {
+        hours.push(hour);
+        hour += TimeDelta::hours(1);
+    }
+    hours
+}
+
+/// Checks the arguments of [`Chunk::from_range`].
+pub fn check_range(
+    start: &DateTime<Utc>,
+    stop: &DateTime<Utc>,
+    margin: TimeDelta,
+) -> Result<(), Error> {
+    if start >= stop || margin < TimeDelta::zero() {
+        return Err(Error::InvalidArgument(format!(
+            "chunk range {start} .. {stop} with margin {margin}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hours_cover_partial_range() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 22, 59, 59).unwrap();
+        let stop = Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 1).unwrap();
+        let covered = hours(&start, &stop);
+        assert_eq!(covered.len(), 3);
+        assert_eq!(
+            covered[0],
+            Utc.with_ymd_and_hms(2025, 1, 1, 22, 0, 0).unwrap()
+        );
+        assert_eq!(
+            covered[2],
+            Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap()
+        );
+
+        let aligned = Utc.with_ymd_and_hms(2025, 1, 1, 3, 0, 0).unwrap();
+        assert_eq!(hours(&aligned, &(aligned + TimeDelta::hours(1))), [aligned]);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::traits::Instrument;
+use crate::types::MissionElapsedTime;
+
+/// Relation between the raw channel of an event and the energy it deposited,
+/// which may differ between detectors and drift with time.
+///
+/// Energies are in keV. `energy` must increase with the channel.
+pub trait EnergyCalibration<I: Instrument>: Send + Sync {
+    /// Energy of `channel` in `detector` at `time`.
+    fn energy(&self, detector: usize, time: MissionElapsedTime<I>, channel: f64) -> f64;
+
+    /// Channel of `energy` in `detector` at `time`, inverting `energy`.
+    fn channel(&self, detector: usize, time: MissionElapsedTime<I>, energy: f64) -> f64 {
+        let (mut low, mut high) = (-1024.0, 65536.0);
+        for _ in 0..64 {
+            let middle = (low + high) / 2.0;
+            if self.energy(detector, time, middle) < energy {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        (low + high) / 2.0
+    }
+}
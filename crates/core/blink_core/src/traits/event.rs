@@ -2,6 +2,7 @@ use crate::traits::Instrument;
 use crate::types::MissionElapsedTime;
 use serde::Serialize;
 use std::fmt::Debug;
+use uom::si::f64::Time;
 
 pub trait Event: Serialize + Debug + Clone {
     type Instrument: Instrument;
@@ -10,7 +11,17 @@ pub trait Event: Serialize + Debug + Clone {
 
     fn time(&self) -> MissionElapsedTime<Self::Instrument>;
     fn channel(&self) -> Self::ChannelType;
+    /// Calibrated energy in keV.
+    fn energy(&self) -> f64;
     // fn detector(&self) -> Self::DetectorType;
+    /// Trigger group, an index into `Instrument::group_names`.
     fn group(&self) -> u8;
     fn keep(&self) -> bool;
+    /// A copy of the event moved to `time`, for scrambling event streams.
+    fn with_time(&self, time: MissionElapsedTime<Self::Instrument>) -> Self;
+    /// Dead time recorded on board for this event, if the instrument
+    /// provides it.
+    fn dead_time(&self) -> Option<Time> {
+        None
+    }
 }
@@ -1,9 +1,25 @@
 use chrono::prelude::*;
 
-pub trait Instrument: Clone + Copy + PartialEq + Eq + PartialOrd + Ord {
+pub trait Instrument:
+    Clone + Copy + PartialEq + Eq + PartialOrd + Ord + Send + Sync + 'static
+{
     type Chunk: crate::traits::Chunk;
 
     fn ref_time() -> &'static DateTime<Utc>;
     fn launch_day() -> NaiveDate;
     fn name() -> &'static str;
+    /// Instrument-frame unit vector of the boresight.
+    fn boresight() -> [f64; 3];
+    /// Instrument-frame unit vectors of the detector axes, one per detector
+    /// rather than per trigger group. Index `i` is the detector numbered `i`
+    /// in `Characteristics::detector_counts` and `Chunk::detectors`, so the
+    /// angles of `Geometry` line up with the per-detector counts.
+    fn detector_axes() -> Vec<[f64; 3]>;
+    /// Names of the trigger groups numbered by `Event::group`.
+    fn group_names() -> &'static [&'static str];
+    /// Total geometric area of the detectors in cm².
+    fn detector_area() -> f64;
+    /// Whether the MET clock runs uniformly through leap seconds (TT/TAI
+    /// based) rather than following UTC.
+    fn met_counts_leap_seconds() -> bool;
 }
@@ -1,3 +1,12 @@
 pub trait Interpolatable {
     fn interpolate(&self, other: &Self, factor: f64) -> Self;
+
+    /// Interpolation that may use the spacing of the two samples in seconds,
+    /// e.g. to scale derivatives for Hermite splines.
+    fn interpolate_over(&self, other: &Self, factor: f64, _span: f64) -> Self
+    where
+        Self: Sized,
+    {
+        self.interpolate(other, factor)
+    }
 }
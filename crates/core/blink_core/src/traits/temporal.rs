@@ -3,20 +3,29 @@ use std::ops::Add;
 use std::ops::Sub;
 
 pub trait Temporal:
-    PartialOrd + Copy + Add<Self::Duration, Output = Self> + Sub<Self::Duration, Output = Self>
+    PartialOrd
+    + Copy
+    + Add<Self::Duration, Output = Self>
+    + Sub<Self::Duration, Output = Self>
+    + Sub<Self, Output = Self::Duration>
 {
-    type Duration: Copy;
+    type Duration: Copy + PartialOrd;
 
     fn lerp_factor(self, start: Self, end: Self) -> f64;
+    fn seconds_since(self, earlier: Self) -> f64;
 }
 
 impl<I: Instrument> Temporal for MissionElapsedTime<I> {
     type Duration = uom::si::f64::Time;
 
     fn lerp_factor(self, start: Self, end: Self) -> f64 {
-        let duration_total = end.time() - start.time();
-        let duration_part = self.time() - start.time();
-        (duration_part / duration_total).get::<uom::si::ratio::ratio>()
+        let duration_total = end.nanoseconds() - start.nanoseconds();
+        let duration_part = self.nanoseconds() - start.nanoseconds();
+        duration_part as f64 / duration_total as f64
+    }
+
+    fn seconds_since(self, earlier: Self) -> f64 {
+        (self - earlier).get::<uom::si::time::second>()
     }
 }
 
@@ -29,4 +38,11 @@ impl Temporal for chrono::DateTime<chrono::Utc> {
         duration_part.num_nanoseconds().unwrap() as f64
             / duration_total.num_nanoseconds().unwrap() as f64
     }
+
+    fn seconds_since(self, earlier: Self) -> f64 {
+        self.signed_duration_since(earlier)
+            .num_nanoseconds()
+            .unwrap() as f64
+            / 1e9
+    }
 }
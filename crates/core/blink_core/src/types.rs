@@ -1,15 +1,35 @@
 pub mod attitude;
+pub mod characteristics;
+pub mod classification;
+pub mod dead_time;
 pub mod ebounds;
+pub mod episode;
+pub mod gain_history;
+pub mod geomagnetic;
+pub mod geometry;
 pub mod mission_elapsed_time;
+pub mod orbit_state;
+pub mod pointing;
 pub mod position;
+pub mod region;
 pub mod signal;
 pub mod temporal_state;
 pub mod trajectory;
 
 pub use attitude::Attitude;
+pub use characteristics::Characteristics;
+pub use classification::{Classification, Label};
+pub use dead_time::DeadTimeCorrected;
 pub use ebounds::Ebounds;
+pub use episode::Episode;
+pub use gain_history::{GainEpoch, GainHistory};
+pub use geomagnetic::Geomagnetic;
+pub use geometry::{Geometry, Incidence};
 pub use mission_elapsed_time::MissionElapsedTime;
+pub use orbit_state::OrbitState;
+pub use pointing::Pointing;
 pub use position::Position;
+pub use region::Region;
 pub use signal::{Signal, UnifiedSignal};
 pub use temporal_state::TemporalState;
-pub use trajectory::Trajectory;
+pub use trajectory::{Extrapolation, Trajectory};
@@ -2,19 +2,213 @@ use serde::{Deserialize, Serialize};
 
 use crate::traits::Interpolatable;
 
-#[derive(Clone, Serialize, Deserialize)]
+/// Unit quaternion `q0 + q1 i + q2 j + q3 k` (scalar first) rotating
+/// instrument-frame vectors into J2000/ECI.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(from = "AttitudeRecord")]
 pub struct Attitude {
+    pub q0: f64,
     pub q1: f64,
     pub q2: f64,
     pub q3: f64,
 }
 
-impl Interpolatable for Attitude {
-    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+/// Serialised form; records written before the scalar part was stored carry
+/// the vector part only.
+#[derive(Deserialize)]
+struct AttitudeRecord {
+    q0: Option<f64>,
+    q1: f64,
+    q2: f64,
+    q3: f64,
+}
+
+impl From<AttitudeRecord> for Attitude {
+    fn from(record: AttitudeRecord) -> Self {
+        match record.q0 {
+            Some(q0) => Attitude {
+                q0,
+                q1: record.q1,
+                q2: record.q2,
+                q3: record.q3,
+            },
+            None => Attitude::from_vector_part(record.q1, record.q2, record.q3),
+        }
+    }
+}
+
+impl Attitude {
+    pub fn new(q0: f64, q1: f64, q2: f64, q3: f64) -> Self {
+        Attitude { q0, q1, q2, q3 }.normalized()
+    }
+
+    /// Completes a quaternion stored as its vector part only (e.g. HXMT
+    /// `ATT_Quater`), taking the non-negative scalar part.
+    pub fn from_vector_part(q1: f64, q2: f64, q3: f64) -> Self {
+        let q0 = (1.0 - q1 * q1 - q2 * q2 - q3 * q3).max(0.0).sqrt();
+        Self::new(q0, q1, q2, q3)
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(&self) -> Self {
+        let norm = self.norm();
+        if norm == 0.0 {
+            return Attitude {
+                q0: 1.0,
+                q1: 0.0,
+                q2: 0.0,
+                q3: 0.0,
+            };
+        }
+        Attitude {
+            q0: self.q0 / norm,
+            q1: self.q1 / norm,
+            q2: self.q2 / norm,
+            q3: self.q3 / norm,
+        }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Attitude {
+            q0: self.q0,
+            q1: -self.q1,
+            q2: -self.q2,
+            q3: -self.q3,
+        }
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.q0 * other.q0 + self.q1 * other.q1 + self.q2 * other.q2 + self.q3 * other.q3
+    }
+
+    /// Rotates an instrument-frame vector into J2000.
+    pub fn to_j2000(&self, v: [f64; 3]) -> [f64; 3] {
+        rotate(self, v)
+    }
+
+    /// Rotates a J2000 vector into the instrument frame.
+    pub fn to_instrument(&self, v: [f64; 3]) -> [f64; 3] {
+        rotate(&self.conjugate(), v)
+    }
+
+    /// Right ascension and declination (degrees) of an instrument-frame axis.
+    pub fn radec(&self, axis: [f64; 3]) -> (f64, f64) {
+        let [x, y, z] = self.to_j2000(axis);
+        let norm = (x * x + y * y + z * z).sqrt();
+        let ra = y.atan2(x).to_degrees().rem_euclid(360.0);
+        let dec = (z / norm).clamp(-1.0, 1.0).asin().to_degrees();
+        (ra, dec)
+    }
+
+    /// Spherical linear interpolation along the shorter arc.
+    pub fn slerp(&self, other: &Self, ratio: f64) -> Self {
+        let mut cos_theta = self.dot(other);
+        let other = if cos_theta < 0.0 {
+            cos_theta = -cos_theta;
+            Attitude {
+                q0: -other.q0,
+                q1: -other.q1,
+                q2: -other.q2,
+                q3: -other.q3,
+            }
+        } else {
+            other.clone()
+        };
+
+        let (w0, w1) = if cos_theta > 0.9995 {
+            // 夹角极小时 sin(theta) → 0，退化为归一化线性插值
+            (1.0 - ratio, ratio)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - ratio) * theta).sin() / sin_theta,
+                (ratio * theta).sin() / sin_theta,
+            )
+        };
+
         Attitude {
-            q1: self.q1 + (other.q1 - self.q1) * ratio,
-            q2: self.q2 + (other.q2 - self.q2) * ratio,
-            q3: self.q3 + (other.q3 - self.q3) * ratio,
+            q0: w0 * self.q0 + w1 * other.q0,
+            q1: w0 * self.q1 + w1 * other.q1,
+            q2: w0 * self.q2 + w1 * other.q2,
+            q3: w0 * self.q3 + w1 * other.q3,
         }
+        .normalized()
+    }
+}
+
+fn rotate(q: &Attitude, v: [f64; 3]) -> [f64; 3] {
+    // v' = v + 2 u × (u × v + q0 v)，u 为矢量部
+    let u = [q.q1, q.q2, q.q3];
+    let t = cross(u, v);
+    let t = [t[0] + q.q0 * v[0], t[1] + q.q0 * v[1], t[2] + q.q0 * v[2]];
+    let t = cross(u, t);
+    [v[0] + 2.0 * t[0], v[1] + 2.0 * t[1], v[2] + 2.0 * t[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+impl Interpolatable for Attitude {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        self.slerp(other, ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f64; 3], b: [f64; 3]) -> bool {
+        a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-12)
+    }
+
+    #[test]
+    fn rotation_about_z() {
+        let half = std::f64::consts::FRAC_PI_4;
+        let q = Attitude::new(half.cos(), 0.0, 0.0, half.sin());
+        assert!(close(q.to_j2000([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]));
+        assert!(close(q.to_instrument([0.0, 1.0, 0.0]), [1.0, 0.0, 0.0]));
+        let (ra, dec) = q.radec([1.0, 0.0, 0.0]);
+        assert!((ra - 90.0).abs() < 1e-9 && dec.abs() < 1e-9);
+    }
+
+    #[test]
+    fn slerp_midpoint_and_sign() {
+        let identity = Attitude::new(1.0, 0.0, 0.0, 0.0);
+        let half = std::f64::consts::FRAC_PI_4;
+        let q = Attitude::new(half.cos(), 0.0, 0.0, half.sin());
+        // q 与 -q 表示同一旋转，插值结果应一致
+        let neg = Attitude::new(-q.q0, -q.q1, -q.q2, -q.q3);
+        let mid = identity.slerp(&q, 0.5);
+        let mid_neg = identity.slerp(&neg, 0.5);
+        let expected = std::f64::consts::FRAC_PI_8;
+        assert!((mid.q0 - expected.cos()).abs() < 1e-12);
+        assert!((mid.q3 - expected.sin()).abs() < 1e-12);
+        assert!((mid.dot(&mid_neg) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vector_part_completes_scalar() {
+        let q = Attitude::from_vector_part(0.0, 0.6, 0.0);
+        assert!((q.q0 - 0.8).abs() < 1e-12);
+        assert!((q.norm() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn old_records_complete_the_scalar_part() {
+        let q: Attitude = serde_json::from_str(r#"{"q1":0.0,"q2":0.6,"q3":0.0}"#).unwrap();
+        assert!((q.q0 - 0.8).abs() < 1e-12);
+        let q: Attitude =
+            serde_json::from_str(r#"{"q0":-0.8,"q1":0.0,"q2":0.6,"q3":0.0}"#).unwrap();
+        assert_eq!(q.q0, -0.8);
     }
 }
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// Temporal and spectral summary of a signal, computed from its events after
+/// the search. Durations are in seconds and rates in counts per second.
+///
+/// "Filtered" events are those passing `Event::keep`, "full" refers to the
+/// whole signal and "best" to its best bin.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct Characteristics {
+    pub duration_full: f64,
+    pub duration_best: f64,
+    pub count_unfiltered_full: u32,
+    pub count_unfiltered_best: u32,
+    pub count_filtered_full: u32,
+    pub count_filtered_best: u32,
+    /// Background rate of filtered events
+    pub background: f64,
+    pub flux_unfiltered_full: f64,
+    pub flux_unfiltered_best: f64,
+    pub flux_filtered_full: f64,
+    pub flux_filtered_best: f64,
+    /// Background-subtracted durations containing 50% and 90% of the counts,
+    /// with bootstrap standard deviations
+    pub t50: f64,
+    pub t50_error: f64,
+    pub t90: f64,
+    pub t90_error: f64,
+    /// Highest rate in the peak light curve
+    pub peak_rate: f64,
+    /// From the start of T90 to the peak, and from the peak to its end
+    pub rise_time: f64,
+    pub fall_time: f64,
+    /// From the start of the first to the end of the last Bayesian Block
+    /// brighter than the background blocks at either end of the window
+    #[serde(default)]
+    pub block_duration: f64,
+    /// Whether those blocks overlap the signal, a check on the merging of
+    /// candidates that does not depend on the search bins
+    #[serde(default)]
+    pub block_overlap: bool,
+    /// Mean energy in keV of the filtered events of the signal
+    #[serde(default)]
+    pub mean_energy: f64,
+    /// Hard over soft counts, both background-subtracted
+    pub hardness_ratio: f64,
+    pub hardness_ratio_error: f64,
+    /// Filtered counts of the signal per detector
+    pub detector_counts: Vec<u32>,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What produced a signal, as judged from its events.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Label {
+    /// Not classified by the instrument
+    #[default]
+    Unclassified,
+    /// Consistent with a burst of photons
+    PhotonLike,
+    /// A cosmic-ray shower or other charged particles
+    ParticleLike,
+    /// An electronic artifact
+    GlitchLike,
+}
+
+/// Label of a signal with the features it was decided from.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct Classification {
+    pub label: Label,
+    /// Rules that fired, in the order they were checked
+    pub reasons: Vec<String>,
+    /// Instrument-specific features by name
+    pub features: BTreeMap<String, f64>,
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Dead-time corrected counts of the best bin of a signal.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct DeadTimeCorrected {
+    /// Fraction of the bin the detectors were live (recorded over corrected
+    /// counts)
+    pub live_fraction: f64,
+    /// Corrected counts, background included
+    pub count: f64,
+    pub count_error: f64,
+    /// Background-subtracted corrected counts per cm² of detector area
+    pub fluence: f64,
+    pub fluence_error: f64,
+}
@@ -1 +1,20 @@
+use crate::traits::{EnergyCalibration, Instrument};
+use crate::types::MissionElapsedTime;
+
+/// Energy bounds in keV of each channel, indexed by channel, as in the
+/// `EBOUNDS` extension of OGIP files.
 pub type Ebounds = Vec<[f64; 2]>;
+
+/// Channel `i` spans `[i, i + 1)` and maps linearly onto its bounds; the
+/// first and last channels extend beyond the table. The same bounds hold for
+/// every detector and time.
+impl<I: Instrument> EnergyCalibration<I> for Ebounds {
+    fn energy(&self, _detector: usize, _time: MissionElapsedTime<I>, channel: f64) -> f64 {
+        if self.is_empty() {
+            return channel;
+        }
+        let index = (channel.floor().max(0.0) as usize).min(self.len() - 1);
+        let [low, high] = self[index];
+        low + (channel - index as f64) * (high - low)
+    }
+}
@@ -0,0 +1,20 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Pulses close enough in time to be one multi-pulse event. Intervals are
+/// in seconds.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Episode {
+    pub start: DateTime<Utc>,
+    pub stop: DateTime<Utc>,
+    /// Start of each pulse in time order, which with the instrument
+    /// identifies its signal in the search output of that day
+    pub signals: Vec<DateTime<Utc>>,
+    /// Kept events of each pulse over its whole span, not only its best bin
+    pub counts: Vec<u32>,
+    /// Peak to peak time of consecutive pulses
+    pub intervals: Vec<f64>,
+    /// From the stop of each pulse to the start of the next
+    pub gaps: Vec<f64>,
+    pub instrument: String,
+}
@@ -0,0 +1,120 @@
+use crate::error::Error;
+use crate::traits::{EnergyCalibration, Instrument};
+use crate::types::MissionElapsedTime;
+use serde::{Deserialize, Serialize};
+
+/// Linear gain of every detector from `met` on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GainEpoch {
+    /// Mission elapsed time in seconds
+    pub met: f64,
+    /// Energy in keV at channel 0, one per detector or one for all
+    pub offset: Vec<f64>,
+    /// keV per channel, one per detector or one for all
+    pub gain: Vec<f64>,
+}
+
+/// Per-detector linear calibrations measured at successive times, e.g. from
+/// the line fits of each calibration campaign. Coefficients are interpolated
+/// linearly between epochs and held before the first and after the last.
+///
+/// Channel `c` spans `[c, c + 1)`, so an event of channel `c` sits at
+/// `offset + gain × (c + 0.5)`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GainHistory {
+    /// Sorted by `met`
+    pub epochs: Vec<GainEpoch>,
+}
+
+impl GainHistory {
+    /// The same calibration for every detector at all times.
+    pub fn uniform(offset: f64, gain: f64) -> Self {
+        Self {
+            epochs: vec![GainEpoch {
+                met: 0.0,
+                offset: vec![offset],
+                gain: vec![gain],
+            }],
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)?;
+        let history: Self =
+            serde_json::from_str(&text).map_err(|e| Error::InvalidData(format!("{path}: {e}")))?;
+        let valid = !history.epochs.is_empty()
+            && history
+                .epochs
+                .windows(2)
+                .all(|pair| pair[0].met < pair[1].met)
+            && history.epochs.iter().all(|epoch| {
+                !epoch.offset.is_empty()
+                    && !epoch.gain.is_empty()
+                    && epoch.gain.iter().all(|&gain| gain > 0.0)
+            });
+        if !valid {
+            return Err(Error::InvalidData(format!(
+                "{path}: epochs must be sorted by met with positive gains"
+            )));
+        }
+        Ok(history)
+    }
+
+    /// Offset and gain of `detector` at `met`.
+    fn coefficients(&self, detector: usize, met: f64) -> (f64, f64) {
+        let at = |epoch: &GainEpoch| {
+            let pick = |values: &[f64]| values.get(detector).copied().unwrap_or(values[0]);
+            (pick(&epoch.offset), pick(&epoch.gain))
+        };
+        let next = self.epochs.partition_point(|epoch| epoch.met <= met);
+        match (next.checked_sub(1), self.epochs.get(next)) {
+            (Some(previous), Some(following)) => {
+                let (previous, following) = (&self.epochs[previous], following);
+                let factor = (met - previous.met) / (following.met - previous.met);
+                let (a, b) = (at(previous), at(following));
+                (a.0 + (b.0 - a.0) * factor, a.1 + (b.1 - a.1) * factor)
+            }
+            (Some(previous), None) => at(&self.epochs[previous]),
+            (None, _) => at(&self.epochs[0]),
+        }
+    }
+}
+
+impl<I: Instrument> EnergyCalibration<I> for GainHistory {
+    fn energy(&self, detector: usize, time: MissionElapsedTime<I>, channel: f64) -> f64 {
+        let (offset, gain) = self.coefficients(detector, time.met());
+        offset + gain * channel
+    }
+
+    fn channel(&self, detector: usize, time: MissionElapsedTime<I>, energy: f64) -> f64 {
+        let (offset, gain) = self.coefficients(detector, time.met());
+        (energy - offset) / gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_drifts_between_epochs() {
+        let history = GainHistory {
+            epochs: vec![
+                GainEpoch {
+                    met: 100.0,
+                    offset: vec![0.0],
+                    gain: vec![10.0, 20.0],
+                },
+                GainEpoch {
+                    met: 200.0,
+                    offset: vec![10.0],
+                    gain: vec![12.0, 20.0],
+                },
+            ],
+        };
+        assert_eq!(history.coefficients(0, 50.0), (0.0, 10.0));
+        assert_eq!(history.coefficients(0, 150.0), (5.0, 11.0));
+        assert_eq!(history.coefficients(1, 150.0), (5.0, 20.0));
+        assert_eq!(history.coefficients(5, 250.0), (10.0, 12.0));
+    }
+}
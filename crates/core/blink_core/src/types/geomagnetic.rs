@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Geomagnetic context at the spacecraft.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct Geomagnetic {
+    /// IGRF field (nT) in local geodetic north, east, down
+    pub field: [f64; 3],
+    /// Latitude relative to the centred (IGRF n = 1) dipole (degrees)
+    pub dipole_latitude: f64,
+    /// Latitude from the IGRF inclination, tan λ = tan I / 2 (degrees)
+    pub dip_latitude: f64,
+    /// Geocentric distance of the apex of the IGRF field line through the
+    /// spacecraft (Earth radii), the dipole L rather than McIlwain's L;
+    /// `None` on open field lines
+    #[serde(alias = "l_shell")]
+    pub apex_radius: Option<f64>,
+    /// Størmer vertical cutoff rigidity (GV) with the apex radius as L
+    pub cutoff_rigidity: f64,
+}
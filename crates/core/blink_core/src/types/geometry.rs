@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    frames,
+    types::{Attitude, OrbitState},
+};
+
+/// Spacecraft–Earth geometry at the signal peak.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct Geometry {
+    /// Angle between each detector axis and nadir (degrees), in
+    /// `Instrument::detector_axes` order
+    pub nadir_angles: Vec<f64>,
+    /// Nadir angle of the Earth limb (degrees); directions closer to nadir
+    /// than this point at the Earth
+    pub earth_limb_angle: f64,
+}
+
+impl Geometry {
+    pub fn new(attitude: &Attitude, orbit: &OrbitState, axes: &[[f64; 3]]) -> Self {
+        let nadir = attitude.to_instrument(unit(orbit.position.map(|x| -x)));
+        Geometry {
+            nadir_angles: axes.iter().map(|axis| angle(*axis, nadir)).collect(),
+            earth_limb_angle: earth_limb_angle(frames::norm(orbit.position)),
+        }
+    }
+}
+
+/// How photons from a point source on or above the Earth reach the
+/// instrument.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Incidence {
+    /// Unit vector from the spacecraft to the source, instrument frame
+    pub direction: [f64; 3],
+    /// Angle between each detector axis and the source (degrees)
+    pub angles: Vec<f64>,
+    /// Angle between the source and nadir seen from the spacecraft (degrees)
+    pub nadir_angle: f64,
+    /// Elevation of the spacecraft above the source's horizon (degrees);
+    /// negative means the source is hidden behind the Earth
+    pub elevation: f64,
+}
+
+impl Incidence {
+    /// `spacecraft` and `source` are geocentric positions in J2000 (m).
+    pub fn new(
+        attitude: &Attitude,
+        spacecraft: [f64; 3],
+        source: [f64; 3],
+        axes: &[[f64; 3]],
+    ) -> Self {
+        let line_of_sight = std::array::from_fn(|i| source[i] - spacecraft[i]);
+        let direction = attitude.to_instrument(unit(line_of_sight));
+        Incidence {
+            angles: axes.iter().map(|axis| angle(*axis, direction)).collect(),
+            direction,
+            nadir_angle: angle(line_of_sight, spacecraft.map(|x| -x)),
+            elevation: 90.0 - angle(line_of_sight.map(|x| -x), source),
+        }
+    }
+}
+
+/// Nadir angle of the limb of a spherical Earth seen from geocentric
+/// distance `radius` (m).
+pub fn earth_limb_angle(radius: f64) -> f64 {
+    (frames::EARTH_MEAN_RADIUS / radius)
+        .clamp(-1.0, 1.0)
+        .asin()
+        .to_degrees()
+}
+
+/// Angle between two vectors (degrees).
+pub fn angle(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (frames::dot(a, b) / (frames::norm(a) * frames::norm(b)))
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees()
+}
+
+fn unit(v: [f64; 3]) -> [f64; 3] {
+    let n = frames::norm(v);
+    v.map(|x| x / n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nadir_pointing_and_stroke_below() {
+        let radius = frames::EARTH_MEAN_RADIUS + 550e3;
+        let orbit = OrbitState {
+            position: [radius, 0.0, 0.0],
+            velocity: [0.0, 7_600.0, 0.0],
+        };
+        // 绕 z 转 180°：仪器 +X 指向 -X（天底）
+        let attitude = Attitude::new(0.0, 0.0, 0.0, 1.0);
+        let geometry = Geometry::new(&attitude, &orbit, &[[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert!(geometry.nadir_angles[0].abs() < 1e-9);
+        assert!((geometry.nadir_angles[1] - 90.0).abs() < 1e-9);
+        assert!((geometry.earth_limb_angle - 67.0).abs() < 0.05);
+
+        let source = [frames::EARTH_MEAN_RADIUS, 0.0, 0.0];
+        let incidence = Incidence::new(&attitude, orbit.position, source, &[[1.0, 0.0, 0.0]]);
+        assert!(incidence.angles[0].abs() < 1e-9);
+        assert!(incidence.nadir_angle.abs() < 1e-9);
+        assert!((incidence.elevation - 90.0).abs() < 1e-9);
+    }
+}
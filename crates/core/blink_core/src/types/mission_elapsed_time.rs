@@ -1,20 +1,59 @@
-use crate::traits::Instrument;
+use crate::{time::LeapSeconds, traits::Instrument};
 use chrono::{Duration, prelude::*};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 use std::ops::{Add, Sub};
-use std::{marker::PhantomData, sync::LazyLock};
 use uom::si::f64::*;
 use uom::si::time::second;
 
-#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+/// Mission elapsed time as whole seconds plus nanoseconds, so that ordering,
+/// arithmetic and UTC conversion are exact at nanosecond precision. An f64
+/// MET has ~60 ns resolution at 4.5e8 s.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(from = "MetRecord")]
 pub struct MissionElapsedTime<I: Instrument> {
-    time: Time,
+    seconds: i64,
+    /// Always in `0..1_000_000_000`
+    nanos: u32,
     _phantom: PhantomData<I>,
 }
 
+/// Serialised form; records written before the split into seconds and
+/// nanoseconds carry the MET as f64 seconds in `time`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MetRecord {
+    Parts { seconds: i64, nanos: u32 },
+    Seconds { time: f64 },
+}
+
+impl<I: Instrument> From<MetRecord> for MissionElapsedTime<I> {
+    fn from(record: MetRecord) -> Self {
+        match record {
+            MetRecord::Parts { seconds, nanos } => Self::from_parts(seconds, nanos as i64),
+            MetRecord::Seconds { time } => Self::new(time),
+        }
+    }
+}
+
+impl<I: Instrument> PartialEq for MissionElapsedTime<I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seconds == other.seconds && self.nanos == other.nanos
+    }
+}
+
 impl<I: Instrument> Eq for MissionElapsedTime<I> {}
 
+impl<I: Instrument> Hash for MissionElapsedTime<I> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.seconds, self.nanos).hash(state);
+    }
+}
+
 impl<I: Instrument> PartialOrd for MissionElapsedTime<I> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -23,27 +62,63 @@ impl<I: Instrument> PartialOrd for MissionElapsedTime<I> {
 
 impl<I: Instrument> Ord for MissionElapsedTime<I> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.time
-            .partial_cmp(&other.time)
-            .unwrap_or(Ordering::Equal)
+        (self.seconds, self.nanos).cmp(&(other.seconds, other.nanos))
     }
 }
 
 impl<I: Instrument> MissionElapsedTime<I> {
+    /// Rounds `met` (seconds) to the nearest nanosecond.
     pub fn new(met: f64) -> Self {
+        let seconds = met.floor();
+        // met - floor(met) 在 f64 中是精确的，舍入只发生在乘 1e9 之后
+        let nanos = ((met - seconds) * 1e9).round() as i64;
+        Self::from_parts(seconds as i64, nanos)
+    }
+
+    /// `seconds + nanos × 1e-9`; `nanos` may be out of range or negative.
+    pub fn from_parts(seconds: i64, nanos: i64) -> Self {
+        Self {
+            seconds: seconds + nanos.div_euclid(NANOS_PER_SECOND),
+            nanos: nanos.rem_euclid(NANOS_PER_SECOND) as u32,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn from_nanoseconds(nanoseconds: i128) -> Self {
+        let per_second = NANOS_PER_SECOND as i128;
         Self {
-            time: Time::new::<second>(met),
+            seconds: nanoseconds.div_euclid(per_second) as i64,
+            nanos: nanoseconds.rem_euclid(per_second) as u32,
             _phantom: PhantomData,
         }
     }
 
+    /// Whole seconds, rounded towards negative infinity.
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    pub fn subsec_nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    pub fn nanoseconds(&self) -> i128 {
+        self.seconds as i128 * NANOS_PER_SECOND as i128 + self.nanos as i128
+    }
+
     pub fn time(&self) -> Time {
-        self.time
+        Time::new::<second>(self.met())
     }
 
     /// MET in seconds as f64.
     pub fn met(&self) -> f64 {
-        self.time.get::<second>()
+        self.seconds as f64 + self.nanos as f64 * 1e-9
+    }
+
+    /// Exact difference `self - earlier`.
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        Duration::seconds(self.seconds - earlier.seconds)
+            + Duration::nanoseconds(self.nanos as i64 - earlier.nanos as i64)
     }
 
     pub fn to_utc(&self) -> DateTime<Utc> {
@@ -51,152 +126,28 @@ impl<I: Instrument> MissionElapsedTime<I> {
     }
 }
 
-static LEAP_SECONDS: LazyLock<[DateTime<Utc>; 27]> = LazyLock::new(|| {
-    [
-        Utc.with_ymd_and_hms(1972, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1972, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1973, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1974, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1975, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1976, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1977, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1978, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1979, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1981, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1982, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1983, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1985, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1987, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1989, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1990, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1992, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1993, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1994, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1995, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1997, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(1998, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(2005, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(2008, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(2012, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(2015, 6, 30, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-        Utc.with_ymd_and_hms(2016, 12, 31, 23, 59, 59)
-            .unwrap()
-            .with_nanosecond(1_000_000_000)
-            .unwrap(),
-    ]
-});
-
 impl<I: Instrument> From<MissionElapsedTime<I>> for DateTime<Utc> {
     fn from(val: MissionElapsedTime<I>) -> Self {
-        let seconds = val.time.get::<second>();
-        let whole_seconds = seconds.trunc() as i64;
-        let nanoseconds = ((seconds.fract() * 1_000_000_000.0) as i64).clamp(0, 999_999_999);
-
-        let mut time =
-            *I::ref_time() + Duration::seconds(whole_seconds) + Duration::nanoseconds(nanoseconds);
-        for leap_second in LEAP_SECONDS.iter() {
-            if *I::ref_time() < *leap_second && time > *leap_second {
-                time -= Duration::seconds(1);
-            }
+        let elapsed = Duration::seconds(val.seconds) + Duration::nanoseconds(val.nanos as i64);
+
+        if I::met_counts_leap_seconds() {
+            let leap_seconds = LeapSeconds::global();
+            leap_seconds.tai_to_utc(leap_seconds.utc_to_tai(*I::ref_time()) + elapsed)
+        } else {
+            *I::ref_time() + elapsed
         }
-        time
     }
 }
 
 impl<I: Instrument> From<DateTime<Utc>> for MissionElapsedTime<I> {
     fn from(value: DateTime<Utc>) -> Self {
-        let duration = value - *I::ref_time();
-        let seconds = duration.num_seconds() as f64;
-        let nanoseconds = duration.subsec_nanos() as f64 / 1_000_000_000.0;
-
-        let mut time = seconds + nanoseconds;
-        for leap_second in LEAP_SECONDS.iter() {
-            if *I::ref_time() < *leap_second && value > *leap_second {
-                time += 1.0;
-            }
-        }
-        MissionElapsedTime {
-            time: Time::new::<second>(time),
-            _phantom: PhantomData,
-        }
+        let duration = if I::met_counts_leap_seconds() {
+            let leap_seconds = LeapSeconds::global();
+            leap_seconds.utc_to_tai(value) - leap_seconds.utc_to_tai(*I::ref_time())
+        } else {
+            value - *I::ref_time()
+        };
+        Self::from_parts(duration.num_seconds(), duration.subsec_nanos() as i64)
     }
 }
 
@@ -204,7 +155,8 @@ impl<I: Instrument> Sub for MissionElapsedTime<I> {
     type Output = Time;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        self.time - rhs.time
+        let nanos = self.nanos as f64 - rhs.nanos as f64;
+        Time::new::<second>((self.seconds - rhs.seconds) as f64 + nanos * 1e-9)
     }
 }
 
@@ -212,10 +164,7 @@ impl<I: Instrument> Sub<Time> for MissionElapsedTime<I> {
     type Output = Self;
 
     fn sub(self, rhs: Time) -> Self::Output {
-        MissionElapsedTime {
-            time: self.time - rhs,
-            _phantom: PhantomData,
-        }
+        self + -rhs
     }
 }
 
@@ -223,9 +172,58 @@ impl<I: Instrument> Add<Time> for MissionElapsedTime<I> {
     type Output = Self;
 
     fn add(self, rhs: Time) -> Self::Output {
-        MissionElapsedTime {
-            time: self.time + rhs,
-            _phantom: PhantomData,
-        }
+        let rhs = rhs.get::<second>();
+        let seconds = rhs.floor();
+        let nanos = ((rhs - seconds) * 1e9).round() as i64;
+        Self::from_parts(self.seconds + seconds as i64, self.nanos as i64 + nanos)
+    }
+}
+
+impl<I: Instrument> Add<Duration> for MissionElapsedTime<I> {
+    type Output = Self;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Self::from_parts(
+            self.seconds + rhs.num_seconds(),
+            self.nanos as i64 + rhs.subsec_nanos() as i64,
+        )
+    }
+}
+
+impl<I: Instrument> Sub<Duration> for MissionElapsedTime<I> {
+    type Output = Self;
+
+    fn sub(self, rhs: Duration) -> Self::Output {
+        self + -rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestInstrument;
+
+    type Met = MissionElapsedTime<TestInstrument>;
+
+    // MET ~4.5e8 s 处 f64 的 ulp 约 60 ns，整数秒 + 纳秒表示需逐纳秒精确
+    #[test]
+    fn met_nanosecond_precision() {
+        let a = Met::from_parts(446724004, 123_456_789);
+        let b = a + chrono::Duration::nanoseconds(1);
+        assert!(a < b);
+        assert_eq!(b.duration_since(a), chrono::Duration::nanoseconds(1));
+        assert_eq!(Met::from(a.to_utc()), a);
+        assert_eq!(b.to_utc() - a.to_utc(), chrono::Duration::nanoseconds(1));
+        assert_eq!(Met::new(-0.25).seconds(), -1);
+    }
+
+    #[test]
+    fn deserializes_both_record_forms() {
+        let met = Met::from_parts(446724004, 123_456_789);
+        let json = serde_json::to_string(&met).unwrap();
+        assert_eq!(serde_json::from_str::<Met>(&json).unwrap(), met);
+        // 旧记录只有 f64 秒
+        let old: Met = serde_json::from_str(r#"{"time":446724004.5,"_phantom":null}"#).unwrap();
+        assert_eq!(old, Met::from_parts(446724004, 500_000_000));
     }
 }
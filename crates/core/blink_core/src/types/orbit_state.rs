@@ -0,0 +1,86 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{frames, traits::Interpolatable, types::Position};
+
+/// Spacecraft state vector in J2000/ECI (metres, metres per second).
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OrbitState {
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+impl OrbitState {
+    /// Geodetic sub-satellite position at `time`.
+    pub fn position(&self, time: DateTime<Utc>) -> Position {
+        Position::from_ecef(frames::eci_to_ecef(self.position, time))
+    }
+
+    /// Position and velocity in the Earth-fixed frame at `time`.
+    pub fn to_ecef(&self, time: DateTime<Utc>) -> ([f64; 3], [f64; 3]) {
+        frames::eci_to_ecef_state(self.position, self.velocity, time)
+    }
+
+    pub fn from_ecef(position: [f64; 3], velocity: [f64; 3], time: DateTime<Utc>) -> Self {
+        let (position, velocity) = frames::ecef_to_eci_state(position, velocity, time);
+        OrbitState { position, velocity }
+    }
+}
+
+impl Interpolatable for OrbitState {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        OrbitState {
+            position: std::array::from_fn(|i| {
+                self.position[i] + (other.position[i] - self.position[i]) * ratio
+            }),
+            velocity: std::array::from_fn(|i| {
+                self.velocity[i] + (other.velocity[i] - self.velocity[i]) * ratio
+            }),
+        }
+    }
+
+    /// Cubic Hermite spline through both positions and velocities.
+    fn interpolate_over(&self, other: &Self, ratio: f64, span: f64) -> Self {
+        let t = ratio;
+        let (t2, t3) = (t * t, t * t * t);
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+        // 基函数对 t 的导数，除以 span 得到对时间的导数
+        let d00 = 6.0 * t2 - 6.0 * t;
+        let d10 = 3.0 * t2 - 4.0 * t + 1.0;
+        let d01 = -6.0 * t2 + 6.0 * t;
+        let d11 = 3.0 * t2 - 2.0 * t;
+        OrbitState {
+            position: std::array::from_fn(|i| {
+                h00 * self.position[i]
+                    + h10 * span * self.velocity[i]
+                    + h01 * other.position[i]
+                    + h11 * span * other.velocity[i]
+            }),
+            velocity: std::array::from_fn(|i| {
+                (d00 * self.position[i] + d01 * other.position[i]) / span
+                    + d10 * self.velocity[i]
+                    + d11 * other.velocity[i]
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hermite_reproduces_cubic_motion() {
+        // x(t) = t^3 在 [1, 3] 上，端点位置和速度都精确时三次 Hermite 无误差
+        let state = |t: f64| OrbitState {
+            position: [t * t * t, 0.0, 0.0],
+            velocity: [3.0 * t * t, 0.0, 0.0],
+        };
+        let mid = state(1.0).interpolate_over(&state(3.0), 0.25, 2.0);
+        assert!((mid.position[0] - 1.5f64.powi(3)).abs() < 1e-12);
+        assert!((mid.velocity[0] - 3.0 * 1.5 * 1.5).abs() < 1e-12);
+    }
+}
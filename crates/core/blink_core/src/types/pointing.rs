@@ -0,0 +1,45 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Attitude, OrbitState, Position};
+
+/// Where the instrument looks: boresight on the sky and the Earth direction
+/// seen from the spacecraft.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct Pointing {
+    /// Boresight right ascension (degrees, J2000)
+    pub ra: f64,
+    /// Boresight declination (degrees, J2000)
+    pub dec: f64,
+    /// Unit vector towards the Earth centre in the instrument frame
+    pub nadir: [f64; 3],
+}
+
+impl Pointing {
+    pub fn new(
+        attitude: &Attitude,
+        position: &Position,
+        time: DateTime<Utc>,
+        boresight: [f64; 3],
+    ) -> Self {
+        let (ra, dec) = attitude.radec(boresight);
+        let [x, y, z] = position.direction_eci(time);
+        Pointing {
+            ra,
+            dec,
+            nadir: attitude.to_instrument([-x, -y, -z]),
+        }
+    }
+
+    /// Same as [`Pointing::new`] but takes the nadir from a J2000 state vector.
+    pub fn from_orbit(attitude: &Attitude, orbit: &OrbitState, boresight: [f64; 3]) -> Self {
+        let (ra, dec) = attitude.radec(boresight);
+        let [x, y, z] = orbit.position;
+        let norm = (x * x + y * y + z * z).sqrt();
+        Pointing {
+            ra,
+            dec,
+            nadir: attitude.to_instrument([-x / norm, -y / norm, -z / norm]),
+        }
+    }
+}
@@ -1,7 +1,10 @@
+use crate::frames;
 use crate::traits::Interpolatable;
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use uom::si::f64::*;
 
+/// WGS84 geodetic position; longitude and latitude in degrees.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Position {
     pub longitude: f64,
@@ -9,12 +12,66 @@ pub struct Position {
     pub altitude: Length,
 }
 
+impl Position {
+    pub fn to_ecef(&self) -> [f64; 3] {
+        frames::geodetic_to_ecef(
+            self.longitude,
+            self.latitude,
+            self.altitude.get::<uom::si::length::meter>(),
+        )
+    }
+
+    pub fn from_ecef(r: [f64; 3]) -> Self {
+        let (longitude, latitude, altitude) = frames::ecef_to_geodetic(r);
+        Position {
+            longitude,
+            latitude,
+            altitude: Length::new::<uom::si::length::meter>(altitude),
+        }
+    }
+
+    pub fn to_eci(&self, time: DateTime<Utc>) -> [f64; 3] {
+        frames::ecef_to_eci(self.to_ecef(), time)
+    }
+
+    /// Geocentric unit vector of the position in J2000/ECI at `time`.
+    pub fn direction_eci(&self, time: DateTime<Utc>) -> [f64; 3] {
+        let r = self.to_eci(time);
+        let norm = frames::norm(r);
+        [r[0] / norm, r[1] / norm, r[2] / norm]
+    }
+}
+
 impl Interpolatable for Position {
+    /// Follows the great circle between the two points, so crossing the
+    /// antimeridian or passing near a pole stays on the right side of the
+    /// Earth; the geocentric radius is blended linearly.
     fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        let (a, b) = (self.to_ecef(), other.to_ecef());
+        let radius = frames::norm(a) + (frames::norm(b) - frames::norm(a)) * ratio;
+        let direction = frames::slerp_direction(a, b, ratio);
+        Position::from_ecef(direction.map(|x| x * radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(longitude: f64, latitude: f64) -> Position {
         Position {
-            longitude: self.longitude + (other.longitude - self.longitude) * ratio,
-            latitude: self.latitude + (other.latitude - self.latitude) * ratio,
-            altitude: self.altitude + (other.altitude - self.altitude) * ratio,
+            longitude,
+            latitude,
+            altitude: Length::new::<uom::si::length::kilometer>(550.0),
         }
     }
+
+    #[test]
+    fn interpolation_crosses_antimeridian() {
+        let mid = at(179.0, 10.0).interpolate(&at(-179.0, 10.0), 0.5);
+        assert!((mid.longitude.abs() - 180.0).abs() < 1e-6);
+        // 大圆弧中点略偏向极区
+        assert!(mid.latitude > 10.0 && mid.latitude - 10.0 < 1e-2);
+        assert!((mid.altitude.get::<uom::si::length::kilometer>() - 550.0).abs() < 1e-3);
+    }
 }
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Orbital region the spacecraft was in, as judged by the instrument's
+/// region masks.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Region {
+    #[default]
+    Clear,
+    /// South Atlantic Anomaly
+    Saa,
+    /// Inner or outer radiation belt (by field-line apex radius)
+    RadiationBelt,
+    /// Cell flagged by the background rate map
+    HighRate,
+}
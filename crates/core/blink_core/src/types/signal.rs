@@ -4,7 +4,10 @@ use uom::si::f64::*;
 
 use crate::{
     traits::{Event, Instrument},
-    types::{Attitude, MissionElapsedTime, Position},
+    types::{
+        Attitude, Characteristics, Classification, DeadTimeCorrected, Geomagnetic, Geometry,
+        MissionElapsedTime, Pointing, Position, Region,
+    },
 };
 
 #[derive(Serialize, Deserialize)]
@@ -19,8 +22,16 @@ pub struct Signal<E: Event> {
     pub mean: f64,
     pub sf: f64,
     pub false_positive_per_year: f64,
+    /// Trigger groups contributing to the best bin
+    pub groups: Vec<u8>,
+    pub dead_time: DeadTimeCorrected,
+    pub characteristics: Characteristics,
+    pub classification: Classification,
     pub attitude: Attitude,
     pub position: Position,
+    pub pointing: Pointing,
+    pub region: Region,
+    pub geometry: Geometry,
 }
 
 impl<E: Event> Signal<E> {
@@ -36,8 +47,24 @@ impl<E: Event> Signal<E> {
             mean: self.mean,
             sf: self.sf,
             false_positive_per_year: self.false_positive_per_year,
+            groups: self
+                .groups
+                .iter()
+                .map(|&group| {
+                    <E::Instrument as Instrument>::group_names()
+                        .get(group as usize)
+                        .map_or_else(|| group.to_string(), |name| name.to_string())
+                })
+                .collect(),
+            dead_time: self.dead_time.clone(),
+            characteristics: self.characteristics.clone(),
+            classification: self.classification.clone(),
             attitude: self.attitude.clone(),
             position: self.position.clone(),
+            pointing: self.pointing.clone(),
+            region: self.region,
+            geometry: self.geometry.clone(),
+            geomagnetic: Geomagnetic::default(),
             instrument: <E::Instrument as Instrument>::name().to_string(),
         }
     }
@@ -55,8 +82,26 @@ pub struct UnifiedSignal {
     pub mean: f64,
     pub sf: f64,
     pub false_positive_per_year: f64,
+    /// Names of the trigger groups contributing to the best bin.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub dead_time: DeadTimeCorrected,
+    #[serde(default)]
+    pub characteristics: Characteristics,
+    #[serde(default)]
+    pub classification: Classification,
     pub attitude: Attitude,
     pub position: Position,
+    #[serde(default)]
+    pub pointing: Pointing,
+    #[serde(default)]
+    pub region: Region,
+    #[serde(default)]
+    pub geometry: Geometry,
+    /// Filled in by the search workflow from the position at the peak.
+    #[serde(default)]
+    pub geomagnetic: Geomagnetic,
     pub instrument: String,
 }
 
@@ -1,49 +1,203 @@
 use serde::Serialize;
 
 use crate::{
+    error::Error,
     traits::{Interpolatable, Temporal},
     types::TemporalState,
 };
 
+/// What to do with a query outside the sampled time range.
+#[derive(Clone, Copy, Debug)]
+pub enum Extrapolation<Duration> {
+    /// Refuse any query outside the sampled range.
+    Refuse,
+    /// Return the nearest edge sample if the query is at most this far out.
+    Hold(Duration),
+    /// Extend the edge segment if the query is at most this far out.
+    Extend(Duration),
+}
+
+/// Time-ordered samples with binary-search lookup.
+///
+/// `points` must be sorted by timestamp. Segments longer than `max_gap` are
+/// treated as data dropouts and never bridged.
 #[derive(Serialize, Debug)]
 pub struct Trajectory<Time: Temporal, State: Interpolatable + Clone> {
     pub points: Vec<TemporalState<Time, State>>,
+    #[serde(skip)]
+    pub max_gap: Option<Time::Duration>,
+    #[serde(skip)]
+    pub extrapolation: Extrapolation<Time::Duration>,
 }
 
 impl<Time: Temporal, State: Interpolatable + Clone> Trajectory<Time, State> {
-    pub fn interpolate(&self, time: Time) -> Option<TemporalState<Time, State>> {
-        let mut i = 0;
-        while i < self.points.len() - 1 && self.points[i + 1].timestamp < time {
-            i += 1;
-        }
-        if i == self.points.len() - 1 {
-            return None;
+    pub fn new(points: Vec<TemporalState<Time, State>>) -> Self {
+        Trajectory {
+            points,
+            max_gap: None,
+            extrapolation: Extrapolation::Refuse,
         }
+    }
 
-        let t0 = self.points[i].timestamp;
-        let t1 = self.points[i + 1].timestamp;
+    pub fn with_max_gap(mut self, max_gap: Time::Duration) -> Self {
+        self.max_gap = Some(max_gap);
+        self
+    }
 
-        let lerp_factor = time.lerp_factor(t0, t1);
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation<Time::Duration>) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
 
-        Some(TemporalState {
-            timestamp: time,
-            state: self.points[i]
-                .state
-                .interpolate(&self.points[i + 1].state, lerp_factor),
-        })
+    pub fn interpolate(&self, time: Time) -> Result<TemporalState<Time, State>, Error> {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(Error::Interpolation("empty trajectory".to_string())),
+        };
+
+        if time < first.timestamp {
+            return self.extrapolate(0, first.timestamp - time, time);
+        }
+        if time > last.timestamp {
+            return self.extrapolate(self.points.len() - 1, time - last.timestamp, time);
+        }
+
+        // 第一个时间戳 >= time 的点，time 落在 (idx - 1, idx] 区间内
+        let idx = self.points.partition_point(|point| point.timestamp < time);
+        if idx == 0 {
+            return Ok(TemporalState {
+                timestamp: time,
+                state: first.state.clone(),
+            });
+        }
+        self.segment(idx - 1, time)
     }
 
     pub fn window(&self, time: Time, half_width: Time::Duration) -> Self {
         let start_time = time - half_width;
         let end_time = time + half_width;
 
+        let start = self
+            .points
+            .partition_point(|point| point.timestamp < start_time);
+        let end = self
+            .points
+            .partition_point(|point| point.timestamp <= end_time);
+
         Trajectory {
-            points: self
-                .points
+            points: self.points[start..end.max(start)].to_vec(),
+            max_gap: self.max_gap,
+            extrapolation: self.extrapolation,
+        }
+    }
+
+    fn segment(&self, i: usize, time: Time) -> Result<TemporalState<Time, State>, Error> {
+        let (p0, p1) = (&self.points[i], &self.points[i + 1]);
+        let gap = p1.timestamp - p0.timestamp;
+        if let Some(max_gap) = self.max_gap
+            && gap > max_gap
+        {
+            return Err(Error::Interpolation(format!(
+                "gap of {} s exceeds the maximum",
+                p1.timestamp.seconds_since(p0.timestamp)
+            )));
+        }
+
+        let factor = time.lerp_factor(p0.timestamp, p1.timestamp);
+        let span = p1.timestamp.seconds_since(p0.timestamp);
+        Ok(TemporalState {
+            timestamp: time,
+            state: p0.state.interpolate_over(&p1.state, factor, span),
+        })
+    }
+
+    fn extrapolate(
+        &self,
+        edge: usize,
+        distance: Time::Duration,
+        time: Time,
+    ) -> Result<TemporalState<Time, State>, Error> {
+        let out_of_range = || {
+            Error::Interpolation(format!(
+                "{} s outside the sampled range",
+                time.seconds_since(self.points[edge].timestamp).abs()
+            ))
+        };
+        match self.extrapolation {
+            Extrapolation::Refuse => Err(out_of_range()),
+            Extrapolation::Hold(limit) if distance <= limit => Ok(TemporalState {
+                timestamp: time,
+                state: self.points[edge].state.clone(),
+            }),
+            Extrapolation::Extend(limit) if distance <= limit && self.points.len() >= 2 => {
+                self.segment(edge.min(self.points.len() - 2), time)
+            }
+            _ => Err(out_of_range()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    #[derive(Clone, Debug)]
+    struct Scalar(f64);
+
+    impl Interpolatable for Scalar {
+        fn interpolate(&self, other: &Self, factor: f64) -> Self {
+            Scalar(self.0 + (other.0 - self.0) * factor)
+        }
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn trajectory(samples: &[(i64, f64)]) -> Trajectory<DateTime<Utc>, Scalar> {
+        Trajectory::new(
+            samples
                 .iter()
-                .filter(|point| point.timestamp >= start_time && point.timestamp <= end_time)
-                .cloned()
+                .map(|&(t, v)| TemporalState {
+                    timestamp: at(t),
+                    state: Scalar(v),
+                })
                 .collect(),
-        }
+        )
+    }
+
+    #[test]
+    fn interpolates_inside_range() {
+        let trajectory = trajectory(&[(0, 0.0), (10, 10.0), (20, 0.0)]);
+        assert_eq!(trajectory.interpolate(at(5)).unwrap().state.0, 5.0);
+        assert_eq!(trajectory.interpolate(at(15)).unwrap().state.0, 5.0);
+        assert_eq!(trajectory.interpolate(at(0)).unwrap().state.0, 0.0);
+        assert_eq!(trajectory.interpolate(at(20)).unwrap().state.0, 0.0);
+    }
+
+    #[test]
+    fn refuses_gaps() {
+        let trajectory =
+            trajectory(&[(0, 0.0), (1, 1.0), (100, 2.0)]).with_max_gap(Duration::seconds(10));
+        assert!(trajectory.interpolate(at(0)).is_ok());
+        assert!(trajectory.interpolate(at(50)).is_err());
+    }
+
+    #[test]
+    fn extrapolation_policy() {
+        let samples = [(0, 0.0), (10, 10.0)];
+        assert!(trajectory(&samples).interpolate(at(11)).is_err());
+
+        let hold =
+            trajectory(&samples).with_extrapolation(Extrapolation::Hold(Duration::seconds(2)));
+        assert_eq!(hold.interpolate(at(11)).unwrap().state.0, 10.0);
+        assert_eq!(hold.interpolate(at(-1)).unwrap().state.0, 0.0);
+        assert!(hold.interpolate(at(13)).is_err());
+
+        let extend =
+            trajectory(&samples).with_extrapolation(Extrapolation::Extend(Duration::seconds(2)));
+        assert_eq!(extend.interpolate(at(12)).unwrap().state.0, 12.0);
+        assert_eq!(extend.interpolate(at(-2)).unwrap().state.0, -2.0);
     }
 }
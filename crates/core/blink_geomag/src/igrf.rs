@@ -0,0 +1,291 @@
+use blink_core::error::Error;
+use chrono::prelude::*;
+use std::{
+    env,
+    sync::{LazyLock, Once},
+};
+
+/// IGRF reference radius (m).
+pub const REFERENCE_RADIUS: f64 = 6_371_200.0;
+
+const MAX_DEGREE: usize = 13;
+const BUNDLED: &str = include_str!("../data/IGRF13.COF");
+
+static IGRF: LazyLock<Igrf> = LazyLock::new(|| {
+    // 可用 BLINK_IGRF_COF 指向更新的 GEOMAG .COF 文件（如 IGRF-14）
+    env::var("BLINK_IGRF_COF")
+        .ok()
+        .and_then(|path| {
+            std::fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|text| Igrf::from_cof(&text))
+                .inspect_err(|e| eprintln!("ignoring BLINK_IGRF_COF {path}: {e}"))
+                .ok()
+        })
+        .unwrap_or_else(|| Igrf::from_cof(BUNDLED).expect("bundled IGRF coefficients are valid"))
+});
+
+/// Gauss coefficients `g[n][m]`, `h[n][m]` in nT, Schmidt semi-normalised.
+#[derive(Clone)]
+pub struct Coefficients {
+    pub g: [[f64; MAX_DEGREE + 1]; MAX_DEGREE + 1],
+    pub h: [[f64; MAX_DEGREE + 1]; MAX_DEGREE + 1],
+}
+
+impl Coefficients {
+    fn zero() -> Self {
+        Coefficients {
+            g: [[0.0; MAX_DEGREE + 1]; MAX_DEGREE + 1],
+            h: [[0.0; MAX_DEGREE + 1]; MAX_DEGREE + 1],
+        }
+    }
+
+    /// Dipole axis in ECEF, pointing to the geomagnetic north pole.
+    pub fn dipole_axis(&self) -> [f64; 3] {
+        let m = [-self.g[1][1], -self.h[1][1], -self.g[1][0]];
+        let norm = (m[0] * m[0] + m[1] * m[1] + m[2] * m[2]).sqrt();
+        [m[0] / norm, m[1] / norm, m[2] / norm]
+    }
+
+    /// Field at a geocentric ECEF point (m), as ECEF components in nT.
+    pub fn field_ecef(&self, r: [f64; 3]) -> [f64; 3] {
+        let [x, y, z] = r;
+        let radius = (x * x + y * y + z * z).sqrt();
+        let theta = (z / radius).clamp(-1.0, 1.0).acos();
+        let phi = y.atan2(x);
+        let [b_r, b_theta, b_phi] = self.field_spherical(radius, theta, phi);
+
+        let (sin_t, cos_t) = theta.sin_cos();
+        let (sin_p, cos_p) = phi.sin_cos();
+        [
+            b_r * sin_t * cos_p + b_theta * cos_t * cos_p - b_phi * sin_p,
+            b_r * sin_t * sin_p + b_theta * cos_t * sin_p + b_phi * cos_p,
+            b_r * cos_t - b_theta * sin_t,
+        ]
+    }
+
+    /// Field `(B_r, B_θ, B_φ)` in nT at geocentric radius (m), colatitude
+    /// and longitude (radians).
+    pub fn field_spherical(&self, radius: f64, theta: f64, phi: f64) -> [f64; 3] {
+        let (sin_t, cos_t) = theta.sin_cos();
+        // 极点处 B_φ 的 1/sinθ 奇异，取一个极小值避开
+        let sin_t = sin_t.max(1e-10);
+        let (p, dp) = legendre(sin_t, cos_t);
+
+        let ratio = REFERENCE_RADIUS / radius;
+        let mut scale = ratio * ratio;
+        let (mut b_r, mut b_theta, mut b_phi) = (0.0, 0.0, 0.0);
+        for n in 1..=MAX_DEGREE {
+            scale *= ratio;
+            for m in 0..=n {
+                let (sin_mp, cos_mp) = (m as f64 * phi).sin_cos();
+                let (g, h) = (self.g[n][m], self.h[n][m]);
+                let a = g * cos_mp + h * sin_mp;
+                b_r += (n + 1) as f64 * scale * a * p[n][m];
+                b_theta -= scale * a * dp[n][m];
+                b_phi -= scale * m as f64 * (h * cos_mp - g * sin_mp) * p[n][m] / sin_t;
+            }
+        }
+        [b_r, b_theta, b_phi]
+    }
+}
+
+type Table = [[f64; MAX_DEGREE + 1]; MAX_DEGREE + 1];
+
+/// Schmidt semi-normalised associated Legendre functions and their
+/// θ-derivatives.
+fn legendre(sin_t: f64, cos_t: f64) -> (Table, Table) {
+    // 先按 Gauss 归一化递推，再乘 Schmidt 因子
+    let mut p = [[0.0; MAX_DEGREE + 1]; MAX_DEGREE + 1];
+    let mut dp = [[0.0; MAX_DEGREE + 1]; MAX_DEGREE + 1];
+    p[0][0] = 1.0;
+    for n in 1..=MAX_DEGREE {
+        for m in 0..=n {
+            if n == m {
+                p[n][m] = sin_t * p[n - 1][m - 1];
+                dp[n][m] = sin_t * dp[n - 1][m - 1] + cos_t * p[n - 1][m - 1];
+            } else {
+                let k = if n == 1 {
+                    0.0
+                } else {
+                    (((n - 1) * (n - 1)) as f64 - (m * m) as f64)
+                        / (((2 * n - 1) * (2 * n - 3)) as f64)
+                };
+                let (p2, dp2) = if n >= 2 {
+                    (p[n - 2][m], dp[n - 2][m])
+                } else {
+                    (0.0, 0.0)
+                };
+                p[n][m] = cos_t * p[n - 1][m] - k * p2;
+                dp[n][m] = cos_t * dp[n - 1][m] - sin_t * p[n - 1][m] - k * dp2;
+            }
+        }
+    }
+
+    let mut schmidt = [[0.0; MAX_DEGREE + 1]; MAX_DEGREE + 1];
+    schmidt[0][0] = 1.0;
+    for n in 1..=MAX_DEGREE {
+        schmidt[n][0] = schmidt[n - 1][0] * (2 * n - 1) as f64 / n as f64;
+        for m in 1..=n {
+            let delta = if m == 1 { 2.0 } else { 1.0 };
+            schmidt[n][m] =
+                schmidt[n][m - 1] * ((n - m + 1) as f64 * delta / (n + m) as f64).sqrt();
+        }
+    }
+    for n in 0..=MAX_DEGREE {
+        for m in 0..=n {
+            p[n][m] *= schmidt[n][m];
+            dp[n][m] *= schmidt[n][m];
+        }
+    }
+    (p, dp)
+}
+
+/// Warns once per process about epochs past the coefficients.
+static PAST_VALIDITY: Once = Once::new();
+
+struct Model {
+    epoch: f64,
+    /// End of the validity interval in the header, 5 years after the epoch
+    /// if absent
+    until: f64,
+    main: Coefficients,
+    secular: Coefficients,
+}
+
+/// IGRF model series read from a GEOMAG `.COF` file.
+pub struct Igrf {
+    models: Vec<Model>,
+}
+
+impl Igrf {
+    /// Coefficients bundled with the crate, or the file named by
+    /// `BLINK_IGRF_COF` if set.
+    pub fn bundled() -> &'static Igrf {
+        &IGRF
+    }
+
+    pub fn from_cof(text: &str) -> Result<Self, Error> {
+        let invalid = |line: &str| Error::InvalidData(format!("bad IGRF line: {line}"));
+        let mut models: Vec<Model> = Vec::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() >= 2 && fields[0].parse::<usize>().is_err() {
+                // 模型头：名称 历元 最大阶数 ...
+                let epoch = fields[1].parse::<f64>().map_err(|_| invalid(line))?;
+                // 头中第 6、7 列为有效区间
+                let until = fields
+                    .get(6)
+                    .and_then(|field| field.parse::<f64>().ok())
+                    .unwrap_or(epoch + 5.0);
+                models.push(Model {
+                    epoch,
+                    until,
+                    main: Coefficients::zero(),
+                    secular: Coefficients::zero(),
+                });
+                continue;
+            }
+
+            let model = models.last_mut().ok_or_else(|| invalid(line))?;
+            let number = |i: usize| -> Result<f64, Error> {
+                fields
+                    .get(i)
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .ok_or_else(|| invalid(line))
+            };
+            let n = number(0)? as usize;
+            let m = number(1)? as usize;
+            if n == 0 || n > MAX_DEGREE || m > n {
+                return Err(invalid(line));
+            }
+            model.main.g[n][m] = number(2)?;
+            model.main.h[n][m] = number(3)?;
+            model.secular.g[n][m] = number(4)?;
+            model.secular.h[n][m] = number(5)?;
+        }
+
+        if models.is_empty() {
+            return Err(Error::InvalidData("no IGRF models found".to_string()));
+        }
+        models.sort_by(|a, b| a.epoch.total_cmp(&b.epoch));
+        Ok(Igrf { models })
+    }
+
+    /// Decimal year up to which the secular variation of the last model is
+    /// valid.
+    pub fn valid_until(&self) -> f64 {
+        self.models
+            .last()
+            .map_or(f64::NEG_INFINITY, |model| model.until)
+    }
+
+    /// Coefficients at a decimal year: linear between epochs, secular
+    /// variation after the last one.
+    ///
+    /// Past [`Igrf::valid_until`] the secular variation is extrapolated
+    /// with a warning on the first such call; point `BLINK_IGRF_COF` at a
+    /// newer generation to avoid it.
+    pub fn coefficients(&self, year: f64) -> Coefficients {
+        let idx = self.models.partition_point(|model| model.epoch <= year);
+        let mut out = Coefficients::zero();
+        if idx == self.models.len() {
+            let last = &self.models[idx - 1];
+            if year > last.until {
+                PAST_VALIDITY.call_once(|| {
+                    eprintln!(
+                        "warning: IGRF coefficients end at {:.1}; extrapolating the secular variation to {year:.2}, set BLINK_IGRF_COF to a newer model",
+                        last.until
+                    )
+                });
+            }
+            let dt = year - last.epoch;
+            for n in 1..=MAX_DEGREE {
+                for m in 0..=n {
+                    out.g[n][m] = last.main.g[n][m] + last.secular.g[n][m] * dt;
+                    out.h[n][m] = last.main.h[n][m] + last.secular.h[n][m] * dt;
+                }
+            }
+            return out;
+        }
+        if idx == 0 {
+            return self.models[0].main.clone();
+        }
+
+        let (a, b) = (&self.models[idx - 1], &self.models[idx]);
+        let ratio = (year - a.epoch) / (b.epoch - a.epoch);
+        for n in 1..=MAX_DEGREE {
+            for m in 0..=n {
+                out.g[n][m] = a.main.g[n][m] + (b.main.g[n][m] - a.main.g[n][m]) * ratio;
+                out.h[n][m] = a.main.h[n][m] + (b.main.h[n][m] - a.main.h[n][m]) * ratio;
+            }
+        }
+        out
+    }
+
+    pub fn at(&self, time: DateTime<Utc>) -> Coefficients {
+        self.coefficients(decimal_year(time))
+    }
+}
+
+pub fn decimal_year(time: DateTime<Utc>) -> f64 {
+    let year = time.year();
+    let start = Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0).unwrap();
+    year as f64 + (time - start).num_seconds() as f64 / (end - start).num_seconds() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_validity_ends_with_the_last_model() {
+        let igrf = Igrf::from_cof(BUNDLED).unwrap();
+        assert_eq!(igrf.valid_until(), 2025.0);
+
+        let header_only = "   IGRF2030  2030.00 13  8  0 2030.00 2035.00   -1.0  600.0  IGRF2030   0\n\
+                           1  0 -29000.00      0.00      1.00      0.00  IGRF2030   1\n";
+        assert_eq!(Igrf::from_cof(header_only).unwrap().valid_until(), 2035.0);
+    }
+}
@@ -0,0 +1,115 @@
+pub mod igrf;
+pub mod trace;
+
+use blink_core::{
+    frames,
+    types::{Geomagnetic, Position, TemporalState},
+};
+use chrono::prelude::*;
+
+use crate::igrf::{Coefficients, Igrf, REFERENCE_RADIUS};
+
+/// Størmer vertical cutoff coefficient (GV), Rc = 14.9 / L², evaluated with
+/// the apex radius standing in for L.
+pub const STORMER_COEFFICIENT: f64 = 14.9;
+
+/// Field lines reaching beyond this many Earth radii are treated as open.
+const MAX_APEX_RADIUS: f64 = 30.0;
+
+pub fn geomagnetic(state: &TemporalState<DateTime<Utc>, Position>) -> Geomagnetic {
+    let coefficients = Igrf::bundled().at(state.timestamp);
+    let r = state.state.to_ecef();
+    let field = local_field(&coefficients, &state.state);
+
+    let radius = frames::norm(r);
+    let axis = coefficients.dipole_axis();
+    let dipole_latitude = (frames::dot(r, axis) / radius).clamp(-1.0, 1.0).asin();
+
+    let horizontal = field[0].hypot(field[1]);
+    let inclination = field[2].atan2(horizontal);
+    let dip_latitude = (inclination.tan() / 2.0).atan();
+
+    let apex_radius = trace_apex_radius(&coefficients, r);
+
+    Geomagnetic {
+        field,
+        dipole_latitude: dipole_latitude.to_degrees(),
+        dip_latitude: dip_latitude.to_degrees(),
+        apex_radius,
+        cutoff_rigidity: apex_radius.map_or(0.0, |l| STORMER_COEFFICIENT / (l * l)),
+    }
+}
+
+/// IGRF field (nT) in local geodetic north, east, down, without the field
+/// line trace.
+pub fn field(state: &TemporalState<DateTime<Utc>, Position>) -> [f64; 3] {
+    local_field(&Igrf::bundled().at(state.timestamp), &state.state)
+}
+
+/// Geocentric distance (Earth radii) of the apex of the IGRF field line
+/// through the position. It equals the dipole L value but is not McIlwain's
+/// L, which follows from |B| and the integral invariant; `None` on open field
+/// lines.
+pub fn apex_radius(state: &TemporalState<DateTime<Utc>, Position>) -> Option<f64> {
+    trace_apex_radius(&Igrf::bundled().at(state.timestamp), state.state.to_ecef())
+}
+
+fn local_field(coefficients: &Coefficients, position: &Position) -> [f64; 3] {
+    let b = coefficients.field_ecef(position.to_ecef());
+    let (sin_lat, cos_lat) = position.latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = position.longitude.to_radians().sin_cos();
+    let north = [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat];
+    let east = [-sin_lon, cos_lon, 0.0];
+    let down = [-cos_lat * cos_lon, -cos_lat * sin_lon, -sin_lat];
+    [
+        frames::dot(b, north),
+        frames::dot(b, east),
+        frames::dot(b, down),
+    ]
+}
+
+fn trace_apex_radius(coefficients: &Coefficients, r: [f64; 3]) -> Option<f64> {
+    trace::apex(coefficients, r, MAX_APEX_RADIUS * REFERENCE_RADIUS)
+        .map(|apex| apex / REFERENCE_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::f64::*;
+
+    #[test]
+    fn beijing_surface_field() {
+        let state = TemporalState {
+            timestamp: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+            state: Position {
+                longitude: 116.4,
+                latitude: 39.9,
+                altitude: Length::new::<uom::si::length::meter>(0.0),
+            },
+        };
+        let geomagnetic = geomagnetic(&state);
+        let total = frames::norm(geomagnetic.field);
+        let inclination = geomagnetic.field[2]
+            .atan2(geomagnetic.field[0].hypot(geomagnetic.field[1]))
+            .to_degrees();
+        assert!((53_000.0..56_000.0).contains(&total), "{total}");
+        assert!((57.0..62.0).contains(&inclination), "{inclination}");
+        assert!(geomagnetic.apex_radius.unwrap() > 1.0);
+    }
+
+    #[test]
+    fn dipole_field_line_apex() {
+        // 纯轴向偶极：L = r / cos²λ
+        let mut coefficients = Igrf::bundled().coefficients(2020.0);
+        coefficients.g = [[0.0; 14]; 14];
+        coefficients.h = [[0.0; 14]; 14];
+        coefficients.g[1][0] = -30_000.0;
+        let latitude = 40f64.to_radians();
+        let r = 1.1 * REFERENCE_RADIUS;
+        let start = [r * latitude.cos(), 0.0, r * latitude.sin()];
+        let apex = trace::apex(&coefficients, start, 100.0 * REFERENCE_RADIUS).unwrap();
+        let expected = r / latitude.cos().powi(2);
+        assert!((apex / expected - 1.0).abs() < 1e-3);
+    }
+}
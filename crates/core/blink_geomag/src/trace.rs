@@ -0,0 +1,52 @@
+use crate::igrf::Coefficients;
+
+/// Follows the field line through `start` (ECEF, m) away from the Earth and
+/// returns the largest geocentric distance reached (m), or `None` if the
+/// line leaves `limit` first (open field line).
+pub fn apex(coefficients: &Coefficients, start: [f64; 3], limit: f64) -> Option<f64> {
+    let r0 = norm(start);
+    for sign in [1.0, -1.0] {
+        let first = step(coefficients, start, sign);
+        if norm(first) <= r0 {
+            continue;
+        }
+
+        let mut r = first;
+        for _ in 0..10_000 {
+            let next = step(coefficients, r, sign);
+            if norm(next) <= norm(r) {
+                return Some(norm(r));
+            }
+            if norm(next) > limit {
+                return None;
+            }
+            r = next;
+        }
+        return None;
+    }
+    // 两个方向都向内：起点就在磁赤道上
+    Some(r0)
+}
+
+/// One RK4 step along the unit field direction, step length 1% of the
+/// geocentric distance.
+fn step(coefficients: &Coefficients, r: [f64; 3], sign: f64) -> [f64; 3] {
+    let h = 0.01 * norm(r);
+    let direction = |p: [f64; 3]| {
+        let b = coefficients.field_ecef(p);
+        let n = norm(b);
+        [sign * b[0] / n, sign * b[1] / n, sign * b[2] / n]
+    };
+    let add =
+        |p: [f64; 3], d: [f64; 3], s: f64| [p[0] + s * d[0], p[1] + s * d[1], p[2] + s * d[2]];
+
+    let k1 = direction(r);
+    let k2 = direction(add(r, k1, h / 2.0));
+    let k3 = direction(add(r, k2, h / 2.0));
+    let k4 = direction(add(r, k3, h));
+    std::array::from_fn(|i| r[i] + h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
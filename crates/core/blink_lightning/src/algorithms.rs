@@ -1,5 +1,6 @@
 mod associated;
 mod coincidence;
 mod geo;
+mod incidence;
 
 pub use coincidence::coincidence_prob;
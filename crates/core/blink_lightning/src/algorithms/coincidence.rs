@@ -3,6 +3,7 @@ use crate::algorithms::geo::time_of_arrival;
 use crate::constants::LIGHTNING_ALTITUDE;
 use crate::database::get_lightnings;
 use crate::types::Lightning;
+use blink_core::error::Error;
 use blink_core::types::Position;
 use blink_core::types::TemporalState;
 use chrono::Duration;
@@ -15,10 +16,10 @@ pub fn coincidence_prob(
     time_tolerance: Duration,
     distance_tolerance: Length,
     time_window: Duration,
-) -> f64 {
+) -> Result<f64, Error> {
     let time_start = position.timestamp - time_tolerance - Duration::seconds(1) - time_window / 2;
     let time_end = position.timestamp + time_tolerance + Duration::seconds(1) + time_window / 2;
-    let mut rows = get_lightnings(time_start, time_end);
+    let mut rows = get_lightnings(time_start, time_end)?;
     rows.retain(|lightning| {
         let dist = distance(
             position.state.latitude,
@@ -57,7 +58,7 @@ pub fn coincidence_prob(
         .sum::<Duration>();
     let total_window = total_window.num_nanoseconds().unwrap_or(0) as f64;
     let total_time = total_time.num_nanoseconds().unwrap_or(0) as f64;
-    total_window / total_time
+    Ok(total_window / total_time)
 }
 
 fn coincidence_window(
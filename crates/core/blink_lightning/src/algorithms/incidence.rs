@@ -0,0 +1,27 @@
+use crate::constants::LIGHTNING_ALTITUDE;
+use crate::types::Lightning;
+use blink_core::types::{Attitude, Incidence, Position, TemporalState};
+use chrono::prelude::*;
+
+impl Lightning {
+    /// Incidence of photons from this stroke (at the nominal source altitude)
+    /// on the instrument at `position`.
+    pub fn incidence(
+        &self,
+        attitude: &Attitude,
+        position: &TemporalState<DateTime<Utc>, Position>,
+        axes: &[[f64; 3]],
+    ) -> Incidence {
+        let source = Position {
+            longitude: self.lon,
+            latitude: self.lat,
+            altitude: *LIGHTNING_ALTITUDE,
+        };
+        Incidence::new(
+            attitude,
+            position.state.to_eci(position.timestamp),
+            source.to_eci(position.timestamp),
+            axes,
+        )
+    }
+}
@@ -4,6 +4,6 @@ use uom::si::f64::*;
 pub static SPEED_OF_LIGHT: LazyLock<Velocity> =
     LazyLock::new(|| Velocity::new::<uom::si::velocity::meter_per_second>(299_792_458.0));
 pub static R_EARTH: LazyLock<Length> =
-    LazyLock::new(|| Length::new::<uom::si::length::meter>(6_371_000.0));
+    LazyLock::new(|| Length::new::<uom::si::length::meter>(blink_core::frames::EARTH_MEAN_RADIUS));
 pub static LIGHTNING_ALTITUDE: LazyLock<Length> =
     LazyLock::new(|| Length::new::<uom::si::length::meter>(15_000.0));
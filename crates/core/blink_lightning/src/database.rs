@@ -1,4 +1,5 @@
 use crate::types::Lightning;
+use blink_core::error::Error;
 use chrono::prelude::*;
 use rusqlite::{Connection, params};
 use std::env;
@@ -6,33 +7,57 @@ use std::env;
 thread_local! {
     // 每个线程持有独立的只读连接：SQLite 允许多读者并发，用线程本地连接（而非
     // 全局 Mutex<Connection>）让 filter 的百万级查询能真正并行，而不是串行等锁。
-    static LIGHTNING_CONNECTION: Connection = open_connection();
+    // 打开失败时保留错误信息，每次查询都如实返回，而不是让线程 panic。
+    static LIGHTNING_CONNECTION: Result<Connection, String> =
+        open_connection().map_err(|e| e.to_string());
 }
 
-fn open_connection() -> Connection {
-    let path = env::var("WWLLN_DB_PATH")
-        .unwrap_or_else(|_| String::from("/Volumes/Graphite/WWLLN/WWLLN.db"));
-    let conn =
-        Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY).unwrap();
+fn database_path() -> String {
+    env::var("WWLLN_DB_PATH").unwrap_or_else(|_| String::from("/Volumes/Graphite/WWLLN/WWLLN.db"))
+}
+
+fn open_connection() -> Result<Connection, Error> {
+    let path = database_path();
+    let conn = Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| database_error(e, &path))?;
     // Set a longer busy timeout (e.g., 30 seconds = 30000 ms)
     conn.busy_timeout(std::time::Duration::from_secs(30))
-        .unwrap();
+        .map_err(|e| database_error(e, &path))?;
     // 用 mmap 直接映射数据库读取，绕过 SQLite 全局页缓存（pcache1）那把互斥锁——
     // filter 用几十个线程各开一个连接跑百万级查询时，该锁的争用是主要瓶颈
     // （perf 显示 ~40% 时间在 pthread_mutex_lock/pcache1）。映射整库（>422GB），
     // 实际驻留由 OS 页缓存按热度管理。
     conn.pragma_update(None, "mmap_size", 549_755_813_888i64)
-        .unwrap();
-    conn
+        .map_err(|e| database_error(e, &path))?;
+    Ok(conn)
+}
+
+fn database_error(error: rusqlite::Error, path: &str) -> Error {
+    match error {
+        // 行内容不合预期说明库本身有问题，而不是连接或锁的瞬时故障
+        rusqlite::Error::FromSqlConversionFailure(..)
+        | rusqlite::Error::InvalidColumnType(..)
+        | rusqlite::Error::IntegralValueOutOfRange(..) => {
+            Error::InvalidData(format!("{path}: {error}"))
+        }
+        error => Error::Database(format!("{path}: {error}")),
+    }
 }
 
-pub fn get_lightnings(time_start: DateTime<Utc>, time_end: DateTime<Utc>) -> Vec<Lightning> {
+pub fn get_lightnings(
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<Vec<Lightning>, Error> {
     let time_start_str = time_start.format("%Y-%m-%d %H:%M:%S%.6f").to_string();
     let time_end_str = time_end.format("%Y-%m-%d %H:%M:%S%.6f").to_string();
     LIGHTNING_CONNECTION.with(|connection| {
-    let mut statement = connection
-        .prepare(
-            "
+        let path = database_path();
+        let connection = connection
+            .as_ref()
+            .map_err(|e| Error::Database(e.clone()))?;
+        let mut statement = connection
+            .prepare(
+                "
                 SELECT
                     time,
                     lat,
@@ -48,28 +73,31 @@ pub fn get_lightnings(time_start: DateTime<Utc>, time_end: DateTime<Utc>) -> Vec
                     time BETWEEN ?1 AND ?2
                 ORDER BY time ASC
                 ",
-        )
-        .unwrap();
-    statement
-        .query_map(params![time_start_str, time_end_str], |row| {
-            Ok(Lightning {
-                time: NaiveDateTime::parse_from_str(
-                    &row.get::<_, String>(0).unwrap(),
-                    "%Y-%m-%d %H:%M:%S%.6f",
-                )
-                .unwrap()
-                .and_utc(),
-                lat: row.get::<_, f64>(1).unwrap(),
-                lon: row.get::<_, f64>(2).unwrap(),
-                resid: row.get::<_, f64>(3).unwrap(),
-                nstn: row.get::<_, i64>(4).unwrap() as u32,
-                energy: row.get::<_, Option<f64>>(5).unwrap(),
-                energy_uncertainty: row.get::<_, Option<f64>>(6).unwrap(),
-                estn: row.get::<_, Option<i64>>(7).unwrap().map(|x| x as u32),
+            )
+            .map_err(|e| database_error(e, &path))?;
+        statement
+            .query_map(params![time_start_str, time_end_str], |row| {
+                let time = row.get::<_, String>(0)?;
+                Ok(Lightning {
+                    time: NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S%.6f")
+                        .map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(
+                                0,
+                                rusqlite::types::Type::Text,
+                                Box::new(e),
+                            )
+                        })?
+                        .and_utc(),
+                    lat: row.get::<_, f64>(1)?,
+                    lon: row.get::<_, f64>(2)?,
+                    resid: row.get::<_, f64>(3)?,
+                    nstn: row.get::<_, i64>(4)? as u32,
+                    energy: row.get::<_, Option<f64>>(5)?,
+                    energy_uncertainty: row.get::<_, Option<f64>>(6)?,
+                    estn: row.get::<_, Option<i64>>(7)?.map(|x| x as u32),
+                })
             })
-        })
-        .unwrap()
-        .map(|x| x.unwrap())
-        .collect::<Vec<_>>()
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| database_error(e, &path))
     })
 }
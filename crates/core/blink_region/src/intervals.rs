@@ -0,0 +1,56 @@
+use blink_core::{
+    traits::Temporal,
+    types::{Position, TemporalState, Trajectory},
+};
+use chrono::prelude::*;
+
+use crate::mask::MaskSet;
+
+/// Merged intervals during which an excluding mask matches along the
+/// trajectory.
+///
+/// Only every `stride`-th sample is evaluated; each masked run is widened by
+/// `padding` on both sides to cover the skipped samples and ingress/egress.
+pub fn masked_intervals<T: Temporal>(
+    trajectory: &Trajectory<T, Position>,
+    to_utc: impl Fn(T) -> DateTime<Utc>,
+    masks: &MaskSet,
+    stride: usize,
+    padding: T::Duration,
+) -> Vec<(T, T)> {
+    let mut runs: Vec<(T, T)> = Vec::new();
+    let mut open: Option<(T, T)> = None;
+    for point in trajectory.points.iter().step_by(stride.max(1)) {
+        let state = TemporalState {
+            timestamp: to_utc(point.timestamp),
+            state: point.state.clone(),
+        };
+        if masks.excluded(&state) {
+            open = Some(match open {
+                Some((start, _)) => (start, point.timestamp),
+                None => (point.timestamp, point.timestamp),
+            });
+        } else if let Some(run) = open.take() {
+            runs.push(run);
+        }
+    }
+    runs.extend(open);
+
+    let mut merged: Vec<(T, T)> = Vec::new();
+    for (start, stop) in runs {
+        let (start, stop) = (start - padding, stop + padding);
+        if let Some(last) = merged.last_mut()
+            && start <= last.1
+        {
+            last.1 = stop;
+            continue;
+        }
+        merged.push((start, stop));
+    }
+    merged
+}
+
+pub fn is_in_intervals<T: PartialOrd>(intervals: &[(T, T)], time: T) -> bool {
+    let idx = intervals.partition_point(|interval| interval.1 < time);
+    idx < intervals.len() && intervals[idx].0 <= time
+}
@@ -0,0 +1,7 @@
+pub mod intervals;
+pub mod mask;
+pub mod rate_map;
+
+pub use intervals::{is_in_intervals, masked_intervals};
+pub use mask::{GeomagneticMask, Mask, MaskSet, PolygonMask};
+pub use rate_map::{GridMask, RateMap};
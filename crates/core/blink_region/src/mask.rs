@@ -0,0 +1,180 @@
+use blink_core::types::{Position, Region, TemporalState};
+use chrono::prelude::*;
+
+pub trait Mask: Send + Sync {
+    /// The region this mask assigns to `state`, or `None` if it does not match.
+    fn region(&self, state: &TemporalState<DateTime<Utc>, Position>) -> Option<Region>;
+}
+
+/// Geographic polygon of `(longitude, latitude)` vertices in degrees.
+///
+/// Longitudes are unwrapped around the first vertex, so a polygon may
+/// straddle the antimeridian as long as it spans less than 180°.
+pub struct PolygonMask {
+    pub region: Region,
+    pub vertices: Vec<(f64, f64)>,
+}
+
+impl PolygonMask {
+    pub fn new(region: Region, vertices: &[(f64, f64)]) -> Self {
+        PolygonMask {
+            region,
+            vertices: vertices.to_vec(),
+        }
+    }
+
+    pub fn contains(&self, longitude: f64, latitude: f64) -> bool {
+        let Some(&(reference, _)) = self.vertices.first() else {
+            return false;
+        };
+        let unwrap = |lon: f64| reference + (lon - reference + 180.0).rem_euclid(360.0) - 180.0;
+        let x = unwrap(longitude);
+        let y = latitude;
+
+        // 射线法
+        let mut inside = false;
+        let mut j = self.vertices.len() - 1;
+        for i in 0..self.vertices.len() {
+            let (xi, yi) = (unwrap(self.vertices[i].0), self.vertices[i].1);
+            let (xj, yj) = (unwrap(self.vertices[j].0), self.vertices[j].1);
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+}
+
+impl Mask for PolygonMask {
+    fn region(&self, state: &TemporalState<DateTime<Utc>, Position>) -> Option<Region> {
+        self.contains(state.state.longitude, state.state.latitude)
+            .then_some(self.region)
+    }
+}
+
+/// Mask on IGRF quantities: a band of field-line apex radius (the dipole L)
+/// or a weak-field threshold (the SAA shows up as |B| well below its
+/// surroundings at LEO altitudes).
+pub struct GeomagneticMask {
+    pub region: Region,
+    /// Matches where the field-line apex lies in this range (Earth radii)
+    pub apex_range: Option<(f64, f64)>,
+    /// Matches where |B| is below this value (nT)
+    pub max_field: Option<f64>,
+}
+
+impl GeomagneticMask {
+    pub fn apex_radius(region: Region, min: f64, max: f64) -> Self {
+        GeomagneticMask {
+            region,
+            apex_range: Some((min, max)),
+            max_field: None,
+        }
+    }
+
+    pub fn weak_field(region: Region, max_field: f64) -> Self {
+        GeomagneticMask {
+            region,
+            apex_range: None,
+            max_field: Some(max_field),
+        }
+    }
+}
+
+impl Mask for GeomagneticMask {
+    fn region(&self, state: &TemporalState<DateTime<Utc>, Position>) -> Option<Region> {
+        if let Some(max_field) = self.max_field {
+            let [north, east, down] = blink_geomag::field(state);
+            if (north * north + east * east + down * down).sqrt() < max_field {
+                return Some(self.region);
+            }
+        }
+        if let Some((min, max)) = self.apex_range {
+            // 开放磁力线视为超出任何区间
+            let apex = blink_geomag::apex_radius(state)?;
+            if (min..=max).contains(&apex) {
+                return Some(self.region);
+            }
+        }
+        None
+    }
+}
+
+/// Ordered set of masks. Excluding masks remove data from the search,
+/// tagging masks only label signals; the first match wins.
+#[derive(Default)]
+pub struct MaskSet {
+    masks: Vec<(Box<dyn Mask>, bool)>,
+}
+
+impl MaskSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exclude(mut self, mask: impl Mask + 'static) -> Self {
+        self.masks.push((Box::new(mask), true));
+        self
+    }
+
+    pub fn tag(mut self, mask: impl Mask + 'static) -> Self {
+        self.masks.push((Box::new(mask), false));
+        self
+    }
+
+    pub fn region(&self, state: &TemporalState<DateTime<Utc>, Position>) -> Region {
+        self.masks
+            .iter()
+            .find_map(|(mask, _)| mask.region(state))
+            .unwrap_or_default()
+    }
+
+    pub fn excluded(&self, state: &TemporalState<DateTime<Utc>, Position>) -> bool {
+        self.masks
+            .iter()
+            .any(|(mask, exclude)| *exclude && mask.region(state).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::f64::*;
+
+    fn state(longitude: f64, latitude: f64) -> TemporalState<DateTime<Utc>, Position> {
+        TemporalState {
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            state: Position {
+                longitude,
+                latitude,
+                altitude: Length::new::<uom::si::length::kilometer>(550.0),
+            },
+        }
+    }
+
+    #[test]
+    fn polygon_across_antimeridian() {
+        let polygon = PolygonMask::new(
+            Region::HighRate,
+            &[
+                (170.0, -10.0),
+                (-170.0, -10.0),
+                (-170.0, 10.0),
+                (170.0, 10.0),
+            ],
+        );
+        assert!(polygon.contains(179.0, 0.0));
+        assert!(polygon.contains(-175.0, 5.0));
+        assert!(!polygon.contains(0.0, 0.0));
+        assert!(!polygon.contains(-160.0, 0.0));
+    }
+
+    #[test]
+    fn weak_field_finds_saa() {
+        let masks = MaskSet::new().exclude(GeomagneticMask::weak_field(Region::Saa, 25_000.0));
+        assert_eq!(masks.region(&state(-45.0, -25.0)), Region::Saa);
+        assert!(masks.excluded(&state(-45.0, -25.0)));
+        assert_eq!(masks.region(&state(116.0, 40.0)), Region::Clear);
+    }
+}
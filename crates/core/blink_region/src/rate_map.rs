@@ -0,0 +1,245 @@
+use blink_core::{
+    error::Error,
+    traits::Temporal,
+    types::{Position, Region, TemporalState, Trajectory},
+};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::mask::Mask;
+
+/// Count rate accumulated on a longitude/latitude grid, typically over
+/// months of data, from which a high-background mask is derived.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(try_from = "RateMapRecord")]
+pub struct RateMap {
+    /// Cell size (degrees)
+    pub resolution: f64,
+    pub counts: Vec<f64>,
+    /// Live time per cell (s)
+    pub exposure: Vec<f64>,
+}
+
+/// Serialised form, checked against the grid of its resolution before use.
+#[derive(Deserialize)]
+struct RateMapRecord {
+    resolution: f64,
+    counts: Vec<f64>,
+    exposure: Vec<f64>,
+}
+
+impl TryFrom<RateMapRecord> for RateMap {
+    type Error = Error;
+
+    fn try_from(record: RateMapRecord) -> Result<Self, Error> {
+        if !(record.resolution > 0.0 && record.resolution <= 180.0) {
+            return Err(Error::InvalidData(format!(
+                "rate map resolution {} is not in (0, 180] degrees",
+                record.resolution
+            )));
+        }
+        let (n_lon, n_lat) = grid_size(record.resolution);
+        let cells = n_lon * n_lat;
+        if record.counts.len() != cells || record.exposure.len() != cells {
+            return Err(Error::InvalidData(format!(
+                "rate map has {} counts and {} exposures, its {}° grid needs {cells}",
+                record.counts.len(),
+                record.exposure.len(),
+                record.resolution
+            )));
+        }
+        Ok(RateMap {
+            resolution: record.resolution,
+            counts: record.counts,
+            exposure: record.exposure,
+        })
+    }
+}
+
+impl RateMap {
+    pub fn new(resolution: f64) -> Self {
+        let cells = grid_size(resolution);
+        RateMap {
+            resolution,
+            counts: vec![0.0; cells.0 * cells.1],
+            exposure: vec![0.0; cells.0 * cells.1],
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| Error::InvalidData(format!("{path}: {e}")))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let json =
+            serde_json::to_string(self).map_err(|e| Error::InvalidData(format!("{path}: {e}")))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn accumulate(&mut self, position: &Position, counts: f64, exposure: f64) {
+        let idx = cell(self.resolution, position.longitude, position.latitude);
+        self.counts[idx] += counts;
+        self.exposure[idx] += exposure;
+    }
+
+    /// Adds the events falling between consecutive trajectory samples to the
+    /// cell of the earlier sample. `times` must be sorted; segments longer
+    /// than `max_gap` are data gaps and contribute nothing.
+    pub fn accumulate_trajectory<T: Temporal>(
+        &mut self,
+        trajectory: &Trajectory<T, Position>,
+        times: &[T],
+        max_gap: T::Duration,
+    ) {
+        for pair in trajectory.points.windows(2) {
+            let (t0, t1) = (pair[0].timestamp, pair[1].timestamp);
+            if t1 - t0 > max_gap {
+                continue;
+            }
+            let start = times.partition_point(|t| *t < t0);
+            let stop = times.partition_point(|t| *t < t1);
+            self.accumulate(&pair[0].state, (stop - start) as f64, t1.seconds_since(t0));
+        }
+    }
+
+    pub fn merge(&mut self, other: &RateMap) -> Result<(), Error> {
+        if other.resolution != self.resolution {
+            return Err(Error::InvalidData(format!(
+                "rate map resolution {} does not match {}",
+                other.resolution, self.resolution
+            )));
+        }
+        for (a, b) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *a += b;
+        }
+        for (a, b) in self.exposure.iter_mut().zip(other.exposure.iter()) {
+            *a += b;
+        }
+        Ok(())
+    }
+
+    pub fn rate(&self, longitude: f64, latitude: f64) -> Option<f64> {
+        let idx = cell(self.resolution, longitude, latitude);
+        (self.exposure[idx] > 0.0).then(|| self.counts[idx] / self.exposure[idx])
+    }
+
+    /// Rate at the position on `trajectory` at `time`, i.e. the rate seen at
+    /// that place on the orbits the map was accumulated from.
+    pub fn rate_along<T: Temporal>(
+        &self,
+        trajectory: &Trajectory<T, Position>,
+        time: T,
+    ) -> Option<f64> {
+        let position = trajectory.interpolate(time).ok()?.state;
+        self.rate(position.longitude, position.latitude)
+    }
+
+    /// Flags cells whose rate exceeds `factor` times the median over cells
+    /// with at least `min_exposure` seconds; thinner cells are never flagged.
+    pub fn to_mask(&self, region: Region, factor: f64, min_exposure: f64) -> GridMask {
+        let rates = self
+            .counts
+            .iter()
+            .zip(self.exposure.iter())
+            .map(|(c, e)| (*e >= min_exposure && *e > 0.0).then(|| c / e))
+            .collect::<Vec<_>>();
+        let mut sorted = rates.iter().flatten().copied().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = sorted
+            .get(sorted.len() / 2)
+            .copied()
+            .unwrap_or(f64::INFINITY);
+
+        GridMask {
+            region,
+            resolution: self.resolution,
+            flagged: rates
+                .iter()
+                .map(|rate| rate.is_some_and(|rate| rate > factor * median))
+                .collect(),
+        }
+    }
+}
+
+/// Boolean grid derived from a [`RateMap`].
+pub struct GridMask {
+    pub region: Region,
+    pub resolution: f64,
+    pub flagged: Vec<bool>,
+}
+
+impl Mask for GridMask {
+    fn region(&self, state: &TemporalState<DateTime<Utc>, Position>) -> Option<Region> {
+        let idx = cell(self.resolution, state.state.longitude, state.state.latitude);
+        self.flagged[idx].then_some(self.region)
+    }
+}
+
+fn grid_size(resolution: f64) -> (usize, usize) {
+    (
+        (360.0 / resolution).ceil() as usize,
+        (180.0 / resolution).ceil() as usize,
+    )
+}
+
+fn cell(resolution: f64, longitude: f64, latitude: f64) -> usize {
+    let (n_lon, n_lat) = grid_size(resolution);
+    let i = (((longitude + 180.0).rem_euclid(360.0) / resolution) as usize).min(n_lon - 1);
+    let j = (((latitude + 90.0) / resolution).max(0.0) as usize).min(n_lat - 1);
+    j * n_lon + i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::f64::*;
+
+    fn at(longitude: f64, latitude: f64) -> Position {
+        Position {
+            longitude,
+            latitude,
+            altitude: Length::new::<uom::si::length::kilometer>(550.0),
+        }
+    }
+
+    #[test]
+    fn hot_cell_is_flagged() {
+        let mut map = RateMap::new(10.0);
+        for lon in (-175..180).step_by(10) {
+            map.accumulate(&at(lon as f64, 5.0), 100.0, 100.0);
+        }
+        map.accumulate(&at(-45.0, 5.0), 5000.0, 0.0);
+
+        let mask = map.to_mask(Region::HighRate, 3.0, 10.0);
+        let state = |position| TemporalState {
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            state: position,
+        };
+        assert_eq!(mask.region(&state(at(-45.0, 5.0))), Some(Region::HighRate));
+        assert_eq!(mask.region(&state(at(45.0, 5.0))), None);
+        assert_eq!(mask.region(&state(at(-45.0, 45.0))), None);
+    }
+
+    #[test]
+    fn mismatched_grid_is_rejected() {
+        let map = RateMap::new(10.0);
+        let json = serde_json::to_string(&map).unwrap();
+        assert!(serde_json::from_str::<RateMap>(&json).is_ok());
+
+        let mut truncated = map.clone();
+        truncated.exposure.pop();
+        let json = serde_json::to_string(&truncated).unwrap();
+        assert!(serde_json::from_str::<RateMap>(&json).is_err());
+
+        let json = json.replacen("\"resolution\":10.0", "\"resolution\":5.0", 1);
+        assert!(serde_json::from_str::<RateMap>(&json).is_err());
+        let json = serde_json::to_string(&RateMap {
+            resolution: 0.0,
+            ..map
+        })
+        .unwrap();
+        assert!(serde_json::from_str::<RateMap>(&json).is_err());
+    }
+}
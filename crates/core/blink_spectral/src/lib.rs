@@ -0,0 +1,13 @@
+//! Forward-folding spectral fits of bright signals: photon models are folded
+//! through an OGIP response and compared with the count spectrum of the
+//! signal by Poisson likelihood.
+
+pub mod fit;
+pub mod model;
+pub mod response;
+pub mod spectrum;
+
+pub use fit::{Fit, Parameter, fit};
+pub use model::{Model, Template};
+pub use response::Response;
+pub use spectrum::CountSpectrum;
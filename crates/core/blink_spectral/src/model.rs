@@ -0,0 +1,194 @@
+//! Photon spectra in photons cm⁻² s⁻¹ keV⁻¹. The first parameter of every
+//! model is its amplitude, which scales it linearly.
+
+use blink_core::error::Error;
+use serde::{Deserialize, Serialize};
+
+/// Energy in keV at which the amplitude of the analytic models is given
+pub const PIVOT: f64 = 100.0;
+
+/// A photon spectrum with free parameters.
+#[derive(Clone, Debug)]
+pub enum Model {
+    /// `A (E / 100 keV)^-index exp(-E / cutoff)`
+    CutoffPowerLaw,
+    /// Band et al. (1993) with `peak` the νFν peak energy: a cutoff power
+    /// law of index `alpha` joined smoothly to a power law of index `beta`
+    Band,
+    /// A tabulated relativistic-runaway bremsstrahlung spectrum times `A`
+    Rrea(Template),
+}
+
+impl Model {
+    pub fn name(&self) -> String {
+        match self {
+            Model::CutoffPowerLaw => "cutoff-power-law".to_string(),
+            Model::Band => "band".to_string(),
+            Model::Rrea(template) => format!("rrea:{}", template.name),
+        }
+    }
+
+    /// Name of each parameter and whether it is fitted in logarithm, being
+    /// positive and spanning decades.
+    pub fn parameters(&self) -> &'static [(&'static str, bool)] {
+        match self {
+            Model::CutoffPowerLaw => &[("amplitude", true), ("index", false), ("cutoff", true)],
+            Model::Band => &[
+                ("amplitude", true),
+                ("alpha", false),
+                ("beta", false),
+                ("peak", true),
+            ],
+            Model::Rrea(_) => &[("amplitude", true)],
+        }
+    }
+
+    /// Starting point of a fit, with unit amplitude.
+    pub fn initial(&self) -> Vec<f64> {
+        match self {
+            Model::CutoffPowerLaw => vec![1.0, 1.0, 1000.0],
+            Model::Band => vec![1.0, -1.0, -2.5, 300.0],
+            Model::Rrea(_) => vec![1.0],
+        }
+    }
+
+    /// Whether `parameters` lie in the domain of the model.
+    pub fn valid(&self, parameters: &[f64]) -> bool {
+        let finite = parameters.iter().all(|value| value.is_finite());
+        finite
+            && match *self {
+                Model::CutoffPowerLaw => parameters[0] > 0.0 && parameters[2] > 0.0,
+                Model::Band => {
+                    let [amplitude, alpha, beta, peak] = parameters[..4] else {
+                        return false;
+                    };
+                    amplitude > 0.0 && alpha > -2.0 && beta < alpha && peak > 0.0
+                }
+                Model::Rrea(_) => parameters[0] > 0.0,
+            }
+    }
+
+    pub fn photon_flux(&self, parameters: &[f64], energy: f64) -> f64 {
+        match self {
+            Model::CutoffPowerLaw => {
+                let [amplitude, index, cutoff] = parameters[..3] else {
+                    return 0.0;
+                };
+                amplitude * (energy / PIVOT).powf(-index) * (-energy / cutoff).exp()
+            }
+            Model::Band => {
+                let [amplitude, alpha, beta, peak] = parameters[..4] else {
+                    return 0.0;
+                };
+                let folding = peak / (2.0 + alpha);
+                let break_energy = (alpha - beta) * folding;
+                if energy < break_energy {
+                    amplitude * (energy / PIVOT).powf(alpha) * (-energy / folding).exp()
+                } else {
+                    amplitude
+                        * (break_energy / PIVOT).powf(alpha - beta)
+                        * (beta - alpha).exp()
+                        * (energy / PIVOT).powf(beta)
+                }
+            }
+            Model::Rrea(template) => parameters[0] * template.value(energy),
+        }
+    }
+}
+
+/// Photon spectrum tabulated on an energy grid, e.g. a Monte Carlo
+/// simulation of runaway electrons at a given source altitude propagated to
+/// the spacecraft.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    /// keV, ascending
+    pub energies: Vec<f64>,
+    /// Photons per keV, in any normalisation
+    pub flux: Vec<f64>,
+}
+
+impl Template {
+    /// Bremsstrahlung of a fully developed avalanche at its source,
+    /// `E^-1 exp(-E / 7.3 MeV)` (Dwyer & Smith 2005), before attenuation in
+    /// the atmosphere.
+    pub fn rrea_source() -> Self {
+        let energies = (0..=200)
+            .map(|i| 10.0 * 4000f64.powf(i as f64 / 200.0))
+            .collect::<Vec<_>>();
+        let flux = energies
+            .iter()
+            .map(|&energy| (PIVOT / energy) * (-(energy - PIVOT) / 7300.0).exp())
+            .collect();
+        Self {
+            name: "source".to_string(),
+            energies,
+            flux,
+        }
+    }
+
+    /// Reads a JSON template.
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)?;
+        let template: Self =
+            serde_json::from_str(&text).map_err(|e| Error::InvalidData(format!("{path}: {e}")))?;
+        if template.energies.len() < 2
+            || template.energies.len() != template.flux.len()
+            || template.energies.windows(2).any(|pair| pair[0] >= pair[1])
+            || template.energies[0] <= 0.0
+            || template.flux.iter().any(|&flux| flux < 0.0)
+        {
+            return Err(Error::InvalidData(format!(
+                "{path}: energies must ascend from above zero with one non-negative flux each"
+            )));
+        }
+        Ok(template)
+    }
+
+    /// Log-log interpolation of the flux at `energy`, zero off the grid.
+    fn value(&self, energy: f64) -> f64 {
+        let upper = self.energies.partition_point(|&e| e < energy);
+        if upper == 0 || upper == self.energies.len() {
+            return if self.energies.get(upper) == Some(&energy) {
+                self.flux[upper]
+            } else {
+                0.0
+            };
+        }
+        let (e0, e1) = (self.energies[upper - 1], self.energies[upper]);
+        let (f0, f1) = (self.flux[upper - 1], self.flux[upper]);
+        if f0 <= 0.0 || f1 <= 0.0 {
+            // 有零值时退回线性插值
+            return f0 + (f1 - f0) * (energy - e0) / (e1 - e0);
+        }
+        let fraction = (energy / e0).ln() / (e1 / e0).ln();
+        f0 * (f1 / f0).powf(fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn band_is_continuous_at_the_break() {
+        let parameters = [1.0, -0.5, -2.3, 500.0];
+        let break_energy = (-0.5 + 2.3) * 500.0 / 1.5;
+        let below = Model::Band.photon_flux(&parameters, break_energy * (1.0 - 1e-9));
+        let above = Model::Band.photon_flux(&parameters, break_energy);
+        assert!((below / above - 1.0).abs() < 1e-6, "{below} {above}");
+    }
+
+    #[test]
+    fn template_interpolates_power_laws_exactly() {
+        let template = Template {
+            name: "test".to_string(),
+            energies: vec![10.0, 1000.0],
+            flux: vec![100.0, 0.01],
+        };
+        assert!((template.value(100.0) - 1.0).abs() < 1e-12);
+        assert_eq!(template.value(1000.0), 0.01);
+        assert_eq!(template.value(5.0), 0.0);
+        assert_eq!(template.value(2000.0), 0.0);
+    }
+}
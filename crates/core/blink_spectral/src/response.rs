@@ -0,0 +1,223 @@
+//! OGIP response matrices: an RSP file, or an RMF with an optional ARF.
+
+use blink_core::{
+    error::{Error, ResultExt},
+    fits,
+    types::Ebounds,
+};
+use fitsio::FitsFile;
+
+use crate::model::Model;
+
+/// Points per photon bin of the Simpson integration of a model
+const SIMPSON_POINTS: usize = 3;
+
+/// Photon energy bins and the matrix rows of each
+type Matrix = (Vec<[f64; 2]>, Vec<Vec<f64>>);
+
+/// Effective area of every photon energy bin into every channel.
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// Photon energy bins `[low, high)` in keV, ascending
+    pub energies: Vec<[f64; 2]>,
+    /// Energy bounds in keV of the channels
+    pub channels: Ebounds,
+    /// cm², by photon bin then channel; includes the ARF when given
+    pub matrix: Vec<Vec<f64>>,
+}
+
+impl Response {
+    pub fn new(
+        energies: Vec<[f64; 2]>,
+        channels: Ebounds,
+        matrix: Vec<Vec<f64>>,
+    ) -> Result<Self, Error> {
+        if matrix.len() != energies.len() || matrix.iter().any(|row| row.len() != channels.len()) {
+            return Err(Error::InvalidData(format!(
+                "response matrix is not {} × {}",
+                energies.len(),
+                channels.len()
+            )));
+        }
+        Ok(Self {
+            energies,
+            channels,
+            matrix,
+        })
+    }
+
+    /// Reads an RSP file, whose matrix already includes the effective area.
+    pub fn from_rsp(path: &str) -> Result<Self, Error> {
+        Self::read(path).path(path)
+    }
+
+    /// Reads an RMF and multiplies each photon bin by the effective area of
+    /// the ARF, if any, on the same energy grid.
+    pub fn from_rmf(rmf: &str, arf: Option<&str>) -> Result<Self, Error> {
+        let mut response = Self::read(rmf).path(rmf)?;
+        if let Some(arf) = arf {
+            let (energies, area) = read_arf(arf).path(arf)?;
+            let same = |a: f64, b: f64| (a - b).abs() <= 1e-4 * a.abs().max(b.abs());
+            if energies.len() != response.energies.len()
+                || energies
+                    .iter()
+                    .zip(&response.energies)
+                    .any(|(a, b)| !same(a[0], b[0]) || !same(a[1], b[1]))
+            {
+                return Err(Error::InvalidData(format!(
+                    "{arf}: energy grid differs from {rmf}"
+                )));
+            }
+            for (row, area) in response.matrix.iter_mut().zip(area) {
+                row.iter_mut().for_each(|value| *value *= area);
+            }
+        }
+        Ok(response)
+    }
+
+    fn read(path: &str) -> Result<Self, Error> {
+        let mut fptr = FitsFile::open(path)?;
+        let channels = read_ebounds(&mut fptr)?;
+        let (energies, matrix) = read_matrix(&mut fptr, channels.len())?;
+        Self::new(energies, channels, matrix)
+    }
+
+    /// Channel whose bounds contain `energy`.
+    pub fn channel(&self, energy: f64) -> Option<usize> {
+        self.channels
+            .iter()
+            .position(|&[low, high]| low <= energy && energy < high)
+    }
+
+    /// Counts expected in each channel from `model` with `parameters` over
+    /// `exposure` seconds.
+    pub fn fold(&self, model: &Model, parameters: &[f64], exposure: f64) -> Vec<f64> {
+        let mut counts = vec![0.0; self.channels.len()];
+        for (&[low, high], row) in self.energies.iter().zip(&self.matrix) {
+            let photons =
+                integrate(|energy| model.photon_flux(parameters, energy), low, high) * exposure;
+            if photons == 0.0 {
+                continue;
+            }
+            for (count, &area) in counts.iter_mut().zip(row) {
+                *count += photons * area;
+            }
+        }
+        counts
+    }
+}
+
+/// Simpson's rule over `[low, high]`.
+fn integrate(f: impl Fn(f64) -> f64, low: f64, high: f64) -> f64 {
+    let steps = SIMPSON_POINTS - 1;
+    let width = (high - low) / steps as f64;
+    let sum = (0..SIMPSON_POINTS)
+        .map(|i| {
+            let weight = if i == 0 || i == steps {
+                1.0
+            } else if i % 2 == 1 {
+                4.0
+            } else {
+                2.0
+            };
+            weight * f(low + width * i as f64)
+        })
+        .sum::<f64>();
+    sum * width / 3.0
+}
+
+fn read_ebounds(fptr: &mut FitsFile) -> Result<Ebounds, Error> {
+    let hdu = fits::hdu(fptr, "EBOUNDS")?;
+    let e_min = fits::read_col::<f64>(fptr, &hdu, "E_MIN")?;
+    let e_max = fits::read_col::<f64>(fptr, &hdu, "E_MAX")?;
+    Ok(e_min.into_iter().zip(e_max).map(|(a, b)| [a, b]).collect())
+}
+
+/// Photon bins and the dense matrix of the `MATRIX` extension, expanding the
+/// channel groups of each row. `F_CHAN`, `N_CHAN` and `MATRIX` may be
+/// fixed-width or variable-length columns.
+fn read_matrix(fptr: &mut FitsFile, channels: usize) -> Result<Matrix, Error> {
+    let mut table = fits::RawTable::open(fptr, &["SPECRESP MATRIX", "MATRIX"])?;
+    // 道址编号的起点，OGIP 默认为 1
+    let offset = table.column_key("TLMIN", "F_CHAN").unwrap_or(1);
+
+    let mut energies = Vec::with_capacity(table.rows);
+    let mut matrix = vec![vec![0.0; channels]; table.rows];
+    for (row, dense) in matrix.iter_mut().enumerate() {
+        let low = table.read_row("ENERG_LO", row)?;
+        let high = table.read_row("ENERG_HI", row)?;
+        let (Some(&low), Some(&high)) = (low.first(), high.first()) else {
+            return Err(Error::InvalidData(format!(
+                "row {row} has no energy bounds"
+            )));
+        };
+        energies.push([low, high]);
+
+        let groups = table
+            .read_row("N_GRP", row)?
+            .first()
+            .copied()
+            .unwrap_or(0.0);
+        let first = table.read_row("F_CHAN", row)?;
+        let number = table.read_row("N_CHAN", row)?;
+        // 定长列按 N_GRP 截取，变长列只存实际的分组
+        let groups = (groups.max(0.0) as usize)
+            .min(first.len())
+            .min(number.len());
+        let mut values = table.read_row("MATRIX", row)?.into_iter();
+        for group in 0..groups {
+            let start = first[group] as i64 - offset;
+            for channel in start..start + number[group] as i64 {
+                let value = values.next().ok_or_else(|| {
+                    Error::InvalidData(format!("MATRIX row {row} shorter than its groups"))
+                })?;
+                if let Some(cell) = usize::try_from(channel).ok().and_then(|c| dense.get_mut(c)) {
+                    *cell = value;
+                }
+            }
+        }
+    }
+    Ok((energies, matrix))
+}
+
+fn read_arf(path: &str) -> Result<(Vec<[f64; 2]>, Vec<f64>), Error> {
+    let mut fptr = FitsFile::open(path)?;
+    let hdu = fits::hdu(&mut fptr, "SPECRESP")?;
+    let low = fits::read_col::<f64>(&mut fptr, &hdu, "ENERG_LO")?;
+    let high = fits::read_col::<f64>(&mut fptr, &hdu, "ENERG_HI")?;
+    let area = fits::read_col::<f64>(&mut fptr, &hdu, "SPECRESP")?;
+    let energies = low.into_iter().zip(high).map(|(a, b)| [a, b]).collect();
+    Ok((energies, area))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simpson_is_exact_for_cubics() {
+        let integral = integrate(|x| x * x * x - 2.0 * x, 1.0, 3.0);
+        assert!((integral - (20.0 - 8.0)).abs() < 1e-12, "{integral}");
+    }
+
+    #[test]
+    fn reads_variable_length_matrix() {
+        // OGIP 常见布局：F_CHAN/N_CHAN/MATRIX 为变长列，道址从 0 起
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/vla.rsp");
+        let response = Response::from_rsp(path).unwrap();
+        assert_eq!(
+            response.energies,
+            [[10.0, 20.0], [20.0, 40.0], [40.0, 80.0]]
+        );
+        assert_eq!(response.channels.len(), 4);
+        assert_eq!(
+            response.matrix,
+            [
+                vec![0.5, 0.25, 0.0, 0.0],
+                vec![0.125, 0.0, 0.375, 0.625],
+                vec![0.0; 4],
+            ]
+        );
+        assert_eq!(response.channel(20.0), Some(1));
+    }
+}
@@ -0,0 +1,72 @@
+use blink_core::{traits::Event, types::MissionElapsedTime};
+use serde::Serialize;
+use uom::si::f64::*;
+
+use crate::response::Response;
+
+/// Counts of a signal in the channels of a response.
+#[derive(Clone, Debug, Serialize)]
+pub struct CountSpectrum {
+    pub counts: Vec<u32>,
+    /// Background counts expected in each channel over the exposure
+    pub background: Vec<f64>,
+    /// Seconds, not corrected for dead time
+    pub exposure: f64,
+}
+
+impl CountSpectrum {
+    /// Spectrum of the events passing `Event::keep` in `span`, with the
+    /// background taken from windows of `margin` on either side.
+    ///
+    /// `events` are sorted by time; events outside the channels of
+    /// `response` are dropped.
+    pub fn from_events<E: Event>(
+        events: &[E],
+        response: &Response,
+        span: [MissionElapsedTime<E::Instrument>; 2],
+        margin: Time,
+    ) -> Self {
+        let slice = |from: MissionElapsedTime<E::Instrument>,
+                     to: MissionElapsedTime<E::Instrument>| {
+            let from = events.partition_point(|event| event.time() < from);
+            let to = events.partition_point(|event| event.time() < to);
+            &events[from..to.max(from)]
+        };
+        let histogram = |events: &[E]| {
+            let mut counts = vec![0; response.channels.len()];
+            for event in events.iter().filter(|event| event.keep()) {
+                if let Some(channel) = response.channel(event.energy()) {
+                    counts[channel] += 1;
+                }
+            }
+            counts
+        };
+
+        let exposure = (span[1] - span[0]).get::<uom::si::time::second>();
+        let counts = histogram(slice(span[0], span[1]));
+        let sidebands = [
+            histogram(slice(span[0] - margin, span[0])),
+            histogram(slice(span[1], span[1] + margin)),
+        ];
+        let scale = if margin.get::<uom::si::time::second>() > 0.0 {
+            exposure / (2.0 * margin.get::<uom::si::time::second>())
+        } else {
+            0.0
+        };
+        let background = sidebands[0]
+            .iter()
+            .zip(&sidebands[1])
+            .map(|(&before, &after)| (before + after) as f64 * scale)
+            .collect();
+        Self {
+            counts,
+            background,
+            exposure,
+        }
+    }
+
+    /// Counts above the expected background.
+    pub fn net(&self) -> f64 {
+        self.counts.iter().sum::<u32>() as f64 - self.background.iter().sum::<f64>()
+    }
+}
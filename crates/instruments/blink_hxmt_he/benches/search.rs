@@ -0,0 +1,72 @@
+//! Serial against sharded `search_new` on one saved hour of HE data.
+//!
+//! ```text
+//! BLINK_BENCH_HOUR=2020-04-15T08 cargo bench -p blink_hxmt_he --bench search
+//! ```
+//!
+//! Reads the hour through `Chunk::from_epoch`, so the 1K files must be where
+//! the search finds them. Every run checks that the shards reproduce the
+//! serial candidates exactly.
+
+use blink_algorithms::snapshot_stepping::search_sharded;
+use blink_core::traits::{Chunk as _, Event as _, Instrument as _};
+use blink_hxmt_he::types::{Chunk, HxmtHe};
+use chrono::prelude::*;
+use std::time::{Duration, Instant};
+
+const ROUNDS: usize = 3;
+
+fn main() {
+    let Ok(hour) = std::env::var("BLINK_BENCH_HOUR") else {
+        eprintln!("BLINK_BENCH_HOUR not set (e.g. 2020-04-15T08), skipping");
+        return;
+    };
+    let naive = NaiveDateTime::parse_from_str(&format!("{hour}:00:00"), "%Y-%m-%dT%H:%M:%S")
+        .unwrap_or_else(|e| panic!("invalid BLINK_BENCH_HOUR '{hour}': {e}"));
+    let chunk = Chunk::from_epoch(&Utc.from_utc_datetime(&naive)).expect("failed to load hour");
+    let events = chunk
+        .events()
+        .into_iter()
+        .filter(|event| event.keep())
+        .collect::<Vec<_>>();
+    eprintln!("{hour}: {} events", events.len());
+
+    let run = |shards: usize| {
+        let mut best = Duration::MAX;
+        let mut candidates = Vec::new();
+        for _ in 0..ROUNDS {
+            let begin = Instant::now();
+            candidates = search_sharded(
+                &events,
+                HxmtHe::group_names().len(),
+                chunk.data_span[0],
+                chunk.data_span[1],
+                Chunk::search_config(),
+                shards,
+            );
+            best = best.min(begin.elapsed());
+        }
+        (best, candidates)
+    };
+
+    let (serial, reference) = run(1);
+    eprintln!(
+        "shards  1: {:>8.3} s  {} candidates",
+        serial.as_secs_f64(),
+        reference.len()
+    );
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    for shards in [2, 4, cores].into_iter().filter(|&n| n > 1) {
+        let (elapsed, candidates) = run(shards);
+        assert!(
+            candidates == reference,
+            "{shards} shards differ from the serial search"
+        );
+        eprintln!(
+            "shards {:>2}: {:>8.3} s  speed-up {:.2}",
+            shards,
+            elapsed.as_secs_f64(),
+            serial.as_secs_f64() / elapsed.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Tells photon bursts from cosmic-ray showers and electronic glitches by
+//! the events of a candidate.
+//!
+//! A shower crosses several detectors at once, fires the anti-coincidence
+//! shields and deposits in the NaI as well as the CsI; a glitch repeats the
+//! same channel or stays in a single detector. Photons of a TGF spread over
+//! tens of microseconds and over the CsI channels without ACD hits.
+//!
+//! Coincidences are only counted against a shower beyond those expected by
+//! chance at the rate of the candidate, so that a bright burst of photons
+//! is not taken for one.
+
+use blink_algorithms::snapshot_stepping::poisson_isf;
+use blink_core::traits::Event as _;
+use blink_core::types::{Classification, Label};
+use std::collections::BTreeMap;
+use uom::si::f64::*;
+
+use crate::types::{Event, Scintillator};
+
+/// Hits in different detectors this close in time are coincident
+const COINCIDENCE_US: f64 = 2.0;
+/// Channels from which deposits are close to saturation
+const HIGH_CHANNEL: u16 = 250;
+
+/// Glitch: share of events in one channel
+const GLITCH_CHANNEL_FRACTION: f64 = 0.5;
+/// Glitch: share of events in one detector
+const GLITCH_DETECTOR_FRACTION: f64 = 0.9;
+/// Particle: share of events with an ACD hit
+const PARTICLE_ACD_FRACTION: f64 = 0.3;
+/// Particle: share of events in NaI
+const PARTICLE_NAI_FRACTION: f64 = 0.5;
+/// Particle: detectors hit within one coincidence window, at least
+const PARTICLE_DETECTORS: usize = 6;
+/// Particle: chance that the most detectors hit within one window are
+/// reached by chance somewhere in the candidate, at most
+const PARTICLE_DETECTORS_CHANCE: f64 = 0.01;
+/// Particle: share of events coincident with another detector, beyond
+/// chance and as a fraction of those not coincident by chance
+const PARTICLE_COINCIDENT_FRACTION: f64 = 0.8;
+/// Coincident fractions expected by chance above this no longer tell
+/// showers from photons
+const MAX_CHANCE_FRACTION: f64 = 0.5;
+/// Particle: share of events near saturation
+const PARTICLE_HIGH_FRACTION: f64 = 0.5;
+
+/// Summary of the events of a candidate, Am-241 calibration events
+/// excluded. Fractions are of those events.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Features {
+    pub events: usize,
+    /// Events with any of the 18 ACD flags set
+    pub acd_fraction: f64,
+    /// Events in NaI rather than CsI
+    pub nai_fraction: f64,
+    /// Most detectors hit within one coincidence window
+    pub max_coincident_detectors: usize,
+    /// Events with a hit in another detector within the coincidence window
+    pub coincident_fraction: f64,
+    /// Events per second between the first and the last event; zero if
+    /// they coincide
+    pub rate: f64,
+    /// `coincident_fraction` expected by chance at `rate`
+    pub chance_fraction: f64,
+    /// Distinct detectors hit
+    pub detectors: usize,
+    /// Largest share in a single detector
+    pub top_detector_fraction: f64,
+    /// Largest share in a single channel, and that channel
+    pub top_channel_fraction: f64,
+    pub top_channel: u16,
+    /// Events at or above `HIGH_CHANNEL`
+    pub high_channel_fraction: f64,
+    pub mean_channel: f64,
+}
+
+impl Features {
+    /// Features of `events`, sorted by time.
+    pub fn extract(events: &[Event]) -> Self {
+        let events = events
+            .iter()
+            .filter(|event| !event.is_am241)
+            .collect::<Vec<_>>();
+        let total = events.len().max(1) as f64;
+        let share = |count: usize| count as f64 / total;
+
+        let mut per_detector = [0; 18];
+        let mut per_channel = [0; 276];
+        for event in &events {
+            per_detector[event.detector.id as usize % 18] += 1;
+            per_channel[(event.channel() as usize).min(275)] += 1;
+        }
+        let (top_channel, &top_channel_count) = per_channel
+            .iter()
+            .enumerate()
+            .max_by_key(|&(channel, count)| (*count, std::cmp::Reverse(channel)))
+            .unwrap();
+
+        // 双指针扫描每个事例前后的符合窗口
+        let window = Time::new::<uom::si::time::microsecond>(COINCIDENCE_US);
+        let (mut max_coincident_detectors, mut coincident) = (0, 0);
+        let mut from = 0;
+        for (i, event) in events.iter().enumerate() {
+            while event.time() - events[from].time() > window {
+                from += 1;
+            }
+            let mut to = i;
+            while to + 1 < events.len() && events[to + 1].time() - event.time() <= window {
+                to += 1;
+            }
+            let others = events[from..=to]
+                .iter()
+                .any(|other| other.detector.id != event.detector.id);
+            coincident += others as usize;
+            let hit = events[i..=to]
+                .iter()
+                .fold(0u32, |mask, other| mask | 1 << (other.detector.id % 18));
+            max_coincident_detectors = max_coincident_detectors.max(hit.count_ones() as usize);
+        }
+
+        // 各探测器独立泊松到达时，±窗口内出现其他探测器事例的概率
+        let span = match (events.first(), events.last()) {
+            (Some(first), Some(last)) => {
+                (last.time() - first.time()).get::<uom::si::time::second>()
+            }
+            _ => 0.0,
+        };
+        let detectors = per_detector.iter().filter(|&&count| count > 0).count();
+        let others = 1.0 - 1.0 / detectors.max(1) as f64;
+        // 所有事例同一时刻时无从比较，视作全部可能偶然符合
+        let (rate, chance_fraction) = if span > 0.0 {
+            let rate = events.len() as f64 / span;
+            let mean = 2.0 * window.get::<uom::si::time::second>() * rate * others;
+            (rate, 1.0 - (-mean).exp())
+        } else {
+            (0.0, 1.0)
+        };
+
+        Self {
+            events: events.len(),
+            acd_fraction: share(
+                events
+                    .iter()
+                    .filter(|event| event.acds.iter().any(|&acd| acd))
+                    .count(),
+            ),
+            nai_fraction: share(
+                events
+                    .iter()
+                    .filter(|event| event.detector.scintillator == Scintillator::Nai)
+                    .count(),
+            ),
+            max_coincident_detectors,
+            coincident_fraction: share(coincident),
+            rate,
+            chance_fraction,
+            detectors,
+            top_detector_fraction: share(per_detector.iter().copied().max().unwrap_or(0)),
+            top_channel_fraction: share(top_channel_count),
+            top_channel: top_channel as u16,
+            high_channel_fraction: share(
+                events
+                    .iter()
+                    .filter(|event| event.channel() >= HIGH_CHANNEL)
+                    .count(),
+            ),
+            mean_channel: events
+                .iter()
+                .map(|event| event.channel() as f64)
+                .sum::<f64>()
+                / total,
+        }
+    }
+
+    /// Fewest detectors within one window that chance at `rate` is
+    /// unlikely to produce anywhere among the events, and not below
+    /// `PARTICLE_DETECTORS`.
+    fn particle_detectors(&self) -> usize {
+        if self.chance_fraction >= 1.0 {
+            return usize::MAX;
+        }
+        let window = COINCIDENCE_US * 1e-6;
+        let chance = PARTICLE_DETECTORS_CHANCE / self.events.max(1) as f64;
+        let by_chance = poisson_isf(chance, self.rate * window) as usize;
+        by_chance.saturating_add(1).max(PARTICLE_DETECTORS)
+    }
+
+    /// Share of the events not coincident by chance that are coincident.
+    fn coincident_excess(&self) -> Option<f64> {
+        (self.chance_fraction <= MAX_CHANCE_FRACTION).then(|| {
+            (self.coincident_fraction - self.chance_fraction) / (1.0 - self.chance_fraction)
+        })
+    }
+
+    fn to_map(&self) -> BTreeMap<String, f64> {
+        [
+            ("events", self.events as f64),
+            ("acd_fraction", self.acd_fraction),
+            ("nai_fraction", self.nai_fraction),
+            (
+                "max_coincident_detectors",
+                self.max_coincident_detectors as f64,
+            ),
+            ("coincident_fraction", self.coincident_fraction),
+            ("rate", self.rate),
+            ("chance_fraction", self.chance_fraction),
+            ("detectors", self.detectors as f64),
+            ("top_detector_fraction", self.top_detector_fraction),
+            ("top_channel_fraction", self.top_channel_fraction),
+            ("top_channel", self.top_channel as f64),
+            ("high_channel_fraction", self.high_channel_fraction),
+            ("mean_channel", self.mean_channel),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+}
+
+/// Labels the candidate made of `events`, sorted by time. Glitch rules are
+/// checked first, then particle rules; every rule that fires is recorded.
+pub fn classify(events: &[Event]) -> Classification {
+    let features = Features::extract(events);
+    let percent = |fraction: f64| format!("{:.0}%", fraction * 100.0);
+
+    let mut glitch = Vec::new();
+    if features.top_channel_fraction >= GLITCH_CHANNEL_FRACTION {
+        glitch.push(format!(
+            "{} of events in channel {}",
+            percent(features.top_channel_fraction),
+            features.top_channel
+        ));
+    }
+    if features.top_detector_fraction >= GLITCH_DETECTOR_FRACTION {
+        glitch.push(format!(
+            "{} of events in one detector",
+            percent(features.top_detector_fraction)
+        ));
+    }
+
+    let mut particle = Vec::new();
+    if features.acd_fraction >= PARTICLE_ACD_FRACTION {
+        particle.push(format!(
+            "ACD hits on {} of events",
+            percent(features.acd_fraction)
+        ));
+    }
+    if features.nai_fraction >= PARTICLE_NAI_FRACTION {
+        particle.push(format!(
+            "{} of events in NaI",
+            percent(features.nai_fraction)
+        ));
+    }
+    if features.max_coincident_detectors >= features.particle_detectors() {
+        particle.push(format!(
+            "{} detectors hit within {COINCIDENCE_US} µs",
+            features.max_coincident_detectors
+        ));
+    }
+    if let Some(excess) = features.coincident_excess()
+        && excess >= PARTICLE_COINCIDENT_FRACTION
+    {
+        particle.push(format!(
+            "{} of events coincident with another detector, {} by chance",
+            percent(features.coincident_fraction),
+            percent(features.chance_fraction)
+        ));
+    }
+    if features.high_channel_fraction >= PARTICLE_HIGH_FRACTION {
+        particle.push(format!(
+            "{} of events above channel {HIGH_CHANNEL}",
+            percent(features.high_channel_fraction)
+        ));
+    }
+
+    let label = if !glitch.is_empty() {
+        Label::GlitchLike
+    } else if !particle.is_empty() {
+        Label::ParticleLike
+    } else {
+        Label::PhotonLike
+    };
+    let mut reasons = [glitch, particle].concat();
+    if reasons.is_empty() {
+        reasons.push("no particle or glitch signature".to_string());
+    }
+    Classification {
+        label,
+        reasons,
+        features: features.to_map(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Detector;
+    use blink_core::types::MissionElapsedTime;
+
+    fn event(microseconds: f64, channel: u8, detector: u8, acd: bool) -> Event {
+        let mut acds = [false; 18];
+        acds[detector as usize] = acd;
+        Event::new(
+            MissionElapsedTime::new(1e8 + microseconds * 1e-6),
+            channel,
+            Detector {
+                id: detector,
+                scintillator: Scintillator::Csi,
+            },
+            false,
+            acds,
+        )
+    }
+
+    #[test]
+    fn labels_photons_showers_and_glitches() {
+        // 50 µs 内 20 个光子，道址与探测器分散
+        let photons = (0..20)
+            .map(|i| event(i as f64 * 2.5, 40 + 7 * i as u8, i as u8 % 18, false))
+            .collect::<Vec<_>>();
+        let photon = classify(&photons);
+        assert_eq!(photon.label, Label::PhotonLike, "{:?}", photon.reasons);
+
+        // 相隔 150 µs 的两次簇射，各在 1 µs 内触发 10 个探测器并带 ACD
+        let shower = (0..20)
+            .map(|i| {
+                let front = (i / 10) as f64 * 150.0;
+                let i = i % 10;
+                event(front + i as f64 * 0.1, 60 + 10 * i as u8, i as u8, true)
+            })
+            .collect::<Vec<_>>();
+        let particle = classify(&shower);
+        assert_eq!(particle.label, Label::ParticleLike);
+        assert!(particle.reasons.len() >= 2, "{:?}", particle.reasons);
+        assert_eq!(particle.features["max_coincident_detectors"], 10.0);
+
+        // 同一探测器同一道址反复出现
+        let repeats = (0..10)
+            .map(|i| event(i as f64 * 5.0, 120, 3, false))
+            .collect::<Vec<_>>();
+        assert_eq!(classify(&repeats).label, Label::GlitchLike);
+    }
+
+    #[test]
+    fn dense_photon_burst_is_not_a_shower() {
+        // 100 µs 内约 400 个光子（4 MHz），泊松到达，探测器与道址分散
+        let golden = 0.618_033_988_749_895;
+        let mut time = 0.0;
+        let photons = (0..400)
+            .map(|i| {
+                let uniform = 1.0 - (i as f64 * golden).fract();
+                time += -uniform.ln() * 0.25;
+                event(time, 40 + (i * 13 % 200) as u8, (i * 7 % 18) as u8, false)
+            })
+            .collect::<Vec<_>>();
+        let features = Features::extract(&photons);
+        assert!(features.coincident_fraction > 0.8);
+        assert!(features.max_coincident_detectors >= PARTICLE_DETECTORS);
+        let photon = classify(&photons);
+        assert_eq!(photon.label, Label::PhotonLike, "{:?}", photon.reasons);
+    }
+}
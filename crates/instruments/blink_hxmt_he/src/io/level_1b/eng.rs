@@ -1,4 +1,4 @@
-use blink_core::error::Error;
+use blink_core::{error::Error, fits};
 
 /// 从工程数据文件中读取 stime→UTC 的固定偏移量。
 ///
@@ -6,10 +6,10 @@ use blink_core::error::Error;
 /// 给出精确的 UTC↔stime 映射。offset = UTC - stime，在整个小时内恒定。
 pub fn read_stime_offset(filename: &str) -> Result<f64, Error> {
     let mut fptr = fitsio::FitsFile::open(filename)?;
-    let hdu = fptr.hdu("HE_Eng")?;
+    let hdu = fits::hdu(&mut fptr, "HE_Eng")?;
 
-    let utc: Vec<i64> = hdu.read_col(&mut fptr, "UTC_Last_Bdc")?;
-    let stime: Vec<i64> = hdu.read_col(&mut fptr, "sTime_Last_Bdc")?;
+    let utc: Vec<i64> = fits::read_col(&mut fptr, &hdu, "UTC_Last_Bdc")?;
+    let stime: Vec<i64> = fits::read_col(&mut fptr, &hdu, "sTime_Last_Bdc")?;
 
     if utc.is_empty() || stime.is_empty() {
         return Err(Error::InvalidData("Empty eng data".into()));
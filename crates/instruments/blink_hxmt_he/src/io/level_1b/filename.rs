@@ -1,3 +1,4 @@
+use blink_core::error::Error;
 use chrono::prelude::*;
 use std::path::Path;
 use std::{env, sync::LazyLock};
@@ -6,7 +7,11 @@ pub static HXMT_1B_DIR: LazyLock<String> = LazyLock::new(|| {
     env::var("HXMT_1B_DIR").unwrap_or_else(|_| "/hxmtfs/data/Archive_tmp/1B".to_string())
 });
 
-pub fn find_filename(type_: &str, time: DateTime<Utc>, serial_num: &str) -> Option<String> {
+pub fn find_filename(
+    type_: &str,
+    time: DateTime<Utc>,
+    serial_num: &str,
+) -> Result<Option<String>, Error> {
     let code = match (type_, serial_num) {
         ("eng", "A") => "0766",
         ("eng", "B") => "1009",
@@ -14,7 +19,11 @@ pub fn find_filename(type_: &str, time: DateTime<Utc>, serial_num: &str) -> Opti
         ("sci", "A") => "0642",
         ("sci", "B") => "0922",
         ("sci", "C") => "1686",
-        _ => panic!("Invalid type or serial number"),
+        _ => {
+            return Err(Error::InvalidArgument(format!(
+                "1B file type {type_} / box {serial_num}"
+            )));
+        }
     };
 
     let mut path = None;
@@ -31,7 +40,7 @@ pub fn find_filename(type_: &str, time: DateTime<Utc>, serial_num: &str) -> Opti
         .join(code);
 
     if !folder_path.exists() {
-        return None;
+        return Ok(None);
     }
 
     let prefix = format!(
@@ -76,26 +85,26 @@ pub fn find_filename(type_: &str, time: DateTime<Utc>, serial_num: &str) -> Opti
         }
     }
 
-    path
+    Ok(path)
 }
 
 /// 获取指定小时的科学数据文件路径（仅返回存在的 box）。
-pub fn get_sci_filenames(time: DateTime<Utc>) -> Vec<(String, String)> {
+pub fn get_sci_filenames(time: DateTime<Utc>) -> Result<Vec<(String, String)>, Error> {
     get_filenames("sci", time)
 }
 
 /// 获取指定小时的工程数据文件路径（仅返回存在的 box）。
-pub fn get_eng_filenames(time: DateTime<Utc>) -> Vec<(String, String)> {
+pub fn get_eng_filenames(time: DateTime<Utc>) -> Result<Vec<(String, String)>, Error> {
     get_filenames("eng", time)
 }
 
-fn get_filenames(type_: &str, time: DateTime<Utc>) -> Vec<(String, String)> {
+fn get_filenames(type_: &str, time: DateTime<Utc>) -> Result<Vec<(String, String)>, Error> {
     let serial_nums = ["A", "B", "C"];
     let mut result = Vec::new();
     for &sn in &serial_nums {
-        if let Some(path) = find_filename(type_, time, sn) {
+        if let Some(path) = find_filename(type_, time, sn)? {
             result.push((sn.to_string(), path));
         }
     }
-    result
+    Ok(result)
 }
@@ -1,4 +1,4 @@
-use blink_core::error::Error;
+use blink_core::{error::Error, fits};
 
 pub struct SciFile {
     pub ccsds: Vec<[u8; 882]>,
@@ -9,8 +9,8 @@ impl SciFile {
         let mut fptr = fitsio::FitsFile::open(filename)?;
 
         // HDU 1: HE_Evt_Src
-        let sci = fptr.hdu("HE_Evt_Src")?;
-        let ccsds_raw: Vec<u8> = sci.read_col(&mut fptr, "CCSDS")?;
+        let sci = fits::hdu(&mut fptr, "HE_Evt_Src")?;
+        let ccsds_raw: Vec<u8> = fits::read_col(&mut fptr, &sci, "CCSDS")?;
         let mut ccsds_array = Vec::with_capacity(ccsds_raw.len() / 882);
         for chunk in ccsds_raw.chunks_exact(882) {
             let mut array = [0; 882];
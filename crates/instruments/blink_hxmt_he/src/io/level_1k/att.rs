@@ -1,7 +1,10 @@
 use crate::io::path::get_path;
 use crate::types::HxmtHe;
-use blink_core::error::Error;
 use blink_core::types::{Attitude, MissionElapsedTime, TemporalState, Trajectory};
+use blink_core::{
+    error::{Error, ResultExt},
+    fits,
+};
 use chrono::prelude::*;
 
 pub struct AttFile {
@@ -27,18 +30,30 @@ impl AttFile {
 
     pub fn from_epoch(epoch: &DateTime<Utc>) -> Result<Self, Error> {
         let path = Self::get_path(epoch)?;
-        Self::new(&path)
+        Self::new(&path).path(&path)
+    }
+
+    /// 拼接下一小时的姿态，跳过与已有数据重叠的部分
+    pub fn append(&mut self, mut other: Self) {
+        let skip = match self.time.last() {
+            Some(&last) => other.time.partition_point(|&t| t <= last),
+            None => 0,
+        };
+        self.time.extend(other.time.drain(skip..));
+        self.q1.extend(other.q1.drain(skip..));
+        self.q2.extend(other.q2.drain(skip..));
+        self.q3.extend(other.q3.drain(skip..));
     }
 
     fn new(filename: &str) -> Result<Self, Error> {
         let mut fptr = fitsio::FitsFile::open(filename)?;
 
         // HDU 3: ATT_Quater
-        let att = fptr.hdu("ATT_Quater")?;
-        let time = att.read_col::<f64>(&mut fptr, "Time")?;
-        let q1 = att.read_col::<f64>(&mut fptr, "Q1")?;
-        let q2 = att.read_col::<f64>(&mut fptr, "Q2")?;
-        let q3 = att.read_col::<f64>(&mut fptr, "Q3")?;
+        let att = fits::hdu(&mut fptr, "ATT_Quater")?;
+        let time = fits::read_col::<f64>(&mut fptr, &att, "Time")?;
+        let q1 = fits::read_col::<f64>(&mut fptr, &att, "Q1")?;
+        let q2 = fits::read_col::<f64>(&mut fptr, &att, "Q2")?;
+        let q3 = fits::read_col::<f64>(&mut fptr, &att, "Q3")?;
 
         Ok(Self { time, q1, q2, q3 })
     }
@@ -54,14 +69,11 @@ impl From<&AttFile> for Trajectory<MissionElapsedTime<HxmtHe>, Attitude> {
             .zip(att_file.q3.iter())
             .map(|(((t, q1), q2), q3)| TemporalState {
                 timestamp: MissionElapsedTime::new(*t),
-                state: Attitude {
-                    q1: *q1,
-                    q2: *q2,
-                    q3: *q3,
-                },
+                // ATT_Quater 只存矢量部，标量部由归一化补全
+                state: Attitude::from_vector_part(*q1, *q2, *q3),
             })
             .collect();
 
-        Trajectory { points }
+        Trajectory::new(points)
     }
 }
@@ -1,6 +1,10 @@
 use super::super::path::get_path;
-use crate::types::{Detector, Event, Scintillator};
-use blink_core::{error::Error, types::MissionElapsedTime};
+use crate::types::{Detector, Event, HxmtHe, Scintillator};
+use blink_core::{
+    error::{Error, ResultExt},
+    fits, time,
+    types::MissionElapsedTime,
+};
 use chrono::prelude::*;
 
 pub struct EventFile {
@@ -29,7 +33,27 @@ impl EventFile {
 
     pub fn from_epoch(epoch: &DateTime<Utc>) -> Result<Self, Error> {
         let path = Self::get_path(epoch)?;
-        Self::new(&path)
+        Self::new(&path, None).path(&path)
+    }
+
+    /// 只读取 MET 落在 `[window[0], window[1])` 内的事例
+    pub fn from_epoch_within(epoch: &DateTime<Utc>, window: [f64; 2]) -> Result<Self, Error> {
+        let path = Self::get_path(epoch)?;
+        Self::new(&path, Some(window)).path(&path)
+    }
+
+    /// 拼接下一小时的事例，跳过与已有数据重叠的部分
+    pub fn append(&mut self, mut other: Self) {
+        let skip = match self.time.last() {
+            Some(&last) => other.time.partition_point(|&t| t <= last),
+            None => 0,
+        };
+        self.time.extend(other.time.drain(skip..));
+        self.det_id.extend(other.det_id.drain(skip..));
+        self.channel.extend(other.channel.drain(skip..));
+        self.pulse_width.extend(other.pulse_width.drain(skip..));
+        self.acd.extend(other.acd.drain(skip..));
+        self.event_type.extend(other.event_type.drain(skip..));
     }
 
     pub fn times(&self) -> &[f64] {
@@ -44,17 +68,28 @@ impl EventFile {
         &self.channel
     }
 
-    fn new(filename: &str) -> Result<Self, Error> {
+    fn new(filename: &str, window: Option<[f64; 2]>) -> Result<Self, Error> {
         let mut fptr = fitsio::FitsFile::open(filename)?;
 
         // HDU 1: Events
-        let events = fptr.hdu("Events")?;
-        let time = events.read_col::<f64>(&mut fptr, "Time")?;
-        let det_id = events.read_col::<u8>(&mut fptr, "Det_ID")?;
-        let channel = events.read_col::<u8>(&mut fptr, "Channel")?;
-        let pulse_width = events.read_col::<u8>(&mut fptr, "Pulse_Width")?;
-
-        let acd_raw = events.read_col::<u32>(&mut fptr, "ACD")?;
+        let events = fits::hdu(&mut fptr, "Events")?;
+        if let Ok(timesys) = events.read_key::<String>(&mut fptr, "TIMESYS") {
+            time::check_timesys::<HxmtHe>(&timesys)?;
+        }
+        let mut time = fits::read_col::<f64>(&mut fptr, &events, "Time")?;
+        // 时间列有序，先定位窗口对应的行，其余列只读这些行
+        let rows = match window {
+            Some([start, stop]) => {
+                time.partition_point(|&t| t < start)..time.partition_point(|&t| t < stop)
+            }
+            None => 0..time.len(),
+        };
+        time = time.drain(rows.clone()).collect();
+        let det_id = fits::read_col_range::<u8>(&mut fptr, &events, "Det_ID", &rows)?;
+        let channel = fits::read_col_range::<u8>(&mut fptr, &events, "Channel", &rows)?;
+        let pulse_width = fits::read_col_range::<u8>(&mut fptr, &events, "Pulse_Width", &rows)?;
+
+        let acd_raw = fits::read_col_range::<u32>(&mut fptr, &events, "ACD", &rows)?;
         let mut acd = Vec::with_capacity(acd_raw.len());
         for &value in &acd_raw {
             let mut array = [false; 18];
@@ -64,8 +99,8 @@ impl EventFile {
             acd.push(array);
         }
 
-        let event_type = events.read_col::<u8>(&mut fptr, "Event_Type")?;
-        // let flag = events.read_col::<u8>(&mut fptr, "FLAG")?;
+        let event_type = fits::read_col_range::<u8>(&mut fptr, &events, "Event_Type", &rows)?;
+        // let flag = fits::read_col::<u8>(&mut fptr, &events, "FLAG")?;
 
         Ok(Self {
             time,
@@ -1,6 +1,7 @@
 use blink_core::{
-    error::Error,
-    types::{MissionElapsedTime, Position, TemporalState, Trajectory},
+    error::{Error, ResultExt},
+    fits,
+    types::{MissionElapsedTime, OrbitState, Position, TemporalState, Trajectory},
 };
 use chrono::prelude::*;
 
@@ -9,6 +10,13 @@ use crate::{io::path::get_path, types::HxmtHe};
 pub struct OrbitFile {
     // HDU 1: Orbit
     time: Vec<f64>,
+    // J2000 位置 (m) 与速度 (m/s)
+    x: Vec<f64>,
+    y: Vec<f64>,
+    z: Vec<f64>,
+    vx: Vec<f64>,
+    vy: Vec<f64>,
+    vz: Vec<f64>,
     lon: Vec<f64>,
     lat: Vec<f64>,
     alt: Vec<f64>,
@@ -29,21 +37,51 @@ impl OrbitFile {
 
     pub fn from_epoch(epoch: &DateTime<Utc>) -> Result<Self, Error> {
         let path = Self::get_path(epoch)?;
-        Self::new(&path)
+        Self::new(&path).path(&path)
+    }
+
+    /// 拼接下一小时的轨道，跳过与已有数据重叠的部分
+    pub fn append(&mut self, mut other: Self) {
+        let skip = match self.time.last() {
+            Some(&last) => other.time.partition_point(|&t| t <= last),
+            None => 0,
+        };
+        self.time.extend(other.time.drain(skip..));
+        self.x.extend(other.x.drain(skip..));
+        self.y.extend(other.y.drain(skip..));
+        self.z.extend(other.z.drain(skip..));
+        self.vx.extend(other.vx.drain(skip..));
+        self.vy.extend(other.vy.drain(skip..));
+        self.vz.extend(other.vz.drain(skip..));
+        self.lon.extend(other.lon.drain(skip..));
+        self.lat.extend(other.lat.drain(skip..));
+        self.alt.extend(other.alt.drain(skip..));
     }
 
     fn new(filename: &str) -> Result<Self, Error> {
         let mut fptr = fitsio::FitsFile::open(filename)?;
 
         // HDU 1: Orbit
-        let orbit = fptr.hdu("Orbit")?;
-        let time = orbit.read_col::<f64>(&mut fptr, "Time")?;
-        let lon = orbit.read_col::<f64>(&mut fptr, "Lon")?;
-        let lat = orbit.read_col::<f64>(&mut fptr, "Lat")?;
-        let alt = orbit.read_col::<f64>(&mut fptr, "Alt")?;
+        let orbit = fits::hdu(&mut fptr, "Orbit")?;
+        let time = fits::read_col::<f64>(&mut fptr, &orbit, "Time")?;
+        let x = fits::read_col::<f64>(&mut fptr, &orbit, "X")?;
+        let y = fits::read_col::<f64>(&mut fptr, &orbit, "Y")?;
+        let z = fits::read_col::<f64>(&mut fptr, &orbit, "Z")?;
+        let vx = fits::read_col::<f64>(&mut fptr, &orbit, "Vx")?;
+        let vy = fits::read_col::<f64>(&mut fptr, &orbit, "Vy")?;
+        let vz = fits::read_col::<f64>(&mut fptr, &orbit, "Vz")?;
+        let lon = fits::read_col::<f64>(&mut fptr, &orbit, "Lon")?;
+        let lat = fits::read_col::<f64>(&mut fptr, &orbit, "Lat")?;
+        let alt = fits::read_col::<f64>(&mut fptr, &orbit, "Alt")?;
 
         Ok(Self {
             time,
+            x,
+            y,
+            z,
+            vx,
+            vy,
+            vz,
             lon,
             lat,
             alt,
@@ -69,6 +107,22 @@ impl From<&OrbitFile> for Trajectory<MissionElapsedTime<HxmtHe>, Position> {
             })
             .collect();
 
-        Trajectory { points }
+        Trajectory::new(points)
+    }
+}
+
+impl From<&OrbitFile> for Trajectory<MissionElapsedTime<HxmtHe>, OrbitState> {
+    fn from(orbit_file: &OrbitFile) -> Self {
+        let points = (0..orbit_file.time.len())
+            .map(|i| TemporalState {
+                timestamp: MissionElapsedTime::new(orbit_file.time[i]),
+                state: OrbitState {
+                    position: [orbit_file.x[i], orbit_file.y[i], orbit_file.z[i]],
+                    velocity: [orbit_file.vx[i], orbit_file.vy[i], orbit_file.vz[i]],
+                },
+            })
+            .collect();
+
+        Trajectory::new(points)
     }
 }
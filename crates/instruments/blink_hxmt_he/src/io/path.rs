@@ -1,4 +1,4 @@
-use blink_core::error::Error;
+use blink_core::error::{Error, ResultExt};
 use chrono::prelude::*;
 use std::{env, path::Path, sync::LazyLock};
 
@@ -7,7 +7,8 @@ static HXMT_1K_DIR: LazyLock<String> = LazyLock::new(|| {
 });
 
 fn get_file(folder: &str, prefix: &str) -> Result<String, Error> {
-    let name = std::fs::read_dir(folder)?
+    let name = std::fs::read_dir(folder)
+        .path(folder)?
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
         .filter(|path| path.is_file())
@@ -31,11 +32,7 @@ fn get_file(folder: &str, prefix: &str) -> Result<String, Error> {
                 prefix
             ))
         })?;
-    Ok(Path::new(&folder)
-        .join(name)
-        .into_os_string()
-        .into_string()
-        .unwrap())
+    Ok(Path::new(&folder).join(name).to_string_lossy().into_owned())
 }
 
 pub fn get_path(epoch: &DateTime<Utc>, file_type: &str) -> Result<String, Error> {
@@ -43,7 +40,9 @@ pub fn get_path(epoch: &DateTime<Utc>, file_type: &str) -> Result<String, Error>
         "Evt" => "HE-Evt",
         "Orbit" => "Orbit",
         "Att" => "Att",
-        _ => panic!("Invalid file type: {}", file_type),
+        _ => {
+            return Err(Error::InvalidArgument(format!("1K file type {file_type}")));
+        }
     };
 
     let num = (*epoch - Utc.with_ymd_and_hms(2017, 6, 15, 0, 0, 0).unwrap()).num_days() + 1;
@@ -1,12 +1,15 @@
 use crate::io::level_1b::{SciFile, get_sci_filenames};
 use crate::io::level_1k::{AttFile, EventFile, OrbitFile};
-use crate::types::{Event, HxmtHe};
+use crate::types::event::{CALIBRATION, ENERGY_THRESHOLD};
+use crate::types::{Detector, Event, HxmtHe, Scintillator};
 use blink_core::error::Error;
+use blink_core::traits::EnergyCalibration;
 use blink_core::types::MissionElapsedTime;
 use chrono::prelude::*;
 
 mod check_saturation;
-mod from_epoch;
+mod from_range;
+mod region;
 mod search;
 
 pub struct Chunk {
@@ -15,25 +18,79 @@ pub struct Chunk {
     pub stime_offsets: Vec<(String, f64)>, // (box_name, offset)
     pub orbit_file: OrbitFile,
     pub att_file: AttFile,
+    /// 搜索范围，只报告起点落在其中的信号
     pub span: [MissionElapsedTime<HxmtHe>; 2],
+    /// 实际载入的事例范围（含两侧余量），用于估计本底
+    pub data_span: [MissionElapsedTime<HxmtHe>; 2],
 }
 
 impl blink_core::traits::Chunk for Chunk {
     type Event = Event;
+    type SearchConfig = blink_algorithms::snapshot_stepping::SearchConfig;
 
-    fn from_epoch(epoch: &chrono::DateTime<chrono::Utc>) -> Result<Self, blink_core::error::Error>
+    fn from_range(
+        start: &DateTime<Utc>,
+        stop: &DateTime<Utc>,
+        margin: chrono::TimeDelta,
+    ) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        from_epoch::from_epoch(epoch)
+        from_range::from_range(start, stop, margin)
     }
 
-    fn search(&self) -> Vec<blink_core::types::Signal<Self::Event>> {
-        search::search(self)
+    fn search_events(
+        &self,
+        config: &Self::SearchConfig,
+        events: Vec<Self::Event>,
+    ) -> Vec<blink_core::types::Signal<Self::Event>> {
+        search::search(self, config, events)
+    }
+
+    fn events(&self) -> Vec<Self::Event> {
+        self.event_file.into_iter().collect()
+    }
+
+    fn live_time(&self) -> uom::si::f64::Time {
+        search::live_time(self)
+    }
+
+    fn search_config() -> Self::SearchConfig {
+        search::search_config()
+    }
+
+    fn detectors() -> usize {
+        18
+    }
+
+    fn synthetic_event(time: MissionElapsedTime<HxmtHe>, energy: f64, detector: usize) -> Event {
+        // 不低于阈值所在道址的下一道，保证通过 keep
+        let lowest = CALIBRATION.channel(detector, time, ENERGY_THRESHOLD).ceil();
+        let channel = CALIBRATION
+            .channel(detector, time, energy)
+            .floor()
+            .max(lowest);
+        let channel = (channel.max(0.0) as u16).clamp(20, 275);
+        // 道址 256 以上按 1K 数据的回绕存成 0–19，与 Event::channel 互逆
+        let channel = if channel >= 256 {
+            channel - 256
+        } else {
+            channel
+        };
+        Event::new(
+            time,
+            channel as u8,
+            Detector {
+                id: detector as u8,
+                scintillator: Scintillator::Csi,
+            },
+            false,
+            [false; 18],
+        )
     }
 
     fn last_modified(epoch: &DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
-        let sci_last_modifieds: Vec<DateTime<Utc>> = get_sci_filenames(*epoch)
+        let sci_last_modifieds: Vec<DateTime<Utc>> = get_sci_filenames(*epoch)?
             .iter()
             .map(|(_, filename)| {
                 let last_modified = std::fs::metadata(filename)?.modified()?;
@@ -61,3 +118,23 @@ impl blink_core::traits::Chunk for Chunk {
         Ok(max_last_modified)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blink_core::traits::{Chunk as _, Event as _};
+
+    #[test]
+    fn synthetic_channels_round_trip_across_the_wrap() {
+        let time = MissionElapsedTime::<HxmtHe>::new(1e8);
+        // 名义增益下道址 c 的中点能量
+        let channel_of =
+            |channel: f64| Chunk::synthetic_event(time, (channel + 0.5) * 11.0, 0).channel();
+        assert_eq!(channel_of(255.0), 255);
+        assert_eq!(channel_of(256.0), 256);
+        assert_eq!(channel_of(275.0), 275);
+        // 超出 1K 道址范围的截到 275，低于阈值的抬到阈值之上
+        assert_eq!(channel_of(300.0), 275);
+        assert_eq!(channel_of(0.0), 38);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    io::{
+        level_1b::{SciFile, get_eng_filenames, get_sci_filenames, read_stime_offset},
+        level_1k::{AttFile, EventFile, OrbitFile},
+    },
+    types::HxmtHe,
+};
+
+use super::Chunk;
+use blink_core::{
+    error::{Category, Error, ResultExt},
+    traits::chunk::{check_range, hours},
+    types::MissionElapsedTime,
+};
+use chrono::{TimeDelta, prelude::*};
+
+pub(super) fn from_range(
+    start: &DateTime<Utc>,
+    stop: &DateTime<Utc>,
+    margin: TimeDelta,
+) -> Result<Chunk, Error> {
+    check_range(start, stop, margin)?;
+    let data_start = *start - margin;
+    let data_stop = *stop + margin;
+    let window = [
+        MissionElapsedTime::<HxmtHe>::from(data_start).met(),
+        MissionElapsedTime::<HxmtHe>::from(data_stop).met(),
+    ];
+
+    let mut files: Option<(EventFile, OrbitFile, AttFile)> = None;
+    let mut loaded: Option<[DateTime<Utc>; 2]> = None;
+    let mut sci_files = Vec::new();
+    let mut stime_offsets = Vec::new();
+
+    for hour in hours(&data_start, &data_stop) {
+        let core = hour < *stop && hour + TimeDelta::hours(1) > *start;
+        let (event_file, orbit_file, att_file) = match load_hour(&hour, window).epoch(hour) {
+            Ok(hour_files) => hour_files,
+            // 余量所在的小时缺数据时只缩小余量
+            Err(e) if !core && e.category() == Category::MissingData => continue,
+            Err(e) => return Err(e),
+        };
+        match &mut files {
+            Some((events, orbits, atts)) => {
+                events.append(event_file);
+                orbits.append(orbit_file);
+                atts.append(att_file);
+            }
+            None => files = Some((event_file, orbit_file, att_file)),
+        }
+        let end = hour + TimeDelta::hours(1);
+        loaded = Some(match loaded {
+            Some([first, _]) => [first, end],
+            None => [hour, end],
+        });
+
+        // 饱和检测只需要搜索范围内的 1B 数据
+        if core {
+            load_sci(&hour, &mut sci_files, &mut stime_offsets).epoch(hour)?;
+        }
+    }
+
+    let (Some((event_file, orbit_file, att_file)), Some([first, last])) = (files, loaded) else {
+        return Err(Error::FileNotFound(format!(
+            "no 1K files for {start} .. {stop}"
+        )));
+    };
+
+    Ok(Chunk {
+        event_file,
+        sci_files,
+        stime_offsets,
+        orbit_file,
+        att_file,
+        span: [
+            MissionElapsedTime::<HxmtHe>::from(*start),
+            MissionElapsedTime::<HxmtHe>::from(*stop),
+        ],
+        data_span: [
+            MissionElapsedTime::<HxmtHe>::from(data_start.max(first)),
+            MissionElapsedTime::<HxmtHe>::from(data_stop.min(last)),
+        ],
+    })
+}
+
+fn load_hour(
+    epoch: &DateTime<Utc>,
+    window: [f64; 2],
+) -> Result<(EventFile, OrbitFile, AttFile), Error> {
+    Ok((
+        EventFile::from_epoch_within(epoch, window)?,
+        OrbitFile::from_epoch(epoch)?,
+        AttFile::from_epoch(epoch)?,
+    ))
+}
+
+fn load_sci(
+    epoch: &DateTime<Utc>,
+    sci_files: &mut Vec<(String, SciFile)>,
+    stime_offsets: &mut Vec<(String, f64)>,
+) -> Result<(), Error> {
+    let sci_pairs = get_sci_filenames(*epoch)?;
+    let eng_pairs = get_eng_filenames(*epoch)?;
+
+    for (box_name, sci_path) in &sci_pairs {
+        let sci = SciFile::new(sci_path).path(sci_path)?;
+        // 找对应的 eng 文件
+        let offset = eng_pairs
+            .iter()
+            .find(|(bn, _)| bn == box_name)
+            .and_then(|(_, eng_path)| read_stime_offset(eng_path).ok())
+            .unwrap_or(0.0);
+        sci_files.push((box_name.clone(), sci));
+        stime_offsets.push((box_name.clone(), offset));
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use super::Chunk;
+use crate::types::HxmtHe;
+use blink_algorithms::background::ReferenceRate;
+use blink_core::traits::Event as _;
+use blink_core::types::{MissionElapsedTime, Position, Region, Trajectory};
+use blink_region::{GeomagneticMask, MaskSet, PolygonMask, RateMap, masked_intervals};
+use std::{
+    env,
+    sync::{Arc, LazyLock},
+};
+use uom::si::f64::*;
+
+type Interval = (MissionElapsedTime<HxmtHe>, MissionElapsedTime<HxmtHe>);
+
+/// 550 km 高度上 SAA 的近似边界（经度, 纬度），偏保守；需要更细的边界时用计数率图。
+const SAA_POLYGON: [(f64, f64); 10] = [
+    (-90.0, -30.0),
+    (-78.0, -10.0),
+    (-55.0, 0.0),
+    (-25.0, 0.0),
+    (5.0, -5.0),
+    (30.0, -18.0),
+    (35.0, -35.0),
+    (10.0, -48.0),
+    (-40.0, -50.0),
+    (-80.0, -45.0),
+];
+
+/// 由 `blink ratemap` 生成的计数率图；设置后额外排除高本底格点。
+pub(super) static RATE_MAP: LazyLock<Option<RateMap>> = LazyLock::new(|| {
+    let path = env::var("HXMT_RATE_MAP").ok()?;
+    RateMap::load(&path)
+        .inspect_err(|e| eprintln!("ignoring HXMT_RATE_MAP: {e}"))
+        .ok()
+});
+
+static REGION_MASKS: LazyLock<MaskSet> = LazyLock::new(|| {
+    // SAA 排除；外辐射带只标记（HXMT 倾角 43°，高纬段会扫到 L ≈ 3–4）
+    let masks = MaskSet::new()
+        .exclude(PolygonMask::new(Region::Saa, &SAA_POLYGON))
+        .tag(GeomagneticMask::apex_radius(
+            Region::RadiationBelt,
+            3.0,
+            8.0,
+        ));
+    match RATE_MAP.as_ref() {
+        Some(map) => masks.exclude(map.to_mask(Region::HighRate, 5.0, 60.0)),
+        None => masks,
+    }
+});
+
+impl Chunk {
+    pub fn region_masks() -> &'static MaskSet {
+        &REGION_MASKS
+    }
+
+    /// 计算被排除掩膜覆盖的时间段（每 10 s 采样一次轨道，两侧各扩 10 s）。
+    pub fn get_region_intervals(&self) -> Vec<Interval> {
+        masked_intervals(
+            &Trajectory::<MissionElapsedTime<HxmtHe>, Position>::from(&self.orbit_file),
+            |time| time.to_utc(),
+            Self::region_masks(),
+            10,
+            Time::new::<uom::si::time::second>(10.0),
+        )
+    }
+
+    /// 把本小时保留事例的计数累加到计数率图。
+    pub fn accumulate_rate_map(&self, map: &mut RateMap) {
+        let times = self
+            .event_file
+            .into_iter()
+            .filter(|event| event.keep())
+            .map(|event| event.time())
+            .collect::<Vec<_>>();
+        map.accumulate_trajectory(
+            &Trajectory::<MissionElapsedTime<HxmtHe>, Position>::from(&self.orbit_file),
+            &times,
+            Time::new::<uom::si::time::second>(10.0),
+        );
+    }
+
+    /// 计数率图上本小时轨道所经格点的计数率，作为轨道相位本底的参考；
+    /// 未设置计数率图时为 `None`。
+    pub fn reference_rate(&self) -> Option<ReferenceRate> {
+        let map = RATE_MAP.as_ref()?;
+        let orbit = Trajectory::<MissionElapsedTime<HxmtHe>, Position>::from(&self.orbit_file);
+        Some(Arc::new(move |time| {
+            map.rate_along(&orbit, MissionElapsedTime::from(time))
+        }))
+    }
+}
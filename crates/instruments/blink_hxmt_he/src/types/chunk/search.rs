@@ -1,70 +1,139 @@
 use super::Chunk;
+use super::region::RATE_MAP;
+use crate::algorithms::classification::classify;
+use crate::types::event::{ENERGY_MAX, ENERGY_SPLIT, ENERGY_THRESHOLD};
 use crate::types::{Event, HxmtHe};
-use blink_algorithms::snapshot_stepping::{SearchConfig, search_new};
-use blink_core::traits::Event as _;
-use blink_core::types::{Attitude, MissionElapsedTime, Position, Signal, Trajectory};
+use blink_algorithms::background::{Background, BackgroundKind};
+use blink_algorithms::bayesian_blocks::DEFAULT_P0;
+use blink_algorithms::characterisation::CharacterisationConfig;
+use blink_algorithms::far;
+use blink_algorithms::signal::{Ephemeris, SignalContext, signals};
+use blink_algorithms::snapshot_stepping::{self, SearchConfig, SearchEnv};
+use blink_core::traits::{Event as _, Instrument as _};
+use blink_core::types::Signal;
+use blink_region::is_in_intervals;
+use std::sync::LazyLock;
 use uom::si::f64::*;
 
-pub fn search(chunk: &Chunk) -> Vec<Signal<Event>> {
-    let events = chunk
-        .event_file
-        .into_iter()
+/// Combination, background and FAR calibration from the `HXMT_*`
+/// variables.
+static SEARCH_ENV: LazyLock<SearchEnv> =
+    LazyLock::new(|| SearchEnv::from_env("HXMT", RATE_MAP.is_some()));
+
+pub fn search(chunk: &Chunk, config: &SearchConfig, all_events: Vec<Event>) -> Vec<Signal<Event>> {
+    // 未筛选的事例只用于刻画信号
+    let events = all_events
+        .iter()
         .filter(|event| event.keep())
+        .cloned()
         .collect::<Vec<_>>();
 
-    let results = search_new(
+    let mut config = config.clone();
+    if SEARCH_ENV.background == BackgroundKind::OrbitPhase
+        && let Some(rate) = chunk.reference_rate()
+    {
+        config.background = Background::OrbitPhase(rate);
+    }
+
+    let results = snapshot_stepping::search(
         &events,
-        1,
-        chunk.span[0],
-        chunk.span[1],
-        SearchConfig {
-            min_duration: Time::new::<uom::si::time::microsecond>(0.0),
-            max_duration: Time::new::<uom::si::time::millisecond>(1.0),
-            neighbor: Time::new::<uom::si::time::second>(1.0),
-            hollow: Time::new::<uom::si::time::millisecond>(10.0),
-            false_positive_per_year: 20.0,
-            min_number: 8,
-        },
+        HxmtHe::group_names().len(),
+        chunk.data_span[0],
+        chunk.data_span[1],
+        config.clone(),
     );
 
     // 饱和排除：用 1B FIFO reset 检测得到的（已扩 ±1s 并求并的）饱和区间，
     // 剔除落在其中的候选。旧的 continuous() 成簇判据已移除——成簇本身不再
     // 作为饱和信号，留作可能的真实发现。
     let saturation_intervals = chunk.get_saturation_intervals();
+    // SAA 等排除掩膜覆盖的时间段同样剔除
+    let region_intervals = chunk.get_region_intervals();
 
     let results = results
         .into_iter()
+        // 余量内的候选归相邻 chunk 所有，这里只用余量估计本底
+        .filter(|candidate| chunk.span[0] <= candidate.start && candidate.start < chunk.span[1])
         .filter(|candidate| {
             let idx = saturation_intervals.partition_point(|iv| iv.1 < candidate.start);
             // 不在任何饱和区间内才保留
             !(idx < saturation_intervals.len() && saturation_intervals[idx].0 <= candidate.start)
         })
+        .filter(|candidate| !is_in_intervals(&region_intervals, candidate.start))
         .collect::<Vec<_>>();
 
-    results
+    let characterisation = CharacterisationConfig {
+        // 起点与 keep() 的能量阈值一致
+        soft: [ENERGY_THRESHOLD, ENERGY_SPLIT],
+        hard: [ENERGY_SPLIT, ENERGY_MAX],
+        detectors: 18,
+        margin: Time::new::<uom::si::time::millisecond>(1.0),
+        peak_bin: Time::new::<uom::si::time::microsecond>(20.0),
+        bootstrap: 200,
+        p0: DEFAULT_P0,
+    };
+    let ephemeris = Ephemeris::new(
+        (&chunk.att_file).into(),
+        (&chunk.orbit_file).into(),
+        (&chunk.orbit_file).into(),
+    );
+    let context = SignalContext {
+        events: &events,
+        all_events: &all_events,
+        // 无死时间记录，按探测器由全部事例（含未保留的）的间隔分布估计（非瘫痪型）
+        unit: |event: &Event| event.detector.id as usize,
+        characterisation: &characterisation,
+        ephemeris: &ephemeris,
+        config: &config,
+    };
+    signals(
+        results,
+        &context,
+        |state| Chunk::region_masks().region(state),
+        classify,
+    )
+}
+
+/// The search range without saturation, excluded regions and gaps of more
+/// than a second in the kept events.
+pub fn live_time(chunk: &Chunk) -> Time {
+    let mut excluded = chunk.get_saturation_intervals();
+    excluded.extend(chunk.get_region_intervals());
+    let times = chunk
+        .event_file
         .into_iter()
-        .filter_map(|candidate| {
-            let peak = candidate.start + candidate.bin_size_best / 2.0;
-            let attitude =
-                Trajectory::<MissionElapsedTime<HxmtHe>, Attitude>::from(&chunk.att_file)
-                    .interpolate(peak)?;
-            let position =
-                Trajectory::<MissionElapsedTime<HxmtHe>, Position>::from(&chunk.orbit_file)
-                    .interpolate(peak)?;
-            Some(Signal {
-                start: candidate.start,
-                stop: candidate.stop,
-                bin_size_min: candidate.bin_size_min,
-                bin_size_max: candidate.bin_size_max,
-                bin_size_best: candidate.bin_size_best,
-                delay: candidate.delay,
-                count: candidate.count,
-                mean: candidate.mean,
-                sf: candidate.sf(),
-                false_positive_per_year: candidate.false_positive_per_year(),
-                attitude: attitude.state,
-                position: position.state,
-            })
-        })
-        .collect::<Vec<_>>()
+        .filter(|event| event.keep())
+        .map(|event| event.time())
+        .collect::<Vec<_>>();
+    far::live_time(
+        chunk.span,
+        &excluded,
+        &times,
+        Time::new::<uom::si::time::second>(1.0),
+    )
+}
+
+pub fn search_config() -> SearchConfig {
+    SEARCH_ENV.search_config()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blink_algorithms::threshold::ThresholdTable;
+
+    #[test]
+    fn threshold_table_rejects_with_the_production_config() {
+        let config = search_config();
+        let table = ThresholdTable::shared(
+            config.false_positive_per_year,
+            config.min_duration,
+            config.max_duration,
+        );
+        let microseconds = |us: f64| Time::new::<uom::si::time::microsecond>(us);
+        // 本底 0.3 的 10 µs 区间内 2 个事例远低于阈值；零宽区间归入第一行
+        assert!(table.rejects(2, 0.3, microseconds(10.0)));
+        assert!(table.rejects(2, 0.3, microseconds(0.0)));
+        assert!(!table.rejects(40, 0.3, microseconds(10.0)));
+    }
 }
@@ -13,3 +13,10 @@ pub struct Detector {
     pub id: u8,
     pub scintillator: Scintillator,
 }
+
+impl Detector {
+    /// 机箱序号：探测器 0–5 属 A，6–11 属 B，12–17 属 C
+    pub fn box_index(&self) -> u8 {
+        self.id / 6
+    }
+}
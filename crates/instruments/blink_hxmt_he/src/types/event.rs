@@ -1,14 +1,44 @@
 use crate::types::Detector;
 use crate::types::HxmtHe;
 use crate::types::Scintillator;
+use blink_core::traits::EnergyCalibration;
 use blink_core::traits::Event as EventTrait;
+use blink_core::types::GainHistory;
 use blink_core::types::MissionElapsedTime;
 use serde::Serialize;
+use std::env;
+use std::sync::LazyLock;
+
+/// Approximate keV per channel of the CsI 1K channels, used when no
+/// calibration is given
+const NOMINAL_GAIN: f64 = 11.0;
+/// Lower edge in keV of the events searched, channel 38 at the nominal gain
+pub const ENERGY_THRESHOLD: f64 = 38.0 * NOMINAL_GAIN;
+/// Boundary in keV between the soft and hard bands, channel 100 at the
+/// nominal gain
+pub const ENERGY_SPLIT: f64 = 100.0 * NOMINAL_GAIN;
+/// Upper edge in keV of the 1K channels at the nominal gain
+pub const ENERGY_MAX: f64 = 276.0 * NOMINAL_GAIN;
+
+/// Channel to energy relation of each detector; `HXMT_ENERGY_CALIBRATION`
+/// names a [`GainHistory`] JSON file, otherwise the nominal gain holds.
+pub(crate) static CALIBRATION: LazyLock<GainHistory> = LazyLock::new(|| {
+    env::var("HXMT_ENERGY_CALIBRATION")
+        .ok()
+        .and_then(|path| {
+            GainHistory::load(&path)
+                .inspect_err(|e| eprintln!("ignoring HXMT_ENERGY_CALIBRATION: {e}"))
+                .ok()
+        })
+        .unwrap_or_else(|| GainHistory::uniform(0.0, NOMINAL_GAIN))
+});
 
 #[derive(Serialize, Debug, Clone)]
 pub struct Event {
     time: MissionElapsedTime<HxmtHe>,
     channel: u8,
+    /// keV, from [`CALIBRATION`]
+    energy: f32,
     pub detector: Detector,
     pub is_am241: bool,
     pub acds: [bool; 18],
@@ -31,15 +61,25 @@ impl EventTrait for Event {
         }
     }
 
+    fn energy(&self) -> f64 {
+        self.energy as f64
+    }
+
     fn group(&self) -> u8 {
-        0
+        self.detector.box_index()
     }
 
     fn keep(&self) -> bool {
-        const CHANNEL_THRESHOLD: u16 = 38;
         self.detector.scintillator == Scintillator::Csi
             && !self.is_am241
-            && self.channel() >= CHANNEL_THRESHOLD
+            && self.energy() >= ENERGY_THRESHOLD
+    }
+
+    fn with_time(&self, time: MissionElapsedTime<Self::Instrument>) -> Self {
+        Self {
+            time,
+            ..self.clone()
+        }
     }
 }
 
@@ -51,12 +91,20 @@ impl Event {
         is_am241: bool,
         acds: [bool; 18],
     ) -> Self {
-        Self {
+        let mut event = Self {
             time,
             channel,
+            energy: 0.0,
             detector,
             is_am241,
             acds,
-        }
+        };
+        // 道址 c 覆盖 [c, c + 1)，取其中点
+        event.energy = CALIBRATION.energy(
+            event.detector.id as usize,
+            time,
+            event.channel() as f64 + 0.5,
+        ) as f32;
+        event
     }
 }
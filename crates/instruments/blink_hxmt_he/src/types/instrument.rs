@@ -23,4 +23,27 @@ impl Instrument for HxmtHe {
     fn name() -> &'static str {
         "Insight-HXMT/HE"
     }
+
+    fn boresight() -> [f64; 3] {
+        [1.0, 0.0, 0.0]
+    }
+
+    fn detector_axes() -> Vec<[f64; 3]> {
+        // 18 个准直探测器同轴，均沿 +X
+        vec![[1.0, 0.0, 0.0]; 18]
+    }
+
+    fn group_names() -> &'static [&'static str] {
+        // 三个机箱各 6 个探测器
+        &["A", "B", "C"]
+    }
+
+    fn detector_area() -> f64 {
+        // 18 个直径 190 mm 的 NaI/CsI 复合晶体
+        18.0 * std::f64::consts::PI * 9.5 * 9.5
+    }
+
+    fn met_counts_leap_seconds() -> bool {
+        true
+    }
 }
@@ -5,6 +5,8 @@ No.    Name      Ver    Type      Cards   Dimensions   Format
   1  Quaternion    1 BinTableHDU     72   2069R x 12C   [1D, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1B, 1J, 1B, 1B]
 */
 
+use blink_core::error::Error;
+use blink_core::fits;
 use blink_core::types::{Attitude, MissionElapsedTime, TemporalState, Trajectory};
 
 use crate::types::SvomGrm;
@@ -14,13 +16,18 @@ pub struct AttFile {
 }
 
 impl AttFile {
-    pub fn from_fits_file(path: &str) -> Result<Self, fitsio::errors::Error> {
+    pub fn from_fits_file(path: &str) -> Result<Self, Error> {
         let mut fptr = fitsio::FitsFile::open(path)?;
 
         let quaternion = QuaternionHdu::from_fptr(&mut fptr)?;
 
         Ok(Self { quaternion })
     }
+
+    /// Appends the following hour, skipping rows already present.
+    pub fn append(&mut self, other: Self) {
+        self.quaternion.append(other.quaternion);
+    }
 }
 
 struct QuaternionHdu {
@@ -28,7 +35,7 @@ struct QuaternionHdu {
     q0: Vec<f32>,
     q1: Vec<f32>,
     q2: Vec<f32>,
-    // q3: Vec<f32>,
+    q3: Vec<f32>,
     // wx: Vec<f32>,
     // wy: Vec<f32>,
     // wz: Vec<f32>,
@@ -39,28 +46,28 @@ struct QuaternionHdu {
 }
 
 impl QuaternionHdu {
-    fn from_fptr(fptr: &mut fitsio::FitsFile) -> Result<Self, fitsio::errors::Error> {
-        let quaternion = fptr.hdu("Quaternion")?;
-
-        let time = quaternion.read_col::<f64>(fptr, "TIME")?;
-        let q0 = quaternion.read_col::<f32>(fptr, "Q0")?;
-        let q1 = quaternion.read_col::<f32>(fptr, "Q1")?;
-        let q2 = quaternion.read_col::<f32>(fptr, "Q2")?;
-        // let q3 = quaternion.read_col::<f32>(fptr, "Q3")?;
-        // let wx = quaternion.read_col::<f32>(fptr, "wx")?;
-        // let wy = quaternion.read_col::<f32>(fptr, "wy")?;
-        // let wz = quaternion.read_col::<f32>(fptr, "wz")?;
-        // let slew_stat = quaternion.read_col::<u8>(fptr, "slew_stat")?;
-        // let target_id = quaternion.read_col::<i32>(fptr, "TargetID")?;
-        // let quality = quaternion.read_col::<u8>(fptr, "Quality")?;
-        // let att_ref = quaternion.read_col::<u8>(fptr, "AttRef")?;
+    fn from_fptr(fptr: &mut fitsio::FitsFile) -> Result<Self, Error> {
+        let quaternion = fits::hdu(fptr, "Quaternion")?;
+
+        let time = fits::read_col::<f64>(fptr, &quaternion, "TIME")?;
+        let q0 = fits::read_col::<f32>(fptr, &quaternion, "Q0")?;
+        let q1 = fits::read_col::<f32>(fptr, &quaternion, "Q1")?;
+        let q2 = fits::read_col::<f32>(fptr, &quaternion, "Q2")?;
+        let q3 = fits::read_col::<f32>(fptr, &quaternion, "Q3")?;
+        // let wx = fits::read_col::<f32>(fptr, &quaternion, "wx")?;
+        // let wy = fits::read_col::<f32>(fptr, &quaternion, "wy")?;
+        // let wz = fits::read_col::<f32>(fptr, &quaternion, "wz")?;
+        // let slew_stat = fits::read_col::<u8>(fptr, &quaternion, "slew_stat")?;
+        // let target_id = fits::read_col::<i32>(fptr, &quaternion, "TargetID")?;
+        // let quality = fits::read_col::<u8>(fptr, &quaternion, "Quality")?;
+        // let att_ref = fits::read_col::<u8>(fptr, &quaternion, "AttRef")?;
 
         Ok(Self {
             time,
             q0,
             q1,
             q2,
-            // q3,
+            q3,
             // wx,
             // wy,
             // wz,
@@ -72,6 +79,20 @@ impl QuaternionHdu {
     }
 }
 
+impl QuaternionHdu {
+    fn append(&mut self, mut other: Self) {
+        let skip = match self.time.last() {
+            Some(&last) => other.time.partition_point(|&t| t <= last),
+            None => 0,
+        };
+        self.time.extend(other.time.drain(skip..));
+        self.q0.extend(other.q0.drain(skip..));
+        self.q1.extend(other.q1.drain(skip..));
+        self.q2.extend(other.q2.drain(skip..));
+        self.q3.extend(other.q3.drain(skip..));
+    }
+}
+
 impl From<&AttFile> for Trajectory<MissionElapsedTime<SvomGrm>, Attitude> {
     fn from(att_file: &AttFile) -> Self {
         let points = att_file
@@ -81,16 +102,38 @@ impl From<&AttFile> for Trajectory<MissionElapsedTime<SvomGrm>, Attitude> {
             .zip(att_file.quaternion.q0.iter())
             .zip(att_file.quaternion.q1.iter())
             .zip(att_file.quaternion.q2.iter())
-            .map(|(((t, q0), q1), q2)| TemporalState {
+            .zip(att_file.quaternion.q3.iter())
+            .map(|((((t, q0), q1), q2), q3)| TemporalState {
                 timestamp: MissionElapsedTime::new(*t),
-                state: Attitude {
-                    q1: *q0 as f64,
-                    q2: *q1 as f64,
-                    q3: *q2 as f64,
-                },
+                // Q0–Q2 为矢量部，Q3 为标量部
+                state: Attitude::new(*q3 as f64, *q0 as f64, *q1 as f64, *q2 as f64),
             })
             .collect();
 
-        Trajectory { points }
+        Trajectory::new(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quaternion_columns_are_scalar_last() {
+        // 绕 z 轴偏航 90°：Q0..Q2 = (0, 0, sin 45°)，Q3 = cos 45°
+        let half = std::f32::consts::FRAC_PI_4;
+        let att_file = AttFile {
+            quaternion: QuaternionHdu {
+                time: vec![1.0, 2.0],
+                q0: vec![0.0; 2],
+                q1: vec![0.0; 2],
+                q2: vec![half.sin(); 2],
+                q3: vec![half.cos(); 2],
+            },
+        };
+        let trajectory = Trajectory::<MissionElapsedTime<SvomGrm>, Attitude>::from(&att_file);
+        let attitude = &trajectory.points[0].state;
+        let [x, y, z] = attitude.to_j2000([1.0, 0.0, 0.0]);
+        assert!(x.abs() < 1e-6 && (y - 1.0).abs() < 1e-6 && z.abs() < 1e-6);
     }
 }
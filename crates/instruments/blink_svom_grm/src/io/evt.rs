@@ -9,44 +9,72 @@ No.    Name      Ver    Type      Cards   Dimensions   Format
   5  EVENTS03      1 BinTableHDU     68   1270337R x 7C   [D, I, B, E, B, B, B]
 */
 
+use blink_core::{error::Error, fits};
 use std::{cmp::Reverse, collections::BinaryHeap};
 
 mod ebounds_hdu;
 mod events_hdu;
 mod gti_hdu;
 
-// use ebounds_hdu::EboundsHdu;
+use ebounds_hdu::EboundsHdu;
 use events_hdu::EventsHdu;
 // use gti_hdu::GtiHdu;
 
 use crate::{io::evt::events_hdu::EventsHduIterator, types::Event};
 
 pub struct EvtFile {
-    // ebounds: EboundsHdu,
     // gti: GtiHdu,
     events01: EventsHdu,
     events02: EventsHdu,
     events03: EventsHdu,
+    timesys: Option<String>,
 }
 
 impl EvtFile {
-    pub fn from_fits_file(path: &str) -> Result<Self, fitsio::errors::Error> {
+    pub fn from_fits_file(path: &str) -> Result<Self, Error> {
+        Self::read(path, None)
+    }
+
+    /// Reads only the events with `TIME` in `[window[0], window[1])`.
+    pub fn from_fits_file_within(path: &str, window: [f64; 2]) -> Result<Self, Error> {
+        Self::read(path, Some(window))
+    }
+
+    fn read(path: &str, window: Option<[f64; 2]>) -> Result<Self, Error> {
         let mut fptr = fitsio::FitsFile::open(path)?;
 
-        // let ebounds = EboundsHdu::from_fptr(&mut fptr)?;
+        // PI 道址的能量边界，三个探测器共用
+        let ebounds = EboundsHdu::from_fptr(&mut fptr)?.ebounds();
         // let gti = GtiHdu::from_fptr(&mut fptr)?;
-        let events01 = EventsHdu::from_fptr(&mut fptr, 1)?;
-        let events02 = EventsHdu::from_fptr(&mut fptr, 2)?;
-        let events03 = EventsHdu::from_fptr(&mut fptr, 3)?;
+        let events01 = EventsHdu::from_fptr(&mut fptr, 1, window, &ebounds)?;
+        let events02 = EventsHdu::from_fptr(&mut fptr, 2, window, &ebounds)?;
+        let events03 = EventsHdu::from_fptr(&mut fptr, 3, window, &ebounds)?;
+        let timesys = fits::hdu(&mut fptr, "EVENTS01")?
+            .read_key::<String>(&mut fptr, "TIMESYS")
+            .ok();
 
         Ok(Self {
-            // ebounds,
             // gti,
             events01,
             events02,
             events03,
+            timesys,
         })
     }
+
+    /// Appends the following hour, skipping events already present. Event
+    /// energies are converted with each file's own `EBOUNDS` when read, so
+    /// a calibration change between hours carries over.
+    pub fn append(&mut self, other: Self) {
+        self.events01.append(other.events01);
+        self.events02.append(other.events02);
+        self.events03.append(other.events03);
+    }
+
+    /// `TIMESYS` header keyword of the event extensions, if present.
+    pub fn timesys(&self) -> Option<&str> {
+        self.timesys.as_deref()
+    }
 }
 
 impl<'a> IntoIterator for &'a EvtFile {
@@ -55,9 +83,9 @@ impl<'a> IntoIterator for &'a EvtFile {
 
     fn into_iter(self) -> Self::IntoIter {
         let mut file_iters = [
-            self.events01.into_iter(),
-            self.events02.into_iter(),
-            self.events03.into_iter(),
+            self.events01.iter(),
+            self.events02.iter(),
+            self.events03.iter(),
         ];
         let mut buffer = BinaryHeap::new();
         for (index, file_iter) in file_iters.iter_mut().enumerate() {
@@ -1,21 +1,43 @@
-// pub(super) struct EboundsHdu {
-//     channel: Vec<i16>,
-//     e_min: Vec<f32>,
-//     e_max: Vec<f32>,
-// }
+use blink_core::{error::Error, fits, types::Ebounds};
 
-// impl EboundsHdu {
-//     pub fn from_fptr(fptr: &mut fitsio::FitsFile) -> Result<Self, fitsio::errors::Error> {
-//         let ebounds = fptr.hdu("EBOUNDS")?;
+pub(super) struct EboundsHdu {
+    channel: Vec<i16>,
+    e_min: Vec<f32>,
+    e_max: Vec<f32>,
+}
 
-//         let channel = ebounds.read_col::<i16>(fptr, "CHANNEL")?;
-//         let e_min = ebounds.read_col::<f32>(fptr, "E_MIN")?;
-//         let e_max = ebounds.read_col::<f32>(fptr, "E_MAX")?;
+impl EboundsHdu {
+    pub fn from_fptr(fptr: &mut fitsio::FitsFile) -> Result<Self, Error> {
+        let ebounds = fits::hdu(fptr, "EBOUNDS")?;
 
-//         Ok(Self {
-//             channel,
-//             e_min,
-//             e_max,
-//         })
-//     }
-// }
+        let channel = fits::read_col::<i16>(fptr, &ebounds, "CHANNEL")?;
+        let e_min = fits::read_col::<f32>(fptr, &ebounds, "E_MIN")?;
+        let e_max = fits::read_col::<f32>(fptr, &ebounds, "E_MAX")?;
+
+        Ok(Self {
+            channel,
+            e_min,
+            e_max,
+        })
+    }
+
+    /// Bounds indexed by `CHANNEL`; channels missing from the table get
+    /// empty bounds at the energy of the previous one.
+    pub fn ebounds(&self) -> Ebounds {
+        let length = self.channel.iter().map(|&c| c.max(0) as usize + 1).max();
+        let mut ebounds = vec![[f64::NAN; 2]; length.unwrap_or(0)];
+        for ((&channel, &e_min), &e_max) in self.channel.iter().zip(&self.e_min).zip(&self.e_max) {
+            if channel >= 0 {
+                ebounds[channel as usize] = [e_min as f64, e_max as f64];
+            }
+        }
+        let mut previous = 0.0;
+        for bounds in &mut ebounds {
+            if bounds[0].is_nan() {
+                *bounds = [previous; 2];
+            }
+            previous = bounds[1];
+        }
+        ebounds
+    }
+}
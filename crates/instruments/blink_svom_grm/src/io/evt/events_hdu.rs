@@ -1,11 +1,18 @@
-use blink_core::types::MissionElapsedTime;
+use blink_core::{
+    error::Error,
+    fits,
+    traits::EnergyCalibration,
+    types::{Ebounds, MissionElapsedTime},
+};
 
-use crate::types::Event;
+use crate::types::{Event, SvomGrm};
 
 pub(super) struct EventsHdu {
     id: u8,
     time: Vec<f64>,
     pi: Vec<i16>,
+    /// keV, from the `EBOUNDS` of the file the events were read from
+    energy: Vec<f32>,
     gain_type: Vec<u8>,
     dead_time: Vec<f32>,
     evt_type: Vec<u8>,
@@ -14,21 +21,45 @@ pub(super) struct EventsHdu {
 }
 
 impl EventsHdu {
-    pub fn from_fptr(fptr: &mut fitsio::FitsFile, id: u8) -> Result<Self, fitsio::errors::Error> {
-        let events = fptr.hdu(format!("EVENTS0{}", id).as_str())?;
+    pub fn from_fptr(
+        fptr: &mut fitsio::FitsFile,
+        id: u8,
+        window: Option<[f64; 2]>,
+        ebounds: &Ebounds,
+    ) -> Result<Self, Error> {
+        let events = fits::hdu(fptr, &format!("EVENTS0{}", id))?;
 
-        let time = events.read_col::<f64>(fptr, "TIME")?;
-        let pi = events.read_col::<i16>(fptr, "PI")?;
-        let gain_type = events.read_col::<u8>(fptr, "GAIN_TYPE")?;
-        let dead_time = events.read_col::<f32>(fptr, "DEAD_TIME")?;
-        let evt_type = events.read_col::<u8>(fptr, "EVT_TYPE")?;
-        let anti_coin = events.read_col::<u8>(fptr, "ANTI_COIN")?;
-        let flag = events.read_col::<u8>(fptr, "FLAG")?;
+        let mut time = fits::read_col::<f64>(fptr, &events, "TIME")?;
+        // TIME 列有序，先定位窗口对应的行，其余列只读这些行
+        let rows = match window {
+            Some([start, stop]) => {
+                time.partition_point(|&t| t < start)..time.partition_point(|&t| t < stop)
+            }
+            None => 0..time.len(),
+        };
+        time = time.drain(rows.clone()).collect();
+        let pi = fits::read_col_range::<i16>(fptr, &events, "PI", &rows)?;
+        let gain_type = fits::read_col_range::<u8>(fptr, &events, "GAIN_TYPE", &rows)?;
+        let dead_time = fits::read_col_range::<f32>(fptr, &events, "DEAD_TIME", &rows)?;
+        let evt_type = fits::read_col_range::<u8>(fptr, &events, "EVT_TYPE", &rows)?;
+        let anti_coin = fits::read_col_range::<u8>(fptr, &events, "ANTI_COIN", &rows)?;
+        let flag = fits::read_col_range::<u8>(fptr, &events, "FLAG", &rows)?;
+        // 能量在读入时按本文件的 EBOUNDS 换算，拼接不同小时后仍各用各的
+        let energy = time
+            .iter()
+            .zip(&pi)
+            .map(|(&t, &channel)| {
+                // 道址 c 覆盖 [c, c + 1)，取其中点
+                let time = MissionElapsedTime::<SvomGrm>::new(t);
+                ebounds.energy(0, time, channel as f64 + 0.5) as f32
+            })
+            .collect();
 
         Ok(Self {
             id,
             time,
             pi,
+            energy,
             gain_type,
             dead_time,
             evt_type,
@@ -38,11 +69,27 @@ impl EventsHdu {
     }
 }
 
-impl<'a> IntoIterator for &'a EventsHdu {
-    type Item = Event;
-    type IntoIter = EventsHduIterator<'a>;
+impl EventsHdu {
+    /// 拼接下一小时的事例，跳过与已有数据重叠的部分
+    pub fn append(&mut self, mut other: Self) {
+        let skip = match self.time.last() {
+            Some(&last) => other.time.partition_point(|&t| t <= last),
+            None => 0,
+        };
+        self.time.extend(other.time.drain(skip..));
+        self.pi.extend(other.pi.drain(skip..));
+        self.energy.extend(other.energy.drain(skip..));
+        self.gain_type.extend(other.gain_type.drain(skip..));
+        self.dead_time.extend(other.dead_time.drain(skip..));
+        self.evt_type.extend(other.evt_type.drain(skip..));
+        self.anti_coin.extend(other.anti_coin.drain(skip..));
+        self.flag.extend(other.flag.drain(skip..));
+    }
+}
 
-    fn into_iter(self) -> Self::IntoIter {
+impl EventsHdu {
+    /// Events in time order.
+    pub fn iter(&self) -> EventsHduIterator<'_> {
         EventsHduIterator {
             hdu: self,
             index: 0,
@@ -60,9 +107,11 @@ impl Iterator for EventsHduIterator<'_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index < self.hdu.time.len() {
+            let time = MissionElapsedTime::<SvomGrm>::new(self.hdu.time[self.index]);
             let event = Event {
-                time: MissionElapsedTime::new(self.hdu.time[self.index]),
+                time,
                 channel: self.hdu.pi[self.index],
+                energy: self.hdu.energy[self.index],
                 detector_id: self.hdu.id,
                 gain_type: self.hdu.gain_type[self.index],
                 dead_time: self.hdu.dead_time[self.index],
@@ -5,7 +5,9 @@ No.    Name      Ver    Type      Cards   Dimensions   Format
   1  ORB           1 BinTableHDU     92   2067R x 16C   [1D, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E, 1E]
 */
 
-use blink_core::types::{MissionElapsedTime, Position, TemporalState, Trajectory};
+use blink_core::error::Error;
+use blink_core::fits;
+use blink_core::types::{MissionElapsedTime, OrbitState, Position, TemporalState, Trajectory};
 
 use crate::types::SvomGrm;
 
@@ -14,23 +16,28 @@ pub struct OrbFile {
 }
 
 impl OrbFile {
-    pub fn from_fits_file(path: &str) -> Result<Self, fitsio::errors::Error> {
+    pub fn from_fits_file(path: &str) -> Result<Self, Error> {
         let mut fptr = fitsio::FitsFile::open(path)?;
 
         let orb = OrbHdu::from_fptr(&mut fptr)?;
 
         Ok(Self { orb })
     }
+
+    /// Appends the following hour, skipping rows already present.
+    pub fn append(&mut self, other: Self) {
+        self.orb.append(other.orb);
+    }
 }
 
 struct OrbHdu {
     time: Vec<f64>,
-    // x_j2000: Vec<f32>,
-    // y_j2000: Vec<f32>,
-    // z_j2000: Vec<f32>,
-    // vx_j2000: Vec<f32>,
-    // vy_j2000: Vec<f32>,
-    // vz_j2000: Vec<f32>,
+    x_j2000: Vec<f32>,
+    y_j2000: Vec<f32>,
+    z_j2000: Vec<f32>,
+    vx_j2000: Vec<f32>,
+    vy_j2000: Vec<f32>,
+    vz_j2000: Vec<f32>,
     // x_wgs84: Vec<f32>,
     // y_wgs84: Vec<f32>,
     // z_wgs84: Vec<f32>,
@@ -43,34 +50,34 @@ struct OrbHdu {
 }
 
 impl OrbHdu {
-    fn from_fptr(fptr: &mut fitsio::FitsFile) -> Result<Self, fitsio::errors::Error> {
-        let orb = fptr.hdu("ORB")?;
-
-        let time = orb.read_col::<f64>(fptr, "TIME")?;
-        // let x_j2000 = orb.read_col::<f32>(fptr, "X_J2000")?;
-        // let y_j2000 = orb.read_col::<f32>(fptr, "Y_J2000")?;
-        // let z_j2000 = orb.read_col::<f32>(fptr, "Z_J2000")?;
-        // let vx_j2000 = orb.read_col::<f32>(fptr, "VX_J2000")?;
-        // let vy_j2000 = orb.read_col::<f32>(fptr, "VY_J2000")?;
-        // let vz_j2000 = orb.read_col::<f32>(fptr, "VZ_J2000")?;
-        // let x_wgs84 = orb.read_col::<f32>(fptr, "X_WGS84")?;
-        // let y_wgs84 = orb.read_col::<f32>(fptr, "Y_WGS84")?;
-        // let z_wgs84 = orb.read_col::<f32>(fptr, "Z_WGS84")?;
-        // let vx_wgs84 = orb.read_col::<f32>(fptr, "VX_WGS84")?;
-        // let vy_wgs84 = orb.read_col::<f32>(fptr, "VY_WGS84")?;
-        // let vz_wgs84 = orb.read_col::<f32>(fptr, "VZ_WGS84")?;
-        let lon = orb.read_col::<f32>(fptr, "LON")?;
-        let lat = orb.read_col::<f32>(fptr, "LAT")?;
-        let alt = orb.read_col::<f32>(fptr, "ALT")?;
+    fn from_fptr(fptr: &mut fitsio::FitsFile) -> Result<Self, Error> {
+        let orb = fits::hdu(fptr, "ORB")?;
+
+        let time = fits::read_col::<f64>(fptr, &orb, "TIME")?;
+        let x_j2000 = fits::read_col::<f32>(fptr, &orb, "X_J2000")?;
+        let y_j2000 = fits::read_col::<f32>(fptr, &orb, "Y_J2000")?;
+        let z_j2000 = fits::read_col::<f32>(fptr, &orb, "Z_J2000")?;
+        let vx_j2000 = fits::read_col::<f32>(fptr, &orb, "VX_J2000")?;
+        let vy_j2000 = fits::read_col::<f32>(fptr, &orb, "VY_J2000")?;
+        let vz_j2000 = fits::read_col::<f32>(fptr, &orb, "VZ_J2000")?;
+        // let x_wgs84 = fits::read_col::<f32>(fptr, &orb, "X_WGS84")?;
+        // let y_wgs84 = fits::read_col::<f32>(fptr, &orb, "Y_WGS84")?;
+        // let z_wgs84 = fits::read_col::<f32>(fptr, &orb, "Z_WGS84")?;
+        // let vx_wgs84 = fits::read_col::<f32>(fptr, &orb, "VX_WGS84")?;
+        // let vy_wgs84 = fits::read_col::<f32>(fptr, &orb, "VY_WGS84")?;
+        // let vz_wgs84 = fits::read_col::<f32>(fptr, &orb, "VZ_WGS84")?;
+        let lon = fits::read_col::<f32>(fptr, &orb, "LON")?;
+        let lat = fits::read_col::<f32>(fptr, &orb, "LAT")?;
+        let alt = fits::read_col::<f32>(fptr, &orb, "ALT")?;
 
         Ok(Self {
             time,
-            // x_j2000,
-            // y_j2000,
-            // z_j2000,
-            // vx_j2000,
-            // vy_j2000,
-            // vz_j2000,
+            x_j2000,
+            y_j2000,
+            z_j2000,
+            vx_j2000,
+            vy_j2000,
+            vz_j2000,
             // x_wgs84,
             // y_wgs84,
             // z_wgs84,
@@ -84,6 +91,25 @@ impl OrbHdu {
     }
 }
 
+impl OrbHdu {
+    fn append(&mut self, mut other: Self) {
+        let skip = match self.time.last() {
+            Some(&last) => other.time.partition_point(|&t| t <= last),
+            None => 0,
+        };
+        self.time.extend(other.time.drain(skip..));
+        self.x_j2000.extend(other.x_j2000.drain(skip..));
+        self.y_j2000.extend(other.y_j2000.drain(skip..));
+        self.z_j2000.extend(other.z_j2000.drain(skip..));
+        self.vx_j2000.extend(other.vx_j2000.drain(skip..));
+        self.vy_j2000.extend(other.vy_j2000.drain(skip..));
+        self.vz_j2000.extend(other.vz_j2000.drain(skip..));
+        self.lon.extend(other.lon.drain(skip..));
+        self.lat.extend(other.lat.drain(skip..));
+        self.alt.extend(other.alt.drain(skip..));
+    }
+}
+
 impl From<&OrbFile> for Trajectory<MissionElapsedTime<SvomGrm>, Position> {
     fn from(orb_file: &OrbFile) -> Self {
         let points = orb_file
@@ -103,6 +129,31 @@ impl From<&OrbFile> for Trajectory<MissionElapsedTime<SvomGrm>, Position> {
             })
             .collect();
 
-        Trajectory { points }
+        Trajectory::new(points)
+    }
+}
+
+impl From<&OrbFile> for Trajectory<MissionElapsedTime<SvomGrm>, OrbitState> {
+    fn from(orb_file: &OrbFile) -> Self {
+        let orb = &orb_file.orb;
+        let points = (0..orb.time.len())
+            .map(|i| TemporalState {
+                timestamp: MissionElapsedTime::new(orb.time[i]),
+                state: OrbitState {
+                    position: [
+                        orb.x_j2000[i] as f64,
+                        orb.y_j2000[i] as f64,
+                        orb.z_j2000[i] as f64,
+                    ],
+                    velocity: [
+                        orb.vx_j2000[i] as f64,
+                        orb.vy_j2000[i] as f64,
+                        orb.vz_j2000[i] as f64,
+                    ],
+                },
+            })
+            .collect();
+
+        Trajectory::new(points)
     }
 }
@@ -7,11 +7,15 @@ use crate::types::instrument::SvomGrm;
 use blink_core::error::Error;
 use chrono::prelude::*;
 
-mod from_epoch;
+mod from_range;
+mod region;
 mod search;
 
 pub struct Chunk {
+    /// Search range; only signals starting inside it are reported
     pub span: [MissionElapsedTime<SvomGrm>; 2],
+    /// Loaded range including the margins, used for the background
+    pub data_span: [MissionElapsedTime<SvomGrm>; 2],
     pub att_file: AttFile,
     pub evt_file: EvtFile,
     pub orb_file: OrbFile,
@@ -19,16 +23,58 @@ pub struct Chunk {
 
 impl blink_core::traits::Chunk for Chunk {
     type Event = Event;
+    type SearchConfig = blink_algorithms::snapshot_stepping::SearchConfig;
 
-    fn from_epoch(epoch: &chrono::DateTime<chrono::Utc>) -> Result<Self, blink_core::error::Error>
+    fn from_range(
+        start: &DateTime<Utc>,
+        stop: &DateTime<Utc>,
+        margin: chrono::TimeDelta,
+    ) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        from_epoch::from_epoch(epoch)
+        from_range::from_range(start, stop, margin)
     }
 
-    fn search(&self) -> Vec<blink_core::types::Signal<Self::Event>> {
-        search::search(self)
+    fn search_events(
+        &self,
+        config: &Self::SearchConfig,
+        events: Vec<Self::Event>,
+    ) -> Vec<blink_core::types::Signal<Self::Event>> {
+        search::search(self, config, events)
+    }
+
+    fn events(&self) -> Vec<Self::Event> {
+        self.evt_file.into_iter().collect()
+    }
+
+    fn live_time(&self) -> uom::si::f64::Time {
+        search::live_time(self)
+    }
+
+    fn search_config() -> Self::SearchConfig {
+        search::search_config()
+    }
+
+    fn detectors() -> usize {
+        3
+    }
+
+    fn synthetic_event(time: MissionElapsedTime<SvomGrm>, energy: f64, detector: usize) -> Event {
+        Event {
+            time,
+            // 注入的事例没有对应的 EBOUNDS，只带能量
+            channel: -1,
+            energy: energy as f32,
+            // GRD01–03
+            detector_id: detector as u8 + 1,
+            gain_type: 0,
+            // 注入的事例不额外引入死时间
+            dead_time: 0.0,
+            evt_type: 0,
+            anti_coin: 0,
+            flag: 0,
+        }
     }
 
     fn last_modified(epoch: &DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
@@ -0,0 +1,93 @@
+use crate::{
+    io::{
+        AttFile, EvtFile, OrbFile,
+        file::{find_att_by_time, find_evt_by_time, find_orb_by_time},
+    },
+    types::instrument::SvomGrm,
+};
+
+use super::Chunk;
+use blink_core::{
+    error::{Category, Error, ResultExt},
+    time,
+    traits::chunk::{check_range, hours},
+    types::MissionElapsedTime,
+};
+use chrono::{TimeDelta, prelude::*};
+
+pub(super) fn from_range(
+    start: &DateTime<Utc>,
+    stop: &DateTime<Utc>,
+    margin: TimeDelta,
+) -> Result<Chunk, Error> {
+    check_range(start, stop, margin)?;
+    let data_start = *start - margin;
+    let data_stop = *stop + margin;
+    let window = [
+        MissionElapsedTime::<SvomGrm>::from(data_start).met(),
+        MissionElapsedTime::<SvomGrm>::from(data_stop).met(),
+    ];
+
+    let mut files: Option<(AttFile, EvtFile, OrbFile)> = None;
+    let mut loaded: Option<[DateTime<Utc>; 2]> = None;
+
+    for hour in hours(&data_start, &data_stop) {
+        let core = hour < *stop && hour + TimeDelta::hours(1) > *start;
+        let (att_file, evt_file, orb_file) = match load_hour(&hour, window).epoch(hour) {
+            Ok(hour_files) => hour_files,
+            // 余量所在的小时缺数据时只缩小余量
+            Err(e) if !core && e.category() == Category::MissingData => continue,
+            Err(e) => return Err(e),
+        };
+        match &mut files {
+            Some((atts, evts, orbs)) => {
+                atts.append(att_file);
+                evts.append(evt_file);
+                orbs.append(orb_file);
+            }
+            None => files = Some((att_file, evt_file, orb_file)),
+        }
+        let end = hour + TimeDelta::hours(1);
+        loaded = Some(match loaded {
+            Some([first, _]) => [first, end],
+            None => [hour, end],
+        });
+    }
+
+    let (Some((att_file, evt_file, orb_file)), Some([first, last])) = (files, loaded) else {
+        return Err(Error::FileNotFound(format!(
+            "no L1B files for {start} .. {stop}"
+        )));
+    };
+
+    Ok(Chunk {
+        span: [
+            MissionElapsedTime::<SvomGrm>::from(*start),
+            MissionElapsedTime::<SvomGrm>::from(*stop),
+        ],
+        data_span: [
+            MissionElapsedTime::<SvomGrm>::from(data_start.max(first)),
+            MissionElapsedTime::<SvomGrm>::from(data_stop.min(last)),
+        ],
+        att_file,
+        evt_file,
+        orb_file,
+    })
+}
+
+fn load_hour(
+    epoch: &DateTime<Utc>,
+    window: [f64; 2],
+) -> Result<(AttFile, EvtFile, OrbFile), Error> {
+    let att_filename = find_att_by_time(epoch)?;
+    let att_file = AttFile::from_fits_file(att_filename.to_str().unwrap()).path(&att_filename)?;
+    let evt_filename = find_evt_by_time(epoch)?;
+    let evt_file = EvtFile::from_fits_file_within(evt_filename.to_str().unwrap(), window)
+        .path(&evt_filename)?;
+    if let Some(timesys) = evt_file.timesys() {
+        time::check_timesys::<SvomGrm>(timesys).path(&evt_filename)?;
+    }
+    let orb_filename = find_orb_by_time(epoch)?;
+    let orb_file = OrbFile::from_fits_file(orb_filename.to_str().unwrap()).path(&orb_filename)?;
+    Ok((att_file, evt_file, orb_file))
+}
@@ -0,0 +1,71 @@
+use super::Chunk;
+use crate::types::SvomGrm;
+use blink_algorithms::background::ReferenceRate;
+use blink_core::traits::Event as _;
+use blink_core::types::{MissionElapsedTime, Position, Region, Trajectory};
+use blink_region::{GeomagneticMask, MaskSet, RateMap, masked_intervals};
+use std::{
+    env,
+    sync::{Arc, LazyLock},
+};
+use uom::si::f64::*;
+
+type Interval = (MissionElapsedTime<SvomGrm>, MissionElapsedTime<SvomGrm>);
+
+/// Rate map written by `blink ratemap`; when set, hot cells are excluded too.
+pub(super) static RATE_MAP: LazyLock<Option<RateMap>> = LazyLock::new(|| {
+    let path = env::var("SVOM_RATE_MAP").ok()?;
+    RateMap::load(&path)
+        .inspect_err(|e| eprintln!("ignoring SVOM_RATE_MAP: {e}"))
+        .ok()
+});
+
+static REGION_MASKS: LazyLock<MaskSet> = LazyLock::new(|| {
+    // SVOM flies at 30° inclination and never reaches the belts; the SAA is
+    // cut on the IGRF field strength at 625 km.
+    let masks = MaskSet::new().exclude(GeomagneticMask::weak_field(Region::Saa, 24_000.0));
+    match RATE_MAP.as_ref() {
+        Some(map) => masks.exclude(map.to_mask(Region::HighRate, 5.0, 60.0)),
+        None => masks,
+    }
+});
+
+impl Chunk {
+    pub fn region_masks() -> &'static MaskSet {
+        &REGION_MASKS
+    }
+
+    pub fn get_region_intervals(&self) -> Vec<Interval> {
+        masked_intervals(
+            &Trajectory::<MissionElapsedTime<SvomGrm>, Position>::from(&self.orb_file),
+            |time| time.to_utc(),
+            Self::region_masks(),
+            10,
+            Time::new::<uom::si::time::second>(10.0),
+        )
+    }
+
+    pub fn accumulate_rate_map(&self, map: &mut RateMap) {
+        let times = self
+            .evt_file
+            .into_iter()
+            .filter(|event| event.keep())
+            .map(|event| event.time())
+            .collect::<Vec<_>>();
+        map.accumulate_trajectory(
+            &Trajectory::<MissionElapsedTime<SvomGrm>, Position>::from(&self.orb_file),
+            &times,
+            Time::new::<uom::si::time::second>(10.0),
+        );
+    }
+
+    /// Rate-map count rate along this hour's orbit, the reference of the
+    /// orbit-phase background; `None` without a rate map.
+    pub fn reference_rate(&self) -> Option<ReferenceRate> {
+        let map = RATE_MAP.as_ref()?;
+        let orbit = Trajectory::<MissionElapsedTime<SvomGrm>, Position>::from(&self.orb_file);
+        Some(Arc::new(move |time| {
+            map.rate_along(&orbit, MissionElapsedTime::from(time))
+        }))
+    }
+}
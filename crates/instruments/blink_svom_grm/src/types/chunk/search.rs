@@ -1,56 +1,118 @@
+use super::region::RATE_MAP;
 use crate::types::Chunk;
 use crate::types::Event;
 use crate::types::SvomGrm;
+use crate::types::event::ENERGY_MAX;
+use crate::types::event::ENERGY_SPLIT;
+use crate::types::event::ENERGY_THRESHOLD;
+use blink_algorithms::background::Background;
+use blink_algorithms::background::BackgroundKind;
+use blink_algorithms::bayesian_blocks::DEFAULT_P0;
+use blink_algorithms::characterisation::CharacterisationConfig;
+use blink_algorithms::far;
+use blink_algorithms::signal::Ephemeris;
+use blink_algorithms::signal::SignalContext;
+use blink_algorithms::signal::signals;
+use blink_algorithms::snapshot_stepping;
 use blink_algorithms::snapshot_stepping::SearchConfig;
-use blink_algorithms::snapshot_stepping::search_new;
-use blink_core::types::Attitude;
-use blink_core::types::MissionElapsedTime;
-use blink_core::types::Position;
+use blink_algorithms::snapshot_stepping::SearchEnv;
+use blink_core::traits::Event as _;
+use blink_core::traits::Instrument as _;
+use blink_core::types::Classification;
 use blink_core::types::Signal;
-use blink_core::types::Trajectory;
+use blink_region::is_in_intervals;
+use std::sync::LazyLock;
 use uom::si::f64::*;
 
-pub(super) fn search(chunk: &Chunk) -> Vec<Signal<Event>> {
-    let events = chunk.evt_file.into_iter().collect::<Vec<_>>();
-    let results = search_new(
+/// Combination, background and FAR calibration from the `SVOM_*`
+/// variables.
+static SEARCH_ENV: LazyLock<SearchEnv> =
+    LazyLock::new(|| SearchEnv::from_env("SVOM", RATE_MAP.is_some()));
+
+pub(super) fn search(
+    chunk: &Chunk,
+    config: &SearchConfig,
+    all_events: Vec<Event>,
+) -> Vec<Signal<Event>> {
+    // 能区外的事例只用于刻画信号
+    let events = all_events
+        .iter()
+        .filter(|event| event.keep())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut config = config.clone();
+    if SEARCH_ENV.background == BackgroundKind::OrbitPhase
+        && let Some(rate) = chunk.reference_rate()
+    {
+        config.background = Background::OrbitPhase(rate);
+    }
+
+    let results = snapshot_stepping::search(
         &events,
-        1,
-        chunk.span[0],
-        chunk.span[1],
-        SearchConfig {
-            min_duration: Time::new::<uom::si::time::microsecond>(0.0),
-            max_duration: Time::new::<uom::si::time::millisecond>(1.0),
-            neighbor: Time::new::<uom::si::time::second>(1.0),
-            hollow: Time::new::<uom::si::time::millisecond>(10.0),
-            false_positive_per_year: 20.0,
-            min_number: 8,
-        },
+        SvomGrm::group_names().len(),
+        chunk.data_span[0],
+        chunk.data_span[1],
+        config.clone(),
+    );
+
+    let region_intervals = chunk.get_region_intervals();
+    let results = results
+        .into_iter()
+        // 余量内的候选归相邻 chunk 所有，这里只用余量估计本底
+        .filter(|candidate| chunk.span[0] <= candidate.start && candidate.start < chunk.span[1])
+        .filter(|candidate| !is_in_intervals(&region_intervals, candidate.start))
+        .collect::<Vec<_>>();
+
+    let characterisation = CharacterisationConfig {
+        // GRM 能区 15 keV–5 MeV，以 150 keV 分软硬
+        soft: [ENERGY_THRESHOLD, ENERGY_SPLIT],
+        hard: [ENERGY_SPLIT, ENERGY_MAX],
+        detectors: 3,
+        margin: Time::new::<uom::si::time::millisecond>(1.0),
+        peak_bin: Time::new::<uom::si::time::microsecond>(20.0),
+        bootstrap: 200,
+        p0: DEFAULT_P0,
+    };
+    let ephemeris = Ephemeris::new(
+        (&chunk.att_file).into(),
+        (&chunk.orb_file).into(),
+        (&chunk.orb_file).into(),
     );
+    let context = SignalContext {
+        events: &events,
+        all_events: &all_events,
+        // 优先用事例自带的 DEAD_TIME；缺失时才按机箱估计非瘫痪模型
+        unit: |event: &Event| event.group() as usize,
+        characterisation: &characterisation,
+        ephemeris: &ephemeris,
+        config: &config,
+    };
+    signals(
+        results,
+        &context,
+        |state| Chunk::region_masks().region(state),
+        |_| Classification::default(),
+    )
+}
 
-    results
+/// The search range without excluded regions and gaps of more than a second
+/// in the kept events.
+pub(super) fn live_time(chunk: &Chunk) -> Time {
+    let times = chunk
+        .evt_file
         .into_iter()
-        .filter_map(|candidate| {
-            let peak = candidate.start + candidate.bin_size_best / 2.0;
-            let attitude =
-                Trajectory::<MissionElapsedTime<SvomGrm>, Attitude>::from(&chunk.att_file)
-                    .interpolate(peak)?;
-            let position =
-                Trajectory::<MissionElapsedTime<SvomGrm>, Position>::from(&chunk.orb_file)
-                    .interpolate(peak)?;
-            Some(Signal {
-                start: candidate.start,
-                stop: candidate.stop,
-                bin_size_min: candidate.bin_size_min,
-                bin_size_max: candidate.bin_size_max,
-                bin_size_best: candidate.bin_size_best,
-                delay: candidate.delay,
-                count: candidate.count,
-                mean: candidate.mean,
-                sf: candidate.sf(),
-                false_positive_per_year: candidate.false_positive_per_year(),
-                attitude: attitude.state,
-                position: position.state,
-            })
-        })
-        .collect::<Vec<_>>()
+        .filter(|event| event.keep())
+        .map(|event| event.time())
+        .collect::<Vec<_>>();
+    far::live_time(
+        chunk.span,
+        &chunk.get_region_intervals(),
+        &times,
+        Time::new::<uom::si::time::second>(1.0),
+    )
+}
+
+pub(super) fn search_config() -> SearchConfig {
+    SEARCH_ENV.search_config()
 }
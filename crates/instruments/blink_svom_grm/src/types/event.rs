@@ -1,12 +1,22 @@
 use blink_core::types::MissionElapsedTime;
 use serde::Serialize;
+use uom::si::f64::Time;
 
 use crate::types::instrument::SvomGrm;
 
+/// Lower edge in keV of the events searched
+pub const ENERGY_THRESHOLD: f64 = 15.0;
+/// Boundary in keV between the soft and hard bands
+pub const ENERGY_SPLIT: f64 = 150.0;
+/// Upper edge in keV of the events searched
+pub const ENERGY_MAX: f64 = 5000.0;
+
 #[derive(Serialize, Debug, Clone)]
 pub struct Event {
     pub time: MissionElapsedTime<SvomGrm>,
     pub channel: i16,
+    /// keV, from the `EBOUNDS` of the file
+    pub energy: f32,
     pub detector_id: u8,
     pub gain_type: u8,
     pub dead_time: f32,
@@ -27,12 +37,31 @@ impl blink_core::traits::Event for Event {
         self.channel
     }
 
+    fn energy(&self) -> f64 {
+        self.energy as f64
+    }
+
     fn group(&self) -> u8 {
-        0
+        // EVENTS01–03 对应 GRD01–03
+        self.detector_id.saturating_sub(1)
     }
 
     fn keep(&self) -> bool {
-        true
+        (ENERGY_THRESHOLD..ENERGY_MAX).contains(&self.energy())
+    }
+
+    fn with_time(&self, time: MissionElapsedTime<Self::Instrument>) -> Self {
+        Self {
+            time,
+            ..self.clone()
+        }
+    }
+
+    fn dead_time(&self) -> Option<Time> {
+        // DEAD_TIME 列单位为 µs
+        Some(Time::new::<uom::si::time::microsecond>(
+            self.dead_time as f64,
+        ))
     }
 }
 
@@ -22,4 +22,40 @@ impl Instrument for SvomGrm {
     fn name() -> &'static str {
         "SVOM/GRM"
     }
+
+    fn boresight() -> [f64; 3] {
+        [1.0, 0.0, 0.0]
+    }
+
+    fn detector_axes() -> Vec<[f64; 3]> {
+        // The three GRDs are tilted 30° off the boresight, 120° apart in
+        // azimuth around it (Wei et al. 2016, "The Deep and Transient
+        // Universe in the SVOM Era", arXiv:1610.06892). Which azimuth GRD01
+        // sits at is not given there; it is taken as +Y until checked
+        // against the instrument frame of the calibration files.
+        let tilt = 30f64.to_radians();
+        (0..3)
+            .map(|i| {
+                let azimuth = (120.0 * i as f64).to_radians();
+                [
+                    tilt.cos(),
+                    tilt.sin() * azimuth.cos(),
+                    tilt.sin() * azimuth.sin(),
+                ]
+            })
+            .collect()
+    }
+
+    fn group_names() -> &'static [&'static str] {
+        &["GRD01", "GRD02", "GRD03"]
+    }
+
+    fn detector_area() -> f64 {
+        // Three NaI(Tl) crystals of 160 mm diameter
+        3.0 * std::f64::consts::PI * 8.0 * 8.0
+    }
+
+    fn met_counts_leap_seconds() -> bool {
+        true
+    }
 }
@@ -1,5 +1,5 @@
 use chrono::prelude::*;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 use crate::util::{epoch_hour_of_met, parse_met_or_utc};
@@ -33,6 +33,134 @@ pub enum TopCommands {
     },
     /// WWLLN lightning association enrichment for detected signals
     Wwlln,
+    /// Accumulate the count-rate map used for region masks (HXMT_RATE_MAP,
+    /// SVOM_RATE_MAP)
+    Ratemap {
+        /// Start date (YYYY-MM-DD)
+        from: String,
+        /// End date (YYYY-MM-DD)
+        to: String,
+        /// Instrument whose data are read
+        #[arg(long, value_enum, default_value_t = InstrumentName::Hxmt)]
+        instrument: InstrumentName,
+        /// Output JSON file; extended in place if it already exists
+        #[arg(long, short = 'o')]
+        out: String,
+        /// Grid cell size in degrees
+        #[arg(long, default_value_t = 2.0)]
+        resolution: f64,
+    },
+    /// Inject synthetic TGFs and measure the detection efficiency of `search`
+    Inject(InjectArgs),
+    /// Calibrate the false-alarm rate by searching time-scrambled data
+    Far(FarArgs),
+    /// Fit photon spectra to the events of a bright signal
+    Spectrum(SpectrumArgs),
+    /// Summarise the per-day _errors.txt files written by `search`
+    Errors {
+        /// Directory to scan recursively
+        #[arg(default_value = "data")]
+        dir: PathBuf,
+    },
+}
+
+/// Instruments the commands can read.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum InstrumentName {
+    /// Insight-HXMT HE CsI
+    Hxmt,
+    /// SVOM GRM
+    Svom,
+}
+
+#[derive(Args)]
+pub struct InjectArgs {
+    /// Start date (YYYY-MM-DD)
+    pub from: String,
+    /// End date (YYYY-MM-DD)
+    pub to: String,
+    /// Output JSON report
+    #[arg(long, short = 'o')]
+    pub out: PathBuf,
+    /// Burst fluences in counts per cm² (comma-separated)
+    #[arg(long, value_delimiter = ',', default_value = "0.01,0.02,0.05,0.1,0.2")]
+    pub fluence: Vec<f64>,
+    /// Burst durations in microseconds (comma-separated)
+    #[arg(long, value_delimiter = ',', default_value = "50,200")]
+    pub duration_us: Vec<f64>,
+    /// Pulse shape: flat, gaussian or fred
+    #[arg(long, default_value = "gaussian")]
+    pub shape: String,
+    /// Photon index of the power law in energy
+    #[arg(long, default_value_t = 1.0)]
+    pub index: f64,
+    /// Injections of each burst per hour
+    #[arg(long, default_value_t = 1)]
+    pub per_hour: usize,
+    /// Random seed; the same seed reproduces the report
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Also run the fixed-bin trigger on the same injections
+    #[arg(long)]
+    pub fixed_bin: bool,
+    /// Instrument whose data the bursts are injected into
+    #[arg(long, value_enum, default_value_t = InstrumentName::Hxmt)]
+    pub instrument: InstrumentName,
+}
+
+#[derive(Args)]
+pub struct FarArgs {
+    /// Start date (YYYY-MM-DD)
+    pub from: String,
+    /// End date (YYYY-MM-DD)
+    pub to: String,
+    /// Output JSON calibration, read by the search from HXMT_FAR_CALIBRATION
+    #[arg(long, short = 'o')]
+    pub out: String,
+    /// Scrambling: intervals (shuffle inter-event intervals) or uniform
+    #[arg(long, default_value = "intervals")]
+    pub method: String,
+    /// Block within which events are scrambled, in seconds
+    #[arg(long, default_value_t = 1.0)]
+    pub block: f64,
+    /// Scrambled copies of each hour
+    #[arg(long, default_value_t = 10)]
+    pub trials: usize,
+    /// Random seed; the same seed reproduces the calibration
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Nominal false alarms per year to calibrate (comma-separated)
+    #[arg(long, value_delimiter = ',', default_value = "1,3,10,30,100,300,1000")]
+    pub thresholds: Vec<f64>,
+}
+
+#[derive(Args)]
+pub struct SpectrumArgs {
+    /// Signal start (MET number or UTC datetime)
+    pub start: String,
+    /// Signal stop (MET number or UTC datetime)
+    pub stop: String,
+    /// Instrument whose events are fitted; MET is read in its clock
+    #[arg(long, value_enum, default_value_t = InstrumentName::Hxmt)]
+    pub instrument: InstrumentName,
+    /// OGIP response: an RSP, or an RMF when --arf is given
+    #[arg(long)]
+    pub response: String,
+    /// OGIP ancillary response multiplied into the RMF
+    #[arg(long)]
+    pub arf: Option<String>,
+    /// Models to fit: cpl, band, rrea (comma-separated)
+    #[arg(long, value_delimiter = ',', default_value = "cpl,band,rrea")]
+    pub model: Vec<String>,
+    /// JSON RREA templates (comma-separated); the source spectrum if omitted
+    #[arg(long, value_delimiter = ',')]
+    pub template: Vec<String>,
+    /// Background window on either side of the signal, in seconds
+    #[arg(long, default_value_t = 1.0)]
+    pub margin: f64,
+    /// Output JSON with the count spectrum and the fits
+    #[arg(long, short = 'o')]
+    pub out: PathBuf,
 }
 
 #[derive(Subcommand)]
@@ -107,6 +235,9 @@ pub struct ReportArgs {
     /// Output directory for the data pack
     #[arg(long, short = 'o')]
     pub out: PathBuf,
+    /// False-positive prior of the Bayesian Blocks segmentation
+    #[arg(long, default_value_t = blink_algorithms::bayesian_blocks::DEFAULT_P0)]
+    pub p0: f64,
 }
 
 #[derive(Args)]
@@ -1,10 +1,38 @@
+use blink_algorithms::light_curve::LightCurve;
+use blink_core::types::MissionElapsedTime;
 use blink_hxmt_he::algorithms::saturation::reconstruct_met_times;
 use blink_hxmt_he::io::level_1b::SciFile;
 use blink_hxmt_he::io::level_1k::EventFile;
+use blink_hxmt_he::types::HxmtHe;
 use chrono::prelude::*;
+use uom::si::f64::Time;
 
 use crate::cli::CompareArgs;
 
+/// 1K and 1B counts of `[t0, t1)` in bins of `bin` seconds.
+fn binned(
+    k1_times: &[f64],
+    b1_times: &[f64],
+    t0: f64,
+    t1: f64,
+    bin: f64,
+) -> LightCurve<HxmtHe> {
+    let mut light_curve = LightCurve::new(
+        MissionElapsedTime::new(t0),
+        MissionElapsedTime::new(t1),
+        Time::new::<uom::si::time::second>(bin),
+        vec!["1K".to_string(), "1B".to_string()],
+    );
+    for (channel, times) in [k1_times, b1_times].into_iter().enumerate() {
+        let from = times.partition_point(|&t| t < t0);
+        let to = times.partition_point(|&t| t < t1);
+        for &t in &times[from..to] {
+            light_curve.fill(channel, MissionElapsedTime::new(t));
+        }
+    }
+    light_curve
+}
+
 pub fn cmd_compare(
     args: &CompareArgs,
     boxes: &[(String, SciFile, f64)],
@@ -64,16 +92,11 @@ pub fn cmd_compare(
             println!("  {:>5} {:>7} {:>7} {:>7} {:>8}", "T+", "1K", "1B", "delta", "delta%");
         }
 
-        let n_coarse = ((met_max - met_min) / args.coarse_bin).ceil() as usize;
-        for i in 0..n_coarse {
-            let t0 = met_min + i as f64 * args.coarse_bin;
-            let t1 = t0 + args.coarse_bin;
-            let t_rel = t0 - met_min;
-
-            let n_1k = k1_times.partition_point(|&t| t < t1)
-                - k1_times.partition_point(|&t| t < t0);
-            let n_1b = b1_times.partition_point(|&t| t < t1)
-                - b1_times.partition_point(|&t| t < t0);
+        let coarse = binned(k1_times, b1_times, met_min, met_max, args.coarse_bin);
+        for i in 0..coarse.len() {
+            let t_rel = coarse.start(i).met() - met_min;
+            let n_1k = coarse.counts[0][i] as usize;
+            let n_1b = coarse.counts[1][i] as usize;
 
             let delta = n_1b as i64 - n_1k as i64;
             let delta_pct = if n_1k > 0 {
@@ -107,17 +130,12 @@ pub fn cmd_compare(
             println!("Fine bins with |delta| > {:.0}%:", args.threshold);
         }
 
-        let n_fine = ((met_max - met_min) / args.fine_bin).ceil() as usize;
+        let fine = binned(k1_times, b1_times, met_min, met_max, args.fine_bin);
         let mut n_prob = 0;
-        for i in 0..n_fine {
-            let t0 = met_min + i as f64 * args.fine_bin;
-            let t1 = t0 + args.fine_bin;
-            let t_rel = t0 - met_min;
-
-            let n_1k = k1_times.partition_point(|&t| t < t1)
-                - k1_times.partition_point(|&t| t < t0);
-            let n_1b = b1_times.partition_point(|&t| t < t1)
-                - b1_times.partition_point(|&t| t < t0);
+        for i in 0..fine.len() {
+            let t_rel = fine.start(i).met() - met_min;
+            let n_1k = fine.counts[0][i] as usize;
+            let n_1b = fine.counts[1][i] as usize;
 
             if n_1k < 3 && n_1b < 3 { continue; }
 
@@ -153,32 +171,21 @@ pub fn cmd_compare(
         }
 
         let cc_bin = 0.001;
-        let n_cc_per_sec = (args.coarse_bin / cc_bin).round() as usize;
-        let n_coarse_cc = ((met_max - met_min) / args.coarse_bin).ceil() as usize;
-
-        for sec in 0..n_coarse_cc {
-            let t0 = met_min + sec as f64 * args.coarse_bin;
-            let t1 = t0 + args.coarse_bin;
 
-            let mut k_h = vec![0i64; n_cc_per_sec];
-            let mut b_h = vec![0i64; n_cc_per_sec];
+        for sec in 0..coarse.len() {
+            // 与 binned 一致，最后一个 bin 截止于 met_max
+            let t0 = coarse.start(sec).met();
+            let t1 = (coarse.start(sec) + coarse.width(sec)).met();
 
-            for &t in &k1_times[k1_times.partition_point(|&x| x < t0)
-                ..k1_times.partition_point(|&x| x < t1)]
-            {
-                let idx = ((t - t0) / cc_bin) as usize;
-                if idx < n_cc_per_sec { k_h[idx] += 1; }
-            }
-            for &t in &b1_times[b1_times.partition_point(|&x| x < t0)
-                ..b1_times.partition_point(|&x| x < t1)]
-            {
-                let idx = ((t - t0) / cc_bin) as usize;
-                if idx < n_cc_per_sec { b_h[idx] += 1; }
-            }
+            let cc = binned(k1_times, b1_times, t0, t1, cc_bin);
+            let k_h: Vec<i64> = cc.counts[0].iter().map(|&v| v as i64).collect();
+            let b_h: Vec<i64> = cc.counts[1].iter().map(|&v| v as i64).collect();
 
             let k_sum: i64 = k_h.iter().sum();
             let b_sum: i64 = b_h.iter().sum();
             if k_sum < 50 || b_sum < 50 { continue; }
+            // 截短的最后一个 bin 可能不足以容纳最大平移
+            if k_h.len() <= args.max_lag { continue; }
 
             let n = k_h.len() as f64;
             let k_mean = k_sum as f64 / n;
@@ -3,7 +3,11 @@ use blink_hxmt_he::algorithms::saturation::{
     extract_second_event_times, reconstruct_met_times, reconstruct_with_wrap_tracking_labeled,
     scan_saturation_intervals_raw,
 };
+use blink_algorithms::light_curve::LightCurve;
+use blink_core::types::MissionElapsedTime;
 use blink_hxmt_he::io::level_1b::SciFile;
+use blink_hxmt_he::types::HxmtHe;
+use uom::si::f64::Time;
 
 use crate::cli::{DumpBurstArgs, DumpHistArgs};
 
@@ -23,9 +27,11 @@ pub fn cmd_dump_times(
     println!("# before={:.1}", args.before);
     println!("# after={:.1}", args.after);
 
+    let mut saturation = Vec::new();
     for (box_name, sci, offset) in boxes {
         eprintln!("Box {} (offset={:.0}) ...", box_name, offset);
 
+        saturation.push((box_name, scan_saturation_intervals_raw(sci, *offset)));
         let all_met = reconstruct_met_times(sci, *offset);
         let n_total = all_met.len();
 
@@ -48,9 +54,8 @@ pub fn cmd_dump_times(
     }
 
     println!("# saturation_intervals");
-    for (box_name, sci, offset) in boxes {
-        let intervals = scan_saturation_intervals_raw(sci, *offset);
-        for (start, stop) in &intervals {
+    for (box_name, intervals) in &saturation {
+        for (start, stop) in intervals {
             if *stop >= met_min && *start <= met_max {
                 println!("SAT,{},{:.6},{:.6}", box_name, start, stop);
             }
@@ -125,49 +130,60 @@ pub fn cmd_dump_hist(
     let met_min = args.window.met_min();
     let met_max = args.window.met_max();
     let bin_width = args.bin;
-    let n_bins = ((met_max - met_min) / bin_width).ceil() as usize;
+    let names = filtered_boxes.iter().map(|(name, _, _)| name.clone()).collect();
+    let mut light_curve = LightCurve::<HxmtHe>::new(
+        MissionElapsedTime::new(met_min),
+        MissionElapsedTime::new(met_max),
+        Time::new::<uom::si::time::second>(bin_width),
+        names,
+    );
 
     eprintln!(
         "Histogram: [{:.3}, {:.3}], bin_width={:.4}s, n_bins={}",
-        met_min, met_max, bin_width, n_bins
+        met_min, met_max, bin_width, light_curve.len()
     );
 
-    let mut hist = vec![0u64; n_bins];
-    let mut n_total = 0u64;
-
-    for (box_name, sci, offset) in filtered_boxes {
+    let mut saturation = Vec::new();
+    let mut box_intervals = Vec::new();
+    for (i, (box_name, sci, offset)) in filtered_boxes.iter().enumerate() {
         let all_met = reconstruct_met_times(sci, *offset);
-        let n_box = all_met.len();
-        for t in &all_met {
-            if *t >= met_min && *t < met_max {
-                let idx = ((*t - met_min) / bin_width) as usize;
-                if idx < n_bins {
-                    hist[idx] += 1;
-                    n_total += 1;
-                }
-            }
-        }
+        let n_box = all_met
+            .iter()
+            .filter(|&&t| light_curve.fill(i, MissionElapsedTime::new(t)))
+            .count();
         eprintln!("  Box {}: {}/{} events in window", box_name, n_box, all_met.len());
+        let intervals = scan_saturation_intervals_raw(sci, *offset);
+        saturation.extend(intervals.iter().map(|&(start, stop)| {
+            (MissionElapsedTime::new(start), MissionElapsedTime::new(stop))
+        }));
+        box_intervals.push((box_name, intervals));
     }
+    // 任一所示 box 饱和的时间都不计入曝光
+    let light_curve = light_curve.with_exposure(None, &saturation);
+    let counts = light_curve.column(None);
+    let errors = light_curve.errors(None);
+    let n_total: u32 = counts.iter().sum();
 
     eprintln!("  Total in hist: {}", n_total);
 
     println!("# trigger_met={:.6}", met_min);
     println!("# before={:.1}, after={:.1}", args.window.before, args.window.after);
     println!("# bin_width={:.6}", bin_width);
-    println!("# n_bins={}", n_bins);
+    println!("# n_bins={}", light_curve.len());
     println!("# n_total={}", n_total);
 
-    println!("# HIST");
-    for (i, count) in hist.iter().enumerate() {
-        let bin_start = met_min + i as f64 * bin_width;
-        println!("{:.6},{}", bin_start, count);
+    println!("# HIST met,count,error_low,error_high,exposure");
+    for i in 0..light_curve.len() {
+        println!(
+            "{:.6},{},{:.3},{:.3},{:.6}",
+            light_curve.start(i).met(), counts[i], errors[i][0], errors[i][1],
+            light_curve.exposure[i].get::<uom::si::time::second>(),
+        );
     }
 
     println!("# SAT");
-    for (box_name, sci, offset) in filtered_boxes {
-        let intervals = scan_saturation_intervals_raw(sci, *offset);
-        for (start, stop) in &intervals {
+    for (box_name, intervals) in &box_intervals {
+        for (start, stop) in intervals {
             if *stop >= met_min && *start <= met_max {
                 println!("SAT,{},{:.6},{:.6}", box_name, start, stop);
             }
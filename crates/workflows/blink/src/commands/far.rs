@@ -0,0 +1,49 @@
+use blink_algorithms::far::Scramble;
+use blink_core::traits::Chunk as _;
+use blink_hxmt_he::types::{Chunk, HxmtHe};
+use blink_search::calibrate_far;
+use chrono::prelude::*;
+use uom::si::f64::Time;
+
+use crate::cli::FarArgs;
+
+/// Calibrate the empirical false-alarm rate over [start, end] and write
+/// the correction curve to `args.out`.
+pub fn cmd_far(args: &FarArgs, start: NaiveDate, end: NaiveDate) {
+    let block = Time::new::<uom::si::time::second>(args.block);
+    let scramble = match args.method.as_str() {
+        "intervals" => Scramble::Intervals { block },
+        "uniform" => Scramble::Uniform { block },
+        other => {
+            eprintln!(
+                "error: --method must be intervals or uniform, got '{}'",
+                other
+            );
+            std::process::exit(2);
+        }
+    };
+
+    // 阈值放宽到最大的名义率，并去掉已有的校准
+    let mut config = Chunk::search_config();
+    config.false_positive_per_year = args.thresholds.iter().copied().fold(0.0, f64::max);
+    config.far_calibration = None;
+
+    let calibration = calibrate_far::<HxmtHe>(
+        start,
+        end,
+        &config,
+        scramble,
+        args.trials,
+        args.seed,
+        &args.thresholds,
+    );
+    for point in &calibration.points {
+        eprintln!(
+            "nominal {:>8.1} /yr  empirical {:>10.1} ± {:<8.1} /yr  ({} signals)",
+            point.nominal, point.empirical, point.error, point.count,
+        );
+    }
+    calibration
+        .save(&args.out)
+        .expect("failed to write calibration");
+}
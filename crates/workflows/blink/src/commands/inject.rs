@@ -0,0 +1,106 @@
+use blink_algorithms::fixed_bin::FixedBin;
+use blink_algorithms::injection::{Burst, PulseShape, Spectrum};
+use blink_algorithms::snapshot_stepping::{Algorithm, SearchConfig};
+use blink_core::traits::{Chunk, Instrument};
+use blink_hxmt_he::types::HxmtHe;
+use blink_hxmt_he::types::event as hxmt_event;
+use blink_search::{InjectionGrid, inject_range};
+use blink_svom_grm::types::SvomGrm;
+use blink_svom_grm::types::event as svom_event;
+use chrono::prelude::*;
+use uom::si::f64::Time;
+
+use crate::cli::{InjectArgs, InstrumentName};
+
+/// Inject a fluence × duration grid of bursts into every hour of
+/// [start, end] and write the efficiency report to `args.out`.
+pub fn cmd_inject(args: &InjectArgs, start: NaiveDate, end: NaiveDate) {
+    match args.instrument {
+        // 与 keep() 的能量阈值一致，CsI 全能区
+        InstrumentName::Hxmt => inject::<HxmtHe>(
+            args,
+            start,
+            end,
+            [hxmt_event::ENERGY_THRESHOLD, hxmt_event::ENERGY_MAX],
+        ),
+        // 与 keep() 的能量范围一致，GRM 15 keV–5 MeV
+        InstrumentName::Svom => inject::<SvomGrm>(
+            args,
+            start,
+            end,
+            [svom_event::ENERGY_THRESHOLD, svom_event::ENERGY_MAX],
+        ),
+    }
+}
+
+/// Injects bursts with photon energies in `energies` (keV) into the data
+/// of `I`.
+fn inject<I: Instrument>(args: &InjectArgs, start: NaiveDate, end: NaiveDate, energies: [f64; 2])
+where
+    I::Chunk: Chunk<SearchConfig = SearchConfig>,
+{
+    let shape = match args.shape.as_str() {
+        "flat" => PulseShape::Flat,
+        "gaussian" => PulseShape::Gaussian,
+        "fred" => PulseShape::Fred { rise: 0.2 },
+        other => {
+            eprintln!(
+                "error: --shape must be flat, gaussian or fred, got '{}'",
+                other
+            );
+            std::process::exit(2);
+        }
+    };
+    let bursts = args
+        .fluence
+        .iter()
+        .flat_map(|&fluence| {
+            args.duration_us.iter().map(move |&duration| Burst {
+                fluence,
+                duration: Time::new::<uom::si::time::microsecond>(duration),
+                shape,
+                spectrum: Spectrum::PowerLaw { index: args.index },
+                energies,
+                detectors: Vec::new(),
+            })
+        })
+        .collect();
+    let grid = InjectionGrid {
+        bursts,
+        per_hour: args.per_hour,
+        seed: args.seed,
+    };
+
+    let mut configs = vec![("default".to_string(), I::Chunk::search_config())];
+    if args.fixed_bin {
+        let mut config = I::Chunk::search_config();
+        config.algorithm = Algorithm::FixedBin(FixedBin::default());
+        configs.push(("fixed-bin".to_string(), config));
+    }
+
+    let report = inject_range::<I>(start, end, &grid, &configs).unwrap_or_else(|e| {
+        eprintln!("error: {e}; lower --per-hour or the number of bursts");
+        std::process::exit(2);
+    });
+    for curve in &report.curves {
+        for point in &curve.points {
+            let burst = &grid.bursts[point.burst];
+            eprintln!(
+                "{:<10} fluence {:>8.4} /cm²  duration {:>6.0} us  {:>4}/{:<4} recovered ({:.2} ± {:.2})",
+                curve.label,
+                burst.fluence,
+                burst.duration.get::<uom::si::time::microsecond>(),
+                point.recovered,
+                point.injected,
+                point.efficiency,
+                point.error,
+            );
+        }
+    }
+    if !report.skipped.is_empty() {
+        eprintln!("{} hours skipped", report.skipped.len());
+    }
+
+    let json = serde_json::to_string_pretty(&report).expect("failed to serialize report");
+    std::fs::write(&args.out, json).expect("failed to write report");
+}
@@ -2,5 +2,9 @@ pub mod compare;
 pub mod detect;
 pub mod dump;
 pub mod extract;
+pub mod far;
+pub mod inject;
+pub mod ratemap;
 pub mod reconstruct;
 pub mod report;
+pub mod spectrum;
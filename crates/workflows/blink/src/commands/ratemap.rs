@@ -0,0 +1,62 @@
+use blink_core::traits::Chunk;
+use blink_region::RateMap;
+use chrono::prelude::*;
+use std::path::Path;
+
+use crate::cli::InstrumentName;
+
+/// Accumulate the count-rate map of `instrument` over [start, end] into `out`.
+/// An existing map at `out` is extended, so months can be added one at a time.
+pub fn cmd_ratemap(
+    instrument: InstrumentName,
+    start: NaiveDate,
+    end: NaiveDate,
+    out: &str,
+    resolution: f64,
+) {
+    let mut map = if Path::new(out).exists() {
+        eprintln!("Extending existing rate map {}", out);
+        RateMap::load(out).expect("failed to load rate map")
+    } else {
+        RateMap::new(resolution)
+    };
+
+    match instrument {
+        InstrumentName::Hxmt => accumulate(
+            start,
+            end,
+            &mut map,
+            blink_hxmt_he::types::Chunk::accumulate_rate_map,
+        ),
+        InstrumentName::Svom => accumulate(
+            start,
+            end,
+            &mut map,
+            blink_svom_grm::types::Chunk::accumulate_rate_map,
+        ),
+    }
+
+    map.save(out).expect("failed to write rate map");
+}
+
+fn accumulate<C: Chunk>(
+    start: NaiveDate,
+    end: NaiveDate,
+    map: &mut RateMap,
+    add: impl Fn(&C, &mut RateMap),
+) {
+    for day in start.iter_days().take_while(|day| *day <= end) {
+        let mut n_hours = 0;
+        for hour in 0..24 {
+            let naive = day.and_hms_opt(hour, 0, 0).expect("invalid time");
+            match C::from_epoch(&Utc.from_utc_datetime(&naive)) {
+                Ok(chunk) => {
+                    add(&chunk, map);
+                    n_hours += 1;
+                }
+                Err(e) => eprintln!("  {}T{:02}: {}", day, hour, e),
+            }
+        }
+        eprintln!("{}: {} hours accumulated", day, n_hours);
+    }
+}
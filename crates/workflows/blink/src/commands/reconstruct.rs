@@ -1,3 +1,5 @@
+use blink_algorithms::light_curve::LightCurve;
+use blink_core::types::MissionElapsedTime;
 use blink_hxmt_he::algorithms::saturation::{
     assign_gap_fill_channels, detect_fifo_reset_intervals, detect_unreliable_intervals,
     extract_packet_infos, reconstruct_gaps, reconstruct_met_channels,
@@ -5,6 +7,8 @@ use blink_hxmt_he::algorithms::saturation::{
     unwrap_channel, BoxReconstructionData, CHANNEL_SEC,
 };
 use blink_hxmt_he::io::level_1b::SciFile;
+use blink_hxmt_he::types::HxmtHe;
+use uom::si::f64::Time;
 
 use crate::cli::ReconstructArgs;
 
@@ -110,7 +114,7 @@ pub fn cmd_reconstruct(
     }
 
     println!("box,type,met,channel,pulse_width,pkt_idx,evt_idx");
-    for (box_name, _data) in &box_data {
+    for (box_name, data) in &box_data {
         let (obs_events, obs_channels, obs_pw) = original_events
             .iter()
             .find(|(n, _, _, _)| n == box_name)
@@ -130,18 +134,26 @@ pub fn cmd_reconstruct(
 
         let mut n_obs = 0u64;
         let mut n_gap = 0u64;
+        let mut light_curve = LightCurve::<HxmtHe>::new(
+            MissionElapsedTime::new(met_min),
+            MissionElapsedTime::new(met_max),
+            Time::new::<uom::si::time::second>(args.bin),
+            vec!["EVT".to_string(), "FILL_GAP".to_string()],
+        );
 
         for (idx, &t) in obs_events.iter().enumerate() {
             if t >= met_min && t <= met_max {
                 let ch = obs_channels[idx];
                 let raw = if ch == CHANNEL_SEC { 0 } else { unwrap_channel(ch) };
                 println!("{},EVT,{:.6},{},{},-1,-1", box_name, t, raw, obs_pw[idx]);
+                light_curve.fill(0, MissionElapsedTime::new(t));
                 n_obs += 1;
             }
         }
         for &(t, ch, pw) in gap_events {
             if t >= met_min && t <= met_max {
                 println!("{},FILL_GAP,{:.6},{},{},-1,-1", box_name, t, unwrap_channel(ch), pw);
+                light_curve.fill(1, MissionElapsedTime::new(t));
                 n_gap += 1;
             }
         }
@@ -150,5 +162,21 @@ pub fn cmd_reconstruct(
             "  Box {}: {} observed, {} gap-filled, bin={:.3}s",
             box_name, n_obs, n_gap, args.bin,
         );
+        // 观测事例的曝光扣除 FIFO reset 区间，只列出有补齐事例的区间
+        let gaps: Vec<_> = data
+            .gaps
+            .iter()
+            .map(|g| (MissionElapsedTime::new(g.start_met), MissionElapsedTime::new(g.stop_met)))
+            .collect();
+        let light_curve = light_curve.with_exposure(None, &gaps);
+        for i in (0..light_curve.len()).filter(|&i| light_curve.counts[1][i] > 0) {
+            let live = (light_curve.exposure[i] / light_curve.width(i))
+                .get::<uom::si::ratio::ratio>();
+            eprintln!(
+                "    T+{:8.3} {:7} observed {:7} filled  live {:5.1}%",
+                light_curve.start(i).met() - met_min,
+                light_curve.counts[0][i], light_curve.counts[1][i], live * 100.0,
+            );
+        }
     }
 }
@@ -4,6 +4,7 @@ use blink_hxmt_he::algorithms::saturation::{
     reconstruct_met_pulse_widths, reconstruct_met_times, reconstruct_with_wrap_tracking,
     solve_events, unwrap_channel, BoxReconstructionData,
 };
+use blink_algorithms::bayesian_blocks::{bayesian_blocks, excess_span};
 use blink_core::types::MissionElapsedTime;
 use blink_hxmt_he::io::level_1b::{get_eng_filenames, get_sci_filenames};
 use blink_hxmt_he::io::level_1k::EventFile;
@@ -36,8 +37,8 @@ pub fn cmd_report(args: &ReportArgs) -> std::io::Result<()> {
     create_dir_all(&args.out)?;
 
     // ── 1B path discovery (for manifest) ──
-    let sci_paths = get_sci_filenames(epoch);
-    let eng_paths = get_eng_filenames(epoch);
+    let sci_paths = get_sci_filenames(epoch).map_err(std::io::Error::other)?;
+    let eng_paths = get_eng_filenames(epoch).map_err(std::io::Error::other)?;
 
     // ── Run reconstruction for all boxes (needed for events_obs + events_rec) ──
     eprintln!("Preparing reconstruction data...");
@@ -148,13 +149,17 @@ pub fn cmd_report(args: &ReportArgs) -> std::io::Result<()> {
         writeln!(w, "met,channel,det_id,pkt_idx,evt_idx,is_second,pulse_width")?;
         let detailed = solve_events(sci, *offset, Some(met_min), Some(met_max));
         let mut n_obs = 0u64;
+        let mut window_times: Vec<f64> = Vec::new();
         for e in &detailed {
             writeln!(
                 w, "{:.6},{},{},{},{},{},{}",
                 e.met, e.channel, e.det_id, e.pkt_index, e.evt_index,
                 if e.is_second { 1 } else { 0 }, e.raw_bytes[1],
             )?;
-            if !e.is_second { n_obs += 1; }
+            if !e.is_second {
+                n_obs += 1;
+                window_times.push(e.met);
+            }
         }
         w.flush()?;
 
@@ -167,6 +172,7 @@ pub fn cmd_report(args: &ReportArgs) -> std::io::Result<()> {
             if t >= met_min && t <= met_max {
                 writeln!(w, "{:.6},{},{}", t, unwrap_channel(ch), pw)?;
                 n_rec += 1;
+                window_times.push(t);
             }
         }
         w.flush()?;
@@ -218,6 +224,23 @@ pub fn cmd_report(args: &ReportArgs) -> std::io::Result<()> {
         w.flush()?;
         let n_resets = in_window.len() as u64;
 
+        // blocks.csv (Bayesian Blocks of observed + reconstructed events in window)
+        window_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let blocks = bayesian_blocks(&window_times, met_min, met_max, args.p0);
+        let blocks_path = box_dir.join("blocks.csv");
+        let mut w = BufWriter::new(File::create(&blocks_path)?);
+        writeln!(w, "start_met,stop_met,count,rate")?;
+        for block in &blocks {
+            writeln!(w, "{:.6},{:.6},{},{:.3}", block.start, block.stop, block.count, block.rate)?;
+        }
+        w.flush()?;
+        if let Some([start, stop]) = excess_span(&blocks) {
+            eprintln!(
+                "  Box {}: {} blocks, excess {:.6} .. {:.6} ({:.3} s)",
+                box_name, blocks.len(), start, stop, stop - start
+            );
+        }
+
         eprintln!(
             "  Box {}: obs={} rec={} resets={} lost={} 1k={}",
             box_name, n_obs, n_rec, n_resets, n_lost_total, n_1k
@@ -0,0 +1,121 @@
+use blink_core::traits::{Chunk, Event};
+use blink_hxmt_he::types::event as hxmt_event;
+use blink_spectral::{CountSpectrum, Model, Response, Template, fit};
+use blink_svom_grm::types::event as svom_event;
+use chrono::TimeDelta;
+use uom::si::f64::Time;
+
+use crate::cli::{InstrumentName, SpectrumArgs};
+use crate::util::parse_time;
+
+/// Fit each requested model to the kept events between `args.start` and
+/// `args.stop` and write the spectrum and fits to `args.out`.
+pub fn cmd_spectrum(args: &SpectrumArgs) {
+    let response = match &args.arf {
+        Some(arf) => Response::from_rmf(&args.response, Some(arf)),
+        None => Response::from_rsp(&args.response),
+    }
+    .expect("failed to read response");
+    let mut models = Vec::new();
+    for name in &args.model {
+        match name.as_str() {
+            "cpl" => models.push(Model::CutoffPowerLaw),
+            "band" => models.push(Model::Band),
+            "rrea" if args.template.is_empty() => models.push(Model::Rrea(Template::rrea_source())),
+            "rrea" => {
+                for path in &args.template {
+                    let template = Template::load(path).expect("failed to read template");
+                    models.push(Model::Rrea(template));
+                }
+            }
+            other => {
+                eprintln!("error: --model must be cpl, band or rrea, got '{}'", other);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    match args.instrument {
+        InstrumentName::Hxmt => fit_signal::<blink_hxmt_he::types::Chunk>(
+            args,
+            &response,
+            &models,
+            [hxmt_event::ENERGY_THRESHOLD, hxmt_event::ENERGY_MAX],
+        ),
+        InstrumentName::Svom => fit_signal::<blink_svom_grm::types::Chunk>(
+            args,
+            &response,
+            &models,
+            [svom_event::ENERGY_THRESHOLD, svom_event::ENERGY_MAX],
+        ),
+    }
+}
+
+/// Loads the events of `C` around the signal, fits `models` within `band`
+/// (keV) and writes the report.
+fn fit_signal<C: Chunk>(
+    args: &SpectrumArgs,
+    response: &Response,
+    models: &[Model],
+    band: [f64; 2],
+) {
+    let start = parse_time::<<C::Event as Event>::Instrument>(&args.start);
+    let stop = parse_time::<<C::Event as Event>::Instrument>(&args.stop);
+    // 两侧本底窗所在的小时一并载入
+    let margin = TimeDelta::microseconds((args.margin * 1e6).ceil() as i64);
+    let chunk =
+        C::from_range(&start.to_utc(), &stop.to_utc(), margin).expect("failed to load events");
+    let events = chunk.events();
+    let spectrum = CountSpectrum::from_events(
+        &events,
+        response,
+        [start, stop],
+        Time::new::<uom::si::time::second>(args.margin),
+    );
+    eprintln!(
+        "{} counts over {:.1} expected background in {:.6} s",
+        spectrum.counts.iter().sum::<u32>(),
+        spectrum.background.iter().sum::<f64>(),
+        spectrum.exposure,
+    );
+
+    let fits = models
+        .iter()
+        .filter_map(|model| {
+            let result = fit(&spectrum, response, model, band);
+            if result.is_none() {
+                eprintln!("{:<20} no source counts in band", model.name());
+            }
+            result
+        })
+        .collect::<Vec<_>>();
+    for result in &fits {
+        let parameters = result
+            .parameters
+            .iter()
+            .map(|p| format!("{} {:.4} ± {:.4}", p.name, p.value, p.error))
+            .collect::<Vec<_>>();
+        eprintln!(
+            "{:<20} C {:>8.2} / {:<4} {}{}",
+            result.model,
+            result.cstat,
+            result.dof,
+            parameters.join(", "),
+            if result.converged {
+                ""
+            } else {
+                "  (not converged)"
+            },
+        );
+    }
+
+    let report = serde_json::json!({
+        "start": start.met(),
+        "stop": stop.met(),
+        "band": band,
+        "spectrum": spectrum,
+        "fits": fits,
+    });
+    std::fs::write(&args.out, serde_json::to_string_pretty(&report).unwrap())
+        .expect("failed to write spectrum");
+}
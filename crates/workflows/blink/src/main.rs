@@ -12,8 +12,12 @@ use commands::dump::{
     cmd_dump_ptime, cmd_dump_times,
 };
 use commands::extract::{cmd_extract_1b, cmd_extract_1k};
+use commands::far::cmd_far;
+use commands::inject::cmd_inject;
+use commands::ratemap::cmd_ratemap;
 use commands::reconstruct::cmd_reconstruct;
 use commands::report::cmd_report;
+use commands::spectrum::cmd_spectrum;
 use util::{filter_boxes, load_boxes, parse_epoch, warn_if_window_crosses_hour};
 
 fn main() {
@@ -147,5 +151,40 @@ fn main() {
         TopCommands::Wwlln => {
             blink_wwlln::run();
         }
+        TopCommands::Ratemap {
+            from,
+            to,
+            instrument,
+            out,
+            resolution,
+        } => {
+            let start = chrono::NaiveDate::parse_from_str(&from, "%Y-%m-%d")
+                .unwrap_or_else(|e| panic!("invalid --from date '{from}': {e}"));
+            let end = chrono::NaiveDate::parse_from_str(&to, "%Y-%m-%d")
+                .unwrap_or_else(|e| panic!("invalid --to date '{to}': {e}"));
+            cmd_ratemap(instrument, start, end, &out, resolution);
+        }
+        TopCommands::Inject(args) => {
+            let start = chrono::NaiveDate::parse_from_str(&args.from, "%Y-%m-%d")
+                .unwrap_or_else(|e| panic!("invalid --from date '{}': {e}", args.from));
+            let end = chrono::NaiveDate::parse_from_str(&args.to, "%Y-%m-%d")
+                .unwrap_or_else(|e| panic!("invalid --to date '{}': {e}", args.to));
+            cmd_inject(&args, start, end);
+        }
+        TopCommands::Far(args) => {
+            let start = chrono::NaiveDate::parse_from_str(&args.from, "%Y-%m-%d")
+                .unwrap_or_else(|e| panic!("invalid --from date '{}': {e}", args.from));
+            let end = chrono::NaiveDate::parse_from_str(&args.to, "%Y-%m-%d")
+                .unwrap_or_else(|e| panic!("invalid --to date '{}': {e}", args.to));
+            cmd_far(&args, start, end);
+        }
+        TopCommands::Spectrum(args) => {
+            cmd_spectrum(&args);
+        }
+        TopCommands::Errors { dir } => {
+            let summary = blink_search::summarize_errors(&dir)
+                .unwrap_or_else(|e| panic!("failed to read {}: {e}", dir.display()));
+            print!("{summary}");
+        }
     }
 }
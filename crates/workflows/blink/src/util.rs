@@ -1,5 +1,9 @@
+use blink_core::error::Error;
+use blink_core::traits::Instrument;
 use blink_core::types::MissionElapsedTime;
-use blink_hxmt_he::io::level_1b::{SciFile, get_eng_filenames, get_sci_filenames, read_stime_offset};
+use blink_hxmt_he::io::level_1b::{
+    SciFile, get_eng_filenames, get_sci_filenames, read_stime_offset,
+};
 use blink_hxmt_he::types::HxmtHe;
 use chrono::prelude::*;
 
@@ -13,18 +17,31 @@ pub fn parse_epoch(epoch_str: &str) -> DateTime<Utc> {
 
 /// Parse a time argument that can be either MET (float) or UTC (datetime string).
 pub fn parse_met_or_utc(s: &str) -> f64 {
+    parse_time::<HxmtHe>(s).met()
+}
+
+/// Parse a time argument that can be either the MET of `I` (float) or UTC
+/// (datetime string).
+pub fn parse_time<I: Instrument>(s: &str) -> MissionElapsedTime<I> {
     if let Ok(met) = s.parse::<f64>() {
-        return met;
+        return MissionElapsedTime::new(met);
     }
     let utc = s.parse::<DateTime<Utc>>().unwrap_or_else(|_| {
-        format!("{}Z", s).parse::<DateTime<Utc>>()
+        format!("{}Z", s)
+            .parse::<DateTime<Utc>>()
             .or_else(|_| format!("{}:00Z", s).parse::<DateTime<Utc>>())
             .or_else(|_| format!("{}:00:00Z", s).parse::<DateTime<Utc>>())
-            .expect("Invalid time format. Use MET number or UTC datetime (e.g. 2020-04-15T08:34:48)")
+            .expect(
+                "Invalid time format. Use MET number or UTC datetime (e.g. 2020-04-15T08:34:48)",
+            )
     });
     // 用核心(含闰秒)转换，与 1B/1K 的 MET 基准一致；不要用朴素日历差。
-    let met = MissionElapsedTime::<HxmtHe>::from(utc).met();
-    eprintln!("  UTC {} -> MET {:.6}", utc.format("%Y-%m-%dT%H:%M:%S"), met);
+    let met = MissionElapsedTime::<I>::from(utc);
+    eprintln!(
+        "  UTC {} -> MET {:.6}",
+        utc.format("%Y-%m-%dT%H:%M:%S"),
+        met.met()
+    );
     met
 }
 
@@ -37,18 +54,36 @@ pub fn epoch_hour_of_met(met: f64) -> DateTime<Utc> {
         .and_utc()
 }
 
+/// Loads the 1B science file of every box in the hour of `epoch` with the
+/// stime offset from its engineering file. Files that fail to load are
+/// reported with their error category and skipped; a missing offset is 0.
 pub fn load_boxes(epoch: DateTime<Utc>) -> Vec<(String, SciFile, f64)> {
-    let sci_pairs = get_sci_filenames(epoch);
-    let eng_pairs = get_eng_filenames(epoch);
+    let report = |what: &str, e: &Error| eprintln!("warning: {}: {} [{}]", what, e, e.category());
+    let sci_pairs = get_sci_filenames(epoch).unwrap_or_else(|e| {
+        report("no 1B science files", &e);
+        Vec::new()
+    });
+    let eng_pairs = get_eng_filenames(epoch).unwrap_or_else(|e| {
+        report("no 1B engineering files, stime offsets set to 0", &e);
+        Vec::new()
+    });
 
     sci_pairs
         .iter()
         .filter_map(|(box_name, sci_path)| {
-            let sci = SciFile::new(sci_path).ok()?;
+            let sci = SciFile::new(sci_path)
+                .inspect_err(|e| report(&format!("skipping box {}", box_name), e))
+                .ok()?;
             let offset = eng_pairs
                 .iter()
                 .find(|(bn, _)| bn == box_name)
-                .and_then(|(_, eng_path)| read_stime_offset(eng_path).ok())
+                .and_then(|(_, eng_path)| {
+                    read_stime_offset(eng_path)
+                        .inspect_err(|e| {
+                            report(&format!("stime offset of box {} set to 0", box_name), e)
+                        })
+                        .ok()
+                })
                 .unwrap_or(0.0);
             Some((box_name.clone(), sci, offset))
         })
@@ -60,7 +95,10 @@ pub fn filter_boxes<'a>(
     filter: &Option<String>,
 ) -> Vec<&'a (String, SciFile, f64)> {
     if let Some(fb) = filter {
-        boxes.iter().filter(|(name, _, _)| name.eq_ignore_ascii_case(fb)).collect()
+        boxes
+            .iter()
+            .filter(|(name, _, _)| name.eq_ignore_ascii_case(fb))
+            .collect()
     } else {
         boxes.iter().collect()
     }
@@ -89,9 +127,11 @@ pub fn warn_if_window_crosses_hour(met: f64, before: f64, after: f64, epoch: Dat
     if met - before < epoch_start_met || met + after > epoch_end_met {
         eprintln!(
             "warning: window [{:.1}, {:.1}] crosses hour boundary; only loading hour {} ({:.1}..{:.1})",
-            met - before, met + after,
+            met - before,
+            met + after,
             epoch.format("%Y-%m-%dT%H"),
-            epoch_start_met, epoch_end_met
+            epoch_start_met,
+            epoch_end_met
         );
     }
 }
@@ -0,0 +1,63 @@
+use blink_algorithms::far::{FarCalibration, Scramble};
+use blink_core::traits::{Chunk, Instrument};
+use chrono::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::load_chunk;
+
+type ConfigOf<I> = <<I as Instrument>::Chunk as Chunk>::SearchConfig;
+
+/// Searches `trials` scrambled copies of every hour of `[start, end]` and
+/// calibrates the false-alarm rate at the nominal `thresholds`.
+///
+/// `config` must be at least as loose as the largest threshold and carry no
+/// calibration of its own. Hours that fail to load are skipped.
+pub fn calibrate_far<I: Instrument>(
+    start: NaiveDate,
+    end: NaiveDate,
+    config: &ConfigOf<I>,
+    scramble: Scramble,
+    trials: usize,
+    seed: u64,
+    thresholds: &[f64],
+) -> FarCalibration {
+    let mut exposure = 0.0;
+    let mut signals = Vec::new();
+    for day in start.iter_days().take_while(|day| *day <= end) {
+        let mut n_hours = 0;
+        for hour in 0..24 {
+            let naive = day.and_hms_opt(hour, 0, 0).expect("invalid time");
+            let epoch = Utc.from_utc_datetime(&naive);
+            let chunk = match load_chunk::<I>(epoch) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    eprintln!("  {}T{:02}: {}", day, hour, e);
+                    continue;
+                }
+            };
+            let events = chunk.events();
+            // 扣除排除区间与数据断档后实际搜索的时长
+            let live_time = chunk.live_time().get::<uom::si::time::second>();
+            // 每小时独立播种，结果不依赖日期范围
+            let mut rng = StdRng::seed_from_u64(seed ^ epoch.timestamp() as u64);
+            for _ in 0..trials {
+                let scrambled = scramble.apply(&events, &mut rng);
+                signals.extend(
+                    chunk
+                        .search_events(config, scrambled)
+                        .into_iter()
+                        .map(|signal| signal.false_positive_per_year),
+                );
+                exposure += live_time;
+            }
+            n_hours += 1;
+        }
+        eprintln!(
+            "{}: {} hours, {} scrambled signals so far",
+            day,
+            n_hours,
+            signals.len()
+        );
+    }
+    FarCalibration::new(scramble, exposure, &signals, thresholds)
+}
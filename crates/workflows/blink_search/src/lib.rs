@@ -1,8 +1,65 @@
+use blink_algorithms::episode::{Separation, episodes};
+use blink_core::error::{Category, Error, ResultExt};
 use blink_core::traits::{Chunk, Instrument};
+use blink_core::types::{TemporalState, UnifiedSignal};
 use blink_workflow::process;
-use chrono::prelude::*;
+use chrono::{TimeDelta, prelude::*};
 use indicatif::{MultiProgress, ProgressBar};
-use std::fs;
+use std::{env, fs, sync::LazyLock, thread, time::Duration};
+use uom::si::f64::Time;
+
+mod far;
+mod injection;
+mod summary;
+
+pub use far::calibrate_far;
+pub use injection::{
+    EfficiencyCurve, EfficiencyPoint, InjectionGrid, InjectionReport, Trial, inject_range,
+};
+pub use summary::{ErrorSummary, summarize_errors};
+
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// 默认余量（毫秒）：覆盖 1 s 的本底窗口，以及 fixed-bin 本底窗口与 bin 之间的 0.1 s 间隔
+const DEFAULT_SEARCH_MARGIN_MS: i64 = 1100;
+
+/// 每小时两侧额外载入的数据（毫秒），只用于估计本底
+static SEARCH_MARGIN: LazyLock<TimeDelta> = LazyLock::new(|| {
+    let millis = env::var("BLINK_SEARCH_MARGIN_MS")
+        .ok()
+        .and_then(|value| {
+            parse_margin(&value)
+                .inspect_err(|e| eprintln!("ignoring BLINK_SEARCH_MARGIN_MS: {e}"))
+                .ok()
+        })
+        .unwrap_or(DEFAULT_SEARCH_MARGIN_MS);
+    TimeDelta::milliseconds(millis)
+});
+
+/// 解析非负的毫秒数
+fn parse_margin(value: &str) -> Result<i64, String> {
+    let millis = value
+        .trim()
+        .parse::<i64>()
+        .map_err(|e| format!("'{value}': {e}"))?;
+    if millis < 0 {
+        return Err(format!("negative margin {millis} ms"));
+    }
+    Ok(millis)
+}
+
+/// 同一多脉冲事件中相邻脉冲的最大间隔（毫秒），默认见 `Separation::default`
+static EPISODE_SEPARATION: LazyLock<Separation> = LazyLock::new(|| {
+    let mut separation = Separation::default();
+    if let Some(millis) = env::var("BLINK_EPISODE_GAP_MS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+    {
+        separation.max_gap = Time::new::<uom::si::time::millisecond>(millis);
+    }
+    separation
+});
 
 pub fn search_day<I: Instrument>(day: NaiveDate, multi_progress: &MultiProgress) {
     let spin_bar = multi_progress.add(ProgressBar::new(24));
@@ -22,13 +79,7 @@ pub fn search_day<I: Instrument>(day: NaiveDate, multi_progress: &MultiProgress)
         month
     );
     std::fs::create_dir_all(&output_dir).expect("failed to create output directory");
-    let output_file = format!(
-        "{}{:04}{:02}{:02}_signals.json",
-        output_dir,
-        year,
-        month,
-        day.day(),
-    );
+    let output_file = day_file::<I>(day, "signals.json");
 
     spin_bar.set_message("check last modified");
     let last_modified = (0..24)
@@ -59,16 +110,26 @@ pub fn search_day<I: Instrument>(day: NaiveDate, multi_progress: &MultiProgress)
     );
 
     let mut all_signals = Vec::new();
-    let mut errors: Vec<(u32, blink_core::error::Error)> = Vec::new();
+    let mut errors: Vec<(u32, Error)> = Vec::new();
+    let mut aborted = false;
     for hour in 0..24 {
         let naive = day.and_hms_opt(hour, 0, 0).expect("invalid time");
-        match I::Chunk::from_epoch(&Utc.from_utc_datetime(&naive)) {
+        match load_chunk::<I>(Utc.from_utc_datetime(&naive)) {
             Ok(chunk) => {
-                let mut sigs = chunk.search().into_iter().map(|e| e.to_unified()).collect();
+                let mut sigs = chunk
+                    .search()
+                    .into_iter()
+                    .map(|e| with_geomagnetic(e.to_unified()))
+                    .collect();
                 all_signals.append(&mut sigs);
             }
             Err(e) => {
+                // 缺数据、坏文件跳过该小时；程序或配置错误每小时都会重现，直接中止
+                aborted = e.category() == Category::Bug;
                 errors.push((hour, e));
+                if aborted {
+                    break;
+                }
             }
         }
         progress_bar.inc(1);
@@ -86,9 +147,15 @@ pub fn search_day<I: Instrument>(day: NaiveDate, multi_progress: &MultiProgress)
     let suffix = format!(".{}.tmp", nanoid::nanoid!(3));
     let temp_file = format!("{}{}", &output_file, &suffix);
 
-    let json = serde_json::to_string_pretty(&all_signals).expect("failed to serialize signals");
-    std::fs::write(&temp_file, json).expect("failed to write output file");
-    std::fs::rename(&temp_file, &output_file).expect("failed to rename output file");
+    // 中止时不写结果文件，保证下次运行会重新处理这一天
+    if !aborted {
+        // 先写多脉冲事件：信号文件是否更新决定这一天是否已处理
+        write_episodes::<I>(day, &all_signals, &suffix);
+
+        let json = serde_json::to_string_pretty(&all_signals).expect("failed to serialize signals");
+        std::fs::write(&temp_file, json).expect("failed to write output file");
+        std::fs::rename(&temp_file, &output_file).expect("failed to rename output file");
+    }
 
     spin_bar_writting.set_message("writing error file");
     let error_file = format!(
@@ -104,13 +171,113 @@ pub fn search_day<I: Instrument>(day: NaiveDate, multi_progress: &MultiProgress)
     } else {
         let mut error_contents = String::new();
         for (hour, error) in &errors {
-            error_contents.push_str(&format!("Error {}T{:02}: {}\n", day, hour, error));
+            // 每行：小时、类别、错误种类、详情，以制表符分隔，供 summarize_errors 解析
+            error_contents.push_str(&format!(
+                "{}T{:02}\t{}\t{}\t{}\n",
+                day,
+                hour,
+                error.category(),
+                error.kind(),
+                error.to_string().replace(['\t', '\n'], " ")
+            ));
         }
         fs::write(&error_file_temp, error_contents).expect("failed to write error file");
         fs::rename(&error_file_temp, &error_file).expect("failed to rename error file");
     }
 
     spin_bar_writting.finish_and_clear();
+
+    if aborted && let Some((hour, error)) = errors.last() {
+        panic!("{} {}T{:02}: aborting: {}", I::name(), day, hour, error);
+    }
+}
+
+/// `data/<I>/YYYY/MM/YYYYMMDD_<name>`, the output of `day`.
+fn day_file<I: Instrument>(day: NaiveDate, name: &str) -> String {
+    format!(
+        "data/{}/{:04}/{:02}/{:04}{:02}{:02}_{}",
+        I::name().replace("/", "_"),
+        day.year(),
+        day.month(),
+        day.year(),
+        day.month(),
+        day.day(),
+        name
+    )
+}
+
+/// Signals written for `day`; none if the day has not been searched.
+fn read_signals<I: Instrument>(day: NaiveDate) -> Vec<UnifiedSignal> {
+    let path = day_file::<I>(day, "signals.json");
+    let Ok(json) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json)
+        .inspect_err(|e| eprintln!("ignoring {path}: {e}"))
+        .unwrap_or_default()
+}
+
+/// Writes the episodes starting on `day`, whose signals are `signals`, and
+/// rewrites those of the day before if it has been searched.
+///
+/// Each day is grouped together with the signals of the days either side,
+/// so an episode spanning midnight is found whole whichever of the two
+/// days is searched last.
+fn write_episodes<I: Instrument>(day: NaiveDate, signals: &[UnifiedSignal], suffix: &str) {
+    let searched = |other: NaiveDate| fs::metadata(day_file::<I>(other, "signals.json")).is_ok();
+    for target in day.pred_opt().into_iter().chain([day]) {
+        if target != day && !searched(target) {
+            continue;
+        }
+        let mut around = Vec::new();
+        for other in target
+            .pred_opt()
+            .into_iter()
+            .chain([target])
+            .chain(target.succ_opt())
+        {
+            if other == day {
+                around.extend_from_slice(signals);
+            } else {
+                around.extend(read_signals::<I>(other));
+            }
+        }
+        let episodes = episodes(&around, &EPISODE_SEPARATION)
+            .into_iter()
+            .filter(|episode| episode.start.date_naive() == target)
+            .collect::<Vec<_>>();
+        let episode_file = day_file::<I>(target, "episodes.json");
+        let json = serde_json::to_string_pretty(&episodes).expect("failed to serialize episodes");
+        let episode_temp = format!("{}{}", &episode_file, suffix);
+        fs::write(&episode_temp, json).expect("failed to write episode file");
+        fs::rename(&episode_temp, &episode_file).expect("failed to rename episode file");
+    }
+}
+
+/// Loads one hour plus the search margin, retrying transient I/O failures
+/// with a growing delay.
+fn load_chunk<I: Instrument>(epoch: DateTime<Utc>) -> Result<I::Chunk, Error> {
+    let mut attempt = 1;
+    loop {
+        match I::Chunk::from_range(&epoch, &(epoch + TimeDelta::hours(1)), *SEARCH_MARGIN)
+            .epoch(epoch)
+            .instrument(I::name())
+        {
+            Err(e) if e.category() == Category::TransientIo && attempt < MAX_ATTEMPTS => {
+                thread::sleep(RETRY_DELAY * attempt);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn with_geomagnetic(mut signal: UnifiedSignal) -> UnifiedSignal {
+    signal.geomagnetic = blink_geomag::geomagnetic(&TemporalState {
+        timestamp: signal.peak_time(),
+        state: signal.position.clone(),
+    });
+    signal
 }
 
 pub fn search_all<I: Instrument>(total_workers: usize, idx_worker: usize) {
@@ -136,3 +303,23 @@ pub fn search_range<I: Instrument>(
         idx_worker,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blink_algorithms::fixed_bin::FixedBin;
+
+    #[test]
+    fn default_margin_covers_the_fixed_bin_history() {
+        let margin = Time::new::<uom::si::time::millisecond>(DEFAULT_SEARCH_MARGIN_MS as f64);
+        assert!(FixedBin::default().history() <= margin);
+    }
+
+    #[test]
+    fn margin_must_be_non_negative_milliseconds() {
+        assert_eq!(parse_margin(" 1500 "), Ok(1500));
+        assert_eq!(parse_margin("0"), Ok(0));
+        assert!(parse_margin("-1").is_err());
+        assert!(parse_margin("1s").is_err());
+    }
+}
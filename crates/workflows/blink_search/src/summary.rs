@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Counts of the hours recorded in `*_errors.txt` files.
+#[derive(Default, Debug)]
+pub struct ErrorSummary {
+    pub files: usize,
+    /// `(category, kind)` → number of failed hours
+    pub counts: BTreeMap<(String, String), usize>,
+    /// First and last failed hour, as written in the files
+    pub span: Option<(String, String)>,
+}
+
+impl ErrorSummary {
+    fn add_line(&mut self, line: &str) {
+        let mut fields = line.splitn(4, '\t');
+        let (Some(hour), Some(category), Some(kind)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return;
+        };
+        *self
+            .counts
+            .entry((category.to_string(), kind.to_string()))
+            .or_default() += 1;
+        self.span = Some(match self.span.take() {
+            Some((first, last)) => (first.min(hour.to_string()), last.max(hour.to_string())),
+            None => (hour.to_string(), hour.to_string()),
+        });
+    }
+}
+
+impl fmt::Display for ErrorSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.counts.values().sum::<usize>();
+        write!(f, "{total} failed hours in {} files", self.files)?;
+        if let Some((first, last)) = &self.span {
+            write!(f, " ({first} .. {last})")?;
+        }
+        writeln!(f)?;
+        for ((category, kind), count) in &self.counts {
+            writeln!(f, "{count:>8}  {category:<14} {kind}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks `root` for the `*_errors.txt` files written by `search_day`.
+pub fn summarize_errors(root: &Path) -> std::io::Result<ErrorSummary> {
+    let mut summary = ErrorSummary::default();
+    visit(root, &mut summary)?;
+    Ok(summary)
+}
+
+fn visit(dir: &Path, summary: &mut ErrorSummary) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            visit(&path, summary)?;
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with("_errors.txt"))
+        {
+            summary.files += 1;
+            for line in std::fs::read_to_string(&path)?.lines() {
+                summary.add_line(line);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_by_category_and_kind() {
+        let mut summary = ErrorSummary::default();
+        summary.add_line("2025-01-02T03\tmissing-data\tfile-not-found\tfile not found: x");
+        summary.add_line("2025-01-01T05\tmissing-data\tfile-not-found\tfile not found: y");
+        summary.add_line("2025-01-02T04\tcorrupt-data\tfitsio\tFitsio error: z");
+        summary.add_line("Error 2024-01-01T00: legacy line");
+
+        let key = ("missing-data".to_string(), "file-not-found".to_string());
+        assert_eq!(summary.counts[&key], 2);
+        assert_eq!(summary.counts.values().sum::<usize>(), 3);
+        assert_eq!(
+            summary.span,
+            Some(("2025-01-01T05".to_string(), "2025-01-02T04".to_string()))
+        );
+    }
+}
@@ -1,8 +1,10 @@
-use blink_core::types::{TemporalState, UnifiedSignal};
+use blink_core::error::Error;
+use blink_core::traits::Instrument as _;
+use blink_core::types::{Incidence, TemporalState, UnifiedSignal};
 use blink_hxmt_he::types::HxmtHe;
 use blink_lightning::{algorithms::coincidence_prob, database::get_lightnings};
 use blink_load::load_all;
-// use blink_svom_grm::types::SvomGrm;
+use blink_svom_grm::types::SvomGrm;
 use chrono::TimeDelta;
 use serde::Serialize;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -12,6 +14,8 @@ use uom::si::f64::*;
 struct LightningInfo {
     associated: bool,
     coincidence_probability: f64,
+    /// 最近（时间上）一次关联闪电的入射几何
+    incidence: Option<Incidence>,
 }
 
 #[derive(Serialize)]
@@ -20,9 +24,18 @@ struct Tgf {
     lightning: LightningInfo,
 }
 
+/// 候选所属仪器的探测器指向
+fn detector_axes(instrument: &str) -> Result<Vec<[f64; 3]>, Error> {
+    match instrument {
+        name if name == HxmtHe::name() => Ok(HxmtHe::detector_axes()),
+        name if name == SvomGrm::name() => Ok(SvomGrm::detector_axes()),
+        other => Err(Error::InvalidData(format!("unknown instrument {other}"))),
+    }
+}
+
 /// 对单个候选做 WWLLN 闪电关联 + 虚警概率。每次调用的两个 `get_lightnings`
 /// 查询走线程本地只读连接（见 blink_lightning::database），可安全并行。
-fn associate(signal: &UnifiedSignal) -> Tgf {
+fn associate(signal: &UnifiedSignal) -> Result<Tgf, Error> {
     let peak_time = signal.peak_time();
     let position = TemporalState {
         timestamp: peak_time,
@@ -31,7 +44,7 @@ fn associate(signal: &UnifiedSignal) -> Tgf {
     let lightnings = get_lightnings(
         peak_time - TimeDelta::seconds(1),
         peak_time + TimeDelta::seconds(1),
-    )
+    )?
     .into_iter()
     .filter(|lightning| {
         lightning.is_associated(
@@ -42,7 +55,13 @@ fn associate(signal: &UnifiedSignal) -> Tgf {
     })
     .collect::<Vec<_>>();
 
-    Tgf {
+    let detector_axes = detector_axes(&signal.instrument)?;
+    let incidence = lightnings
+        .iter()
+        .min_by_key(|lightning| (lightning.time - peak_time).abs())
+        .map(|lightning| lightning.incidence(&signal.attitude, &position, &detector_axes));
+
+    Ok(Tgf {
         signal: signal.clone(),
         lightning: LightningInfo {
             associated: !lightnings.is_empty(),
@@ -51,9 +70,10 @@ fn associate(signal: &UnifiedSignal) -> Tgf {
                 TimeDelta::milliseconds(5),
                 Length::new::<uom::si::length::kilometer>(800.0),
                 TimeDelta::minutes(2),
-            ),
+            )?,
+            incidence,
         },
-    }
+    })
 }
 
 pub fn run() {
@@ -84,7 +104,10 @@ pub fn run() {
                         if i >= total {
                             break;
                         }
-                        local.push((i, associate(&signals_ref[i])));
+                        // 闪电库读不了时继续跑只会产出全“未关联”的假结果，直接中止
+                        let tgf = associate(&signals_ref[i])
+                            .unwrap_or_else(|e| panic!("filter: candidate {i}: {e}"));
+                        local.push((i, tgf));
                         let n = done.fetch_add(1, Ordering::Relaxed) + 1;
                         if n % 100_000 == 0 {
                             eprintln!("filter: {n}/{total}");